@@ -0,0 +1,567 @@
+use std::path::PathBuf;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_spl::associated_token::get_associated_token_address;
+use anyhow::{anyhow, bail, Result};
+use clap::{Args, Parser, Subcommand};
+use pump::state::{FinalizeStep, LaunchMode, PenaltyRecipient, ProtocolParams, UserContribution};
+use pump_cli::output;
+use pump_cli::spec::{parse_curve, parse_mode, parse_pause, paused_names};
+use pump_client::instructions::{self, FinalizeAccounts, LaunchParams};
+use pump_client::{fetch, pda};
+use serde_json::{json, Value};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::signature::{read_keypair_file, Keypair, Signature, Signer};
+use solana_sdk::transaction::Transaction;
+
+#[derive(Parser)]
+#[command(about = "Operate pump launches")]
+struct Cli {
+    /// RPC endpoint of the cluster.
+    #[arg(long, short = 'u', default_value = "http://127.0.0.1:8899")]
+    url: String,
+
+    /// Keypair that signs and pays. Defaults to the Solana CLI keypair.
+    #[arg(long, short = 'k')]
+    keypair: Option<PathBuf>,
+
+    /// Print results as JSON.
+    #[arg(long, global = true)]
+    json: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Create the protocol config; the keypair must be the program's upgrade
+    /// authority and becomes its admin.
+    InitConfig {
+        /// Receives the fee taken on finalize.
+        #[arg(long)]
+        fee_recipient: Pubkey,
+        /// Parameters left out keep the program's defaults.
+        #[command(flatten)]
+        params: ParamArgs,
+    },
+    /// Show the protocol config and any queued change.
+    Config,
+    /// Queue new protocol parameters behind the timelock; the keypair must be
+    /// the admin. Parameters left out keep their current values.
+    QueueConfig {
+        #[command(flatten)]
+        params: ParamArgs,
+    },
+    /// Apply the queued parameters once the timelock is over.
+    ExecuteConfig,
+    /// Drop the queued parameters; the keypair must be the admin.
+    CancelConfig,
+    /// Set the protocol's pause state; the keypair must be the config admin,
+    /// or the pause guardian when only adding to what is paused. Without
+    /// arguments everything is resumed. Refunds are never paused.
+    SetPause {
+        /// Stop every pausable instruction.
+        #[arg(long)]
+        all: bool,
+        /// Stop only these instructions, e.g. `contribute,finalize`.
+        #[arg(long, value_delimiter = ',')]
+        instructions: Vec<String>,
+    },
+    /// Nominate the next admin; the keypair must be the current one.
+    ProposeAdmin {
+        pending_admin: Pubkey,
+    },
+    /// Take over as admin; the keypair must be the nominated one.
+    AcceptAdmin,
+    /// Assign the fee admin and pause guardian; the keypair must be the admin.
+    SetRoles {
+        #[arg(long)]
+        fee_admin: Pubkey,
+        #[arg(long)]
+        pause_guardian: Pubkey,
+    },
+    /// Queue a new fee recipient behind the timelock; the keypair must be the
+    /// fee admin.
+    SetFeeRecipient {
+        fee_recipient: Pubkey,
+    },
+    /// Reserve tickers so no launch can use them; the keypair must be the
+    /// admin.
+    ReserveTickers {
+        #[arg(required = true)]
+        tickers: Vec<String>,
+    },
+    /// Lift ticker reservations; the keypair must be the admin.
+    ReleaseTickers {
+        #[arg(required = true)]
+        tickers: Vec<String>,
+    },
+    /// Create a launch; the keypair becomes its creator.
+    Create {
+        #[arg(long)]
+        name: String,
+        #[arg(long)]
+        ticker: String,
+        /// Total supply in base units.
+        #[arg(long)]
+        supply: u64,
+        #[arg(long, default_value_t = 9)]
+        decimals: u8,
+        /// Lamports the creator contributes up front, 0 for a sealed-bid or lottery launch.
+        #[arg(long)]
+        initial_contribution: u64,
+        /// `standard`, `sealed-bid:...`, `lottery:...` or `lbp:...`.
+        #[arg(long, default_value = "standard")]
+        mode: String,
+        /// `linear:...`, `exponential:...`, `sigmoid:...` or `constant-product:...`.
+        #[arg(long)]
+        curve: String,
+        /// Lamports the creator posts as a bond, returned on graduation.
+        #[arg(long, default_value_t = 0)]
+        bond: u64,
+    },
+    /// Contribute lamports to a launch.
+    Contribute {
+        token: Pubkey,
+        #[arg(long)]
+        amount: u64,
+    },
+    /// Refund the keypair's contributions to a launch that missed its target
+    /// or was cancelled.
+    Refund {
+        token: Pubkey,
+        /// Refund only this contribution account.
+        #[arg(long)]
+        contribution: Option<Pubkey>,
+    },
+    /// Withdraw lamports from the keypair's contributions while the sale runs,
+    /// less the protocol's early-exit penalty.
+    Withdraw {
+        token: Pubkey,
+        #[arg(long)]
+        amount: u64,
+    },
+    /// Push the refund deadline of the keypair's launch back, once.
+    Extend {
+        token: Pubkey,
+        /// Seconds to add to the deadline.
+        #[arg(long)]
+        by: i64,
+    },
+    /// Withdraw the keypair's contributions from a launch whose deadline was
+    /// extended, while the opt-out window is open.
+    OptOut {
+        token: Pubkey,
+    },
+    /// Cancel a launch. The keypair must be its creator, before anyone else
+    /// has contributed, or the protocol admin.
+    Cancel {
+        token: Pubkey,
+        /// Burn the unsold supply held for the launch.
+        #[arg(long)]
+        burn: bool,
+    },
+    /// Slash the bond of a launch left unfinalized past its grace period.
+    SlashBond {
+        token: Pubkey,
+    },
+    /// Run the remaining finalize steps: pay contributors and collect the fee,
+    /// open the Raydium pool, burn its LP tokens and graduate the launch.
+    /// Contributors are paid into their associated token accounts, in
+    /// batches; an interrupted run picks up where it stopped.
+    Finalize {
+        token: Pubkey,
+        /// OpenBook market the Raydium pool is opened on.
+        #[arg(long)]
+        market: Pubkey,
+    },
+    /// Settle a sealed bid or lottery tickets; anyone may settle another
+    /// wallet's position, which is paid to that wallet.
+    Claim {
+        token: Pubkey,
+        /// Owner of the position; defaults to the keypair.
+        #[arg(long)]
+        owner: Option<Pubkey>,
+    },
+    /// Show a launch, by address or by creator and ticker.
+    Inspect {
+        #[arg(required_unless_present = "ticker")]
+        token: Option<Pubkey>,
+        #[arg(long, requires = "ticker")]
+        creator: Option<Pubkey>,
+        #[arg(long)]
+        ticker: Option<String>,
+    },
+    /// List every contribution to a launch.
+    Contributors {
+        token: Pubkey,
+    },
+    /// Show a creator's launch history; defaults to the keypair's.
+    Profile {
+        creator: Option<Pubkey>,
+    },
+}
+
+/// Protocol parameters, each overriding the value it is applied to.
+#[derive(Args)]
+struct ParamArgs {
+    /// Lamports a creator pays to open a launch.
+    #[arg(long)]
+    launch_fee: Option<u64>,
+    /// Share of a successful raise, in basis points, taken on finalize.
+    #[arg(long)]
+    finalize_fee_bps: Option<u16>,
+    /// Lamports out of a successful raise paid to whoever finalizes it.
+    #[arg(long)]
+    crank_reward: Option<u64>,
+    /// Lamports a launch has to raise.
+    #[arg(long)]
+    target: Option<u64>,
+    /// Most a single position or bid can hold, in lamports.
+    #[arg(long)]
+    max_contribution: Option<u64>,
+    /// Highest opening valuation, in lamports, for a creator without a
+    /// graduated launch; 0 lifts the limit.
+    #[arg(long)]
+    max_unproven_valuation: Option<u64>,
+    /// Seconds before positions in a launch that missed its target become
+    /// refundable.
+    #[arg(long)]
+    refund_delay: Option<i64>,
+    /// Longest a creator may extend their refund deadline, in seconds.
+    #[arg(long)]
+    max_deadline_extension: Option<i64>,
+    /// Share of the launch fee, in basis points, returned to a creator who
+    /// cancels their own launch.
+    #[arg(long)]
+    cancel_rebate_bps: Option<u16>,
+    /// Penalty, in basis points, on contributions withdrawn mid-sale.
+    #[arg(long)]
+    withdraw_penalty_bps: Option<u16>,
+    /// Whether withdrawal penalties go to the remaining contributors instead
+    /// of the fee recipient.
+    #[arg(long)]
+    penalty_to_contributors: Option<bool>,
+    /// Share of a creator bond, in basis points, slashed to contributors
+    /// when the launch is abandoned.
+    #[arg(long)]
+    bond_slash_bps: Option<u16>,
+    /// Seconds a creator has to finalize once the target is reached before
+    /// the bond can be slashed.
+    #[arg(long)]
+    finalize_grace_period: Option<i64>,
+    /// Seconds a queued config change waits before it can be executed.
+    #[arg(long)]
+    config_delay: Option<i64>,
+    /// Program that owns the randomness accounts lotteries draw from.
+    #[arg(long)]
+    randomness_program: Option<Pubkey>,
+}
+
+impl ParamArgs {
+    fn apply(self, params: ProtocolParams) -> ProtocolParams {
+        ProtocolParams {
+            launch_fee: self.launch_fee.unwrap_or(params.launch_fee),
+            finalize_fee_bps: self.finalize_fee_bps.unwrap_or(params.finalize_fee_bps),
+            fee_recipient: params.fee_recipient,
+            crank_reward: self.crank_reward.unwrap_or(params.crank_reward),
+            target: self.target.unwrap_or(params.target),
+            max_contribution: self.max_contribution.unwrap_or(params.max_contribution),
+            max_unproven_valuation: self.max_unproven_valuation.unwrap_or(params.max_unproven_valuation),
+            refund_delay: self.refund_delay.unwrap_or(params.refund_delay),
+            max_deadline_extension: self.max_deadline_extension.unwrap_or(params.max_deadline_extension),
+            cancel_rebate_bps: self.cancel_rebate_bps.unwrap_or(params.cancel_rebate_bps),
+            withdraw_penalty_bps: self.withdraw_penalty_bps.unwrap_or(params.withdraw_penalty_bps),
+            withdraw_penalty_recipient: match self.penalty_to_contributors {
+                Some(true) => PenaltyRecipient::Contributors,
+                Some(false) => PenaltyRecipient::Treasury,
+                None => params.withdraw_penalty_recipient,
+            },
+            bond_slash_bps: self.bond_slash_bps.unwrap_or(params.bond_slash_bps),
+            finalize_grace_period: self.finalize_grace_period.unwrap_or(params.finalize_grace_period),
+            config_delay: self.config_delay.unwrap_or(params.config_delay),
+            randomness_program: self.randomness_program.unwrap_or(params.randomness_program),
+        }
+    }
+}
+
+struct Context {
+    client: RpcClient,
+    payer: Keypair,
+}
+
+impl Context {
+    fn send(&self, instructions: &[Instruction]) -> Result<Signature> {
+        let blockhash = self.client.get_latest_blockhash()?;
+        let tx = Transaction::new_signed_with_payer(instructions, Some(&self.payer.pubkey()), &[&self.payer], blockhash);
+        Ok(self.client.send_and_confirm_transaction(&tx)?)
+    }
+
+    /// `owner`'s contributions to `token` that still hold lamports.
+    fn open_contributions(&self, token: &Pubkey, owner: &Pubkey) -> Result<Vec<(Pubkey, UserContribution)>> {
+        Ok(fetch::contributions(&self.client, token)?
+            .into_iter()
+            .filter(|(_, contribution)| contribution.user == *owner && contribution.amount > 0)
+            .collect())
+    }
+}
+
+/// Positions paid per `finalize_settle` transaction, small enough for each
+/// position and its recipient to fit alongside the other accounts.
+const FINALIZE_BATCH: usize = 8;
+
+fn default_keypair() -> Result<PathBuf> {
+    let home = std::env::var_os("HOME").ok_or_else(|| anyhow!("HOME is not set; pass --keypair"))?;
+    Ok(PathBuf::from(home).join(".config/solana/id.json"))
+}
+
+fn run(command: Command, ctx: &Context) -> Result<Value> {
+    let payer = ctx.payer.pubkey();
+
+    let value = match command {
+        Command::InitConfig { fee_recipient, params } => {
+            let params = ProtocolParams { fee_recipient, ..params.apply(ProtocolParams::default()) };
+            let signature = ctx.send(&[instructions::initialize_config(&payer, params)])?;
+            json!({ "signature": signature.to_string(), "config": pda::config().0.to_string() })
+        }
+        Command::Config => output::config(&fetch::config(&ctx.client)?),
+        Command::QueueConfig { params } => {
+            let params = params.apply(fetch::config(&ctx.client)?.params);
+            let signature = ctx.send(&[instructions::queue_config_change(&payer, params)])?;
+            let config = fetch::config(&ctx.client)?;
+            json!({ "signature": signature.to_string(), "eta": config.pending_change.map(|change| change.eta) })
+        }
+        Command::ExecuteConfig => {
+            json!({ "signature": ctx.send(&[instructions::execute_config_change()])?.to_string() })
+        }
+        Command::CancelConfig => {
+            json!({ "signature": ctx.send(&[instructions::cancel_config_change(&payer)])?.to_string() })
+        }
+        Command::SetPause { all, instructions: names } => {
+            let bits = parse_pause(&names)?;
+            let signature = ctx.send(&[instructions::set_pause(&payer, all, bits)])?;
+            json!({ "signature": signature.to_string(), "paused": all, "paused_instructions": paused_names(bits) })
+        }
+        Command::ProposeAdmin { pending_admin } => {
+            json!({ "signature": ctx.send(&[instructions::propose_admin(&payer, &pending_admin)])?.to_string() })
+        }
+        Command::AcceptAdmin => {
+            json!({ "signature": ctx.send(&[instructions::accept_admin(&payer)])?.to_string() })
+        }
+        Command::SetRoles { fee_admin, pause_guardian } => {
+            let ix = instructions::set_roles(&payer, &fee_admin, &pause_guardian);
+            json!({ "signature": ctx.send(&[ix])?.to_string() })
+        }
+        Command::SetFeeRecipient { fee_recipient } => {
+            let signature = ctx.send(&[instructions::set_fee_recipient(&payer, &fee_recipient)])?;
+            let config = fetch::config(&ctx.client)?;
+            json!({ "signature": signature.to_string(), "eta": config.pending_change.map(|change| change.eta) })
+        }
+        Command::ReserveTickers { tickers } => {
+            let ixs: Vec<_> = tickers.iter().map(|ticker| instructions::reserve_ticker(&payer, ticker)).collect();
+            json!({ "signature": ctx.send(&ixs)?.to_string() })
+        }
+        Command::ReleaseTickers { tickers } => {
+            let ixs: Vec<_> = tickers.iter().map(|ticker| instructions::release_ticker(&payer, ticker)).collect();
+            json!({ "signature": ctx.send(&ixs)?.to_string() })
+        }
+        Command::Create { name, ticker, supply, decimals, initial_contribution, mode, curve, bond } => {
+            let ix = instructions::initialize(&payer, LaunchParams {
+                name,
+                ticker: ticker.clone(),
+                total_supply: supply,
+                initial_target: initial_contribution,
+                decimals,
+                mode: parse_mode(&mode)?,
+                curve: parse_curve(&curve)?,
+                bond,
+            });
+            let signature = ctx.send(&[ix])?;
+            json!({
+                "signature": signature.to_string(),
+                "token": pda::token(&payer, &ticker).0.to_string(),
+                "mint": pda::mint(&payer, &ticker).0.to_string(),
+            })
+        }
+        Command::Contribute { token, amount } => {
+            let launch = fetch::launch(&ctx.client, &token)?;
+            let number = launch.contribution_count;
+            let signature = ctx.send(&[instructions::contribute(&payer, &token, number, amount)])?;
+            json!({
+                "signature": signature.to_string(),
+                "contribution": pda::user_contribution(&payer, &token, number).0.to_string(),
+            })
+        }
+        Command::Refund { token, contribution } => {
+            let contributions = match contribution {
+                Some(address) => vec![address],
+                None => ctx.open_contributions(&token, &payer)?.into_iter().map(|(address, _)| address).collect(),
+            };
+            if contributions.is_empty() {
+                bail!("{payer} has nothing to refund from {token}");
+            }
+            let launch = fetch::launch(&ctx.client, &token)?;
+            let ixs: Vec<Instruction> = contributions.iter()
+                .map(|address| instructions::refund(&payer, &token, &launch.creator, &launch.ticker, address))
+                .collect();
+            json!({ "signature": ctx.send(&ixs)?.to_string(), "refunded": contributions.len() })
+        }
+        Command::Withdraw { token, amount } => {
+            let fee_recipient = fetch::config(&ctx.client)?.params.fee_recipient;
+            // Drain positions in order until the amount is covered.
+            let mut left = amount;
+            let mut ixs = Vec::new();
+            for (address, contribution) in ctx.open_contributions(&token, &payer)? {
+                if left == 0 {
+                    break;
+                }
+                let take = left.min(contribution.amount);
+                ixs.push(instructions::withdraw_contribution(&payer, &token, &address, &fee_recipient, take));
+                left -= take;
+            }
+            if left > 0 {
+                bail!("{payer} has only {} lamports in {token}", amount - left);
+            }
+            json!({ "signature": ctx.send(&ixs)?.to_string(), "withdrawn": amount })
+        }
+        Command::Extend { token, by } => {
+            let signature = ctx.send(&[instructions::extend_deadline(&payer, &token, by)])?;
+            let launch = fetch::launch(&ctx.client, &token)?;
+            json!({
+                "signature": signature.to_string(),
+                "refund_deadline": launch.refund_deadline,
+                "opt_out_deadline": launch.opt_out_deadline,
+            })
+        }
+        Command::OptOut { token } => {
+            let contributions = ctx.open_contributions(&token, &payer)?;
+            if contributions.is_empty() {
+                bail!("{payer} has nothing to withdraw from {token}");
+            }
+            let ixs: Vec<Instruction> = contributions.iter()
+                .map(|(address, _)| instructions::opt_out(&payer, &token, address))
+                .collect();
+            json!({ "signature": ctx.send(&ixs)?.to_string(), "withdrawn": ixs.len() })
+        }
+        Command::Cancel { token, burn } => {
+            let launch = fetch::launch(&ctx.client, &token)?;
+            let ix = instructions::cancel_launch(&payer, &token, &launch.creator, &launch.mint, &launch.ticker, burn);
+            json!({ "signature": ctx.send(&[ix])?.to_string() })
+        }
+        Command::SlashBond { token } => {
+            let launch = fetch::launch(&ctx.client, &token)?;
+            let ix = instructions::slash_bond(&token, &launch.creator, &launch.ticker);
+            json!({ "signature": ctx.send(&[ix])?.to_string() })
+        }
+        Command::Finalize { token, market } => {
+            let launch = fetch::launch(&ctx.client, &token)?;
+            // Only standard launches pay contributors out on finalize.
+            let positions: Vec<(Pubkey, Pubkey)> = match launch.mode {
+                LaunchMode::Standard => fetch::contributions(&ctx.client, &token)?
+                    .into_iter()
+                    .filter(|(_, contribution)| contribution.amount > 0 && !contribution.settled)
+                    .map(|(address, contribution)| {
+                        (address, get_associated_token_address(&contribution.user, &launch.mint))
+                    })
+                    .collect(),
+                _ => vec![],
+            };
+            let mut accounts = FinalizeAccounts {
+                token,
+                creator: launch.creator,
+                ticker: launch.ticker.clone(),
+                cranker: payer,
+                mint: launch.mint,
+                fee_recipient: fetch::config(&ctx.client)?.params.fee_recipient,
+                market,
+                positions: vec![],
+                mode: launch.mode,
+            };
+            // Steps that already ran are skipped on chain, so every one is sent.
+            let mut signatures = vec![];
+            let mut batches: Vec<&[(Pubkey, Pubkey)]> = positions.chunks(FINALIZE_BATCH).collect();
+            if batches.is_empty() {
+                batches.push(&[]);
+            }
+            for batch in batches {
+                accounts.positions = batch.to_vec();
+                signatures.push(ctx.send(&[instructions::finalize_settle(&accounts)])?);
+            }
+            // Settling leaves a launch short of its target as it was.
+            if fetch::launch(&ctx.client, &token)?.finalize_step == FinalizeStep::Pending {
+                bail!("the launch has not reached its target; it stays virtual");
+            }
+            for ix in [
+                instructions::finalize_create_pool(&accounts),
+                instructions::finalize_lock_lp(&accounts),
+                instructions::finalize_complete(&accounts),
+            ] {
+                signatures.push(ctx.send(&[ix])?);
+            }
+            let signatures: Vec<String> = signatures.iter().map(Signature::to_string).collect();
+            json!({ "signatures": signatures })
+        }
+        Command::Claim { token, owner } => {
+            let owner = owner.unwrap_or(payer);
+            let launch = fetch::launch(&ctx.client, &token)?;
+            let ixs = match launch.mode {
+                LaunchMode::SealedBid { .. } => vec![instructions::settle_bid(&payer, &owner, &token, &launch.mint)],
+                LaunchMode::Lottery { .. } => ctx.open_contributions(&token, &owner)?
+                    .into_iter()
+                    // The creator's opening contribution is keyed by ticker
+                    // and cannot hold tickets.
+                    .filter(|(address, contribution)| {
+                        *address == pda::user_contribution(&owner, &token, contribution.contribution_number).0
+                            && contribution.ticket_count > 0
+                    })
+                    .map(|(_, contribution)| {
+                        instructions::settle_tickets(&payer, &owner, &token, &launch.mint, contribution.contribution_number)
+                    })
+                    .collect(),
+                _ => bail!("standard and bootstrapping launches pay out on finalize; there is nothing to claim"),
+            };
+            if ixs.is_empty() {
+                bail!("{owner} has no tickets to settle in {token}");
+            }
+            json!({ "signature": ctx.send(&ixs)?.to_string(), "settled": ixs.len() })
+        }
+        Command::Inspect { token, creator, ticker } => {
+            let address = match (token, ticker) {
+                (Some(token), _) => token,
+                (None, Some(ticker)) => pda::token(&creator.unwrap_or(payer), &ticker).0,
+                (None, None) => unreachable!("clap requires a token or a ticker"),
+            };
+            output::launch(&address, &fetch::launch(&ctx.client, &address)?)
+        }
+        Command::Contributors { token } => Value::Array(
+            fetch::contributions(&ctx.client, &token)?
+                .iter()
+                .map(|(address, contribution)| output::contribution(address, contribution))
+                .collect(),
+        ),
+        Command::Profile { creator } => {
+            output::profile(&fetch::creator_profile(&ctx.client, &creator.unwrap_or(payer))?)
+        }
+    };
+    Ok(value)
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let keypair = match cli.keypair {
+        Some(path) => path,
+        None => default_keypair()?,
+    };
+    let ctx = Context {
+        client: RpcClient::new_with_commitment(cli.url, CommitmentConfig::confirmed()),
+        payer: read_keypair_file(&keypair)
+            .map_err(|error| anyhow!("reading keypair {}: {error}", keypair.display()))?,
+    };
+
+    let value = run(cli.command, &ctx)?;
+    output::print(&value, cli.json);
+    Ok(())
+}
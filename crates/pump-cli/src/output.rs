@@ -0,0 +1,216 @@
+//! Command results. Every command produces a JSON value, printed as is with
+//! `--json` or as aligned `key: value` lines otherwise.
+
+use anchor_lang::prelude::Pubkey;
+use pump::state::{
+    Config, CreatorProfile, FinalizeStep, LaunchMode, LaunchStatus, LaunchTerms, PenaltyRecipient, ProtocolParams, TokenDetails, UserContribution,
+};
+use pump::utils::Curve;
+use serde_json::{json, Map, Value};
+
+pub fn mode(mode: &LaunchMode) -> Value {
+    match *mode {
+        LaunchMode::Standard => json!({ "kind": "standard" }),
+        LaunchMode::SealedBid { commit_duration, reveal_duration, penalty_bps, min_price, price_step } => json!({
+            "kind": "sealed-bid",
+            "commit_duration": commit_duration,
+            "reveal_duration": reveal_duration,
+            "penalty_bps": penalty_bps,
+            "min_price": min_price,
+            "price_step": price_step,
+        }),
+        LaunchMode::Lottery { ticket_price, winning_tickets, sale_duration, randomness_account } => json!({
+            "kind": "lottery",
+            "ticket_price": ticket_price,
+            "winning_tickets": winning_tickets,
+            "sale_duration": sale_duration,
+            "randomness_account": randomness_account.to_string(),
+        }),
+        LaunchMode::Lbp { start_weight_bps, end_weight_bps, duration } => json!({
+            "kind": "lbp",
+            "start_weight_bps": start_weight_bps,
+            "end_weight_bps": end_weight_bps,
+            "duration": duration,
+        }),
+    }
+}
+
+pub fn curve(curve: &Curve) -> Value {
+    match *curve {
+        Curve::Linear { base_price, slope } => json!({ "kind": "linear", "base_price": base_price, "slope": slope }),
+        Curve::Exponential { base_price, growth } => {
+            json!({ "kind": "exponential", "base_price": base_price, "growth": growth })
+        }
+        Curve::Sigmoid { max_price, midpoint, width } => {
+            json!({ "kind": "sigmoid", "max_price": max_price, "midpoint": midpoint, "width": width })
+        }
+        Curve::ConstantProduct { virtual_sol, virtual_tokens } => {
+            json!({ "kind": "constant-product", "virtual_sol": virtual_sol, "virtual_tokens": virtual_tokens })
+        }
+    }
+}
+
+pub fn params(params: &ProtocolParams) -> Value {
+    json!({
+        "launch_fee": params.launch_fee,
+        "finalize_fee_bps": params.finalize_fee_bps,
+        "fee_recipient": params.fee_recipient.to_string(),
+        "crank_reward": params.crank_reward,
+        "target": params.target,
+        "max_contribution": params.max_contribution,
+        "max_unproven_valuation": params.max_unproven_valuation,
+        "refund_delay": params.refund_delay,
+        "max_deadline_extension": params.max_deadline_extension,
+        "cancel_rebate_bps": params.cancel_rebate_bps,
+        "withdraw_penalty_bps": params.withdraw_penalty_bps,
+        "penalty_to_contributors": params.withdraw_penalty_recipient == PenaltyRecipient::Contributors,
+        "bond_slash_bps": params.bond_slash_bps,
+        "finalize_grace_period": params.finalize_grace_period,
+        "config_delay": params.config_delay,
+        "randomness_program": params.randomness_program.to_string(),
+    })
+}
+
+fn terms(terms: &LaunchTerms) -> Value {
+    json!({
+        "launch_fee": terms.launch_fee,
+        "finalize_fee_bps": terms.finalize_fee_bps,
+        "crank_reward": terms.crank_reward,
+        "max_contribution": terms.max_contribution,
+        "max_deadline_extension": terms.max_deadline_extension,
+        "cancel_rebate_bps": terms.cancel_rebate_bps,
+        "withdraw_penalty_bps": terms.withdraw_penalty_bps,
+        "penalty_to_contributors": terms.withdraw_penalty_recipient == PenaltyRecipient::Contributors,
+        "bond_slash_bps": terms.bond_slash_bps,
+        "finalize_grace_period": terms.finalize_grace_period,
+    })
+}
+
+pub fn config(config: &Config) -> Value {
+    json!({
+        "admin": config.admin.to_string(),
+        "pending_admin": (config.pending_admin != Pubkey::default()).then(|| config.pending_admin.to_string()),
+        "fee_admin": config.fee_admin.to_string(),
+        "pause_guardian": config.pause_guardian.to_string(),
+        "params": params(&config.params),
+        "pending_change": config.pending_change.map(|change| json!({
+            "params": params(&change.params),
+            "eta": change.eta,
+        })),
+        "paused": config.paused,
+        "paused_instructions": config.paused_instructions,
+    })
+}
+
+pub fn launch(address: &Pubkey, launch: &TokenDetails) -> Value {
+    json!({
+        "address": address.to_string(),
+        "name": launch.name,
+        "ticker": launch.ticker,
+        "creator": launch.creator.to_string(),
+        "mint": launch.mint.to_string(),
+        "decimals": launch.decimals,
+        "total_supply": launch.total_supply,
+        "target": launch.target,
+        "total_contributed": launch.total_contributed,
+        "tokens_sold": launch.tokens_sold,
+        "refundable": launch.refundable,
+        "contribution_count": launch.contribution_count,
+        "tickets_sold": launch.tickets_sold,
+        "creation_time": launch.creation_time,
+        "refund_deadline": launch.refund_deadline,
+        "extended_at": launch.extended_at,
+        "opt_out_deadline": launch.opt_out_deadline,
+        "opted_out": launch.opted_out,
+        "withdrawal_penalties": launch.withdrawal_penalties,
+        "bond": launch.bond,
+        "target_reached_at": launch.target_reached_at,
+        "terms": terms(&launch.terms),
+        "finalized": !launch.is_virtual,
+        "finalize_step": finalize_step(launch.finalize_step),
+        "contributions_settled": launch.contributions_settled,
+        "open_positions": launch.open_positions,
+        "market": (launch.finalize_step >= FinalizeStep::PoolCreated).then(|| launch.market.to_string()),
+        "cancelled": launch.status == LaunchStatus::Cancelled,
+        "failed": launch.status == LaunchStatus::Failed,
+        "mode": mode(&launch.mode),
+        "curve": curve(&launch.curve),
+    })
+}
+
+fn finalize_step(step: FinalizeStep) -> &'static str {
+    match step {
+        FinalizeStep::Pending => "pending",
+        FinalizeStep::Settling => "settling",
+        FinalizeStep::Settled => "settled",
+        FinalizeStep::PoolCreated => "pool_created",
+        FinalizeStep::LpLocked => "lp_locked",
+        FinalizeStep::Complete => "complete",
+    }
+}
+
+pub fn profile(profile: &CreatorProfile) -> Value {
+    json!({
+        "creator": profile.creator.to_string(),
+        "launches": profile.launches,
+        "graduations": profile.graduations,
+        "cancellations": profile.cancellations,
+        "failures": profile.failures,
+        "total_raised": profile.total_raised,
+    })
+}
+
+pub fn contribution(address: &Pubkey, contribution: &UserContribution) -> Value {
+    json!({
+        "address": address.to_string(),
+        "user": contribution.user.to_string(),
+        "contribution_number": contribution.contribution_number,
+        "amount": contribution.amount,
+        "tokens": contribution.total_tokens,
+        "first_ticket": contribution.first_ticket,
+        "ticket_count": contribution.ticket_count,
+        "opted_out": contribution.opted_out,
+        "settled": contribution.settled,
+        "timestamp": contribution.timestamp,
+    })
+}
+
+pub fn print(value: &Value, json: bool) {
+    if json {
+        println!("{value:#}");
+        return;
+    }
+    match value {
+        Value::Array(items) => {
+            for (index, item) in items.iter().enumerate() {
+                if index > 0 {
+                    println!();
+                }
+                print(item, false);
+            }
+        }
+        Value::Object(fields) => print_fields(fields),
+        other => println!("{}", plain(other)),
+    }
+}
+
+fn print_fields(fields: &Map<String, Value>) {
+    let width = fields.keys().map(String::len).max().unwrap_or_default();
+    for (key, value) in fields {
+        println!("{key:width$}  {}", plain(value));
+    }
+}
+
+fn plain(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        Value::Object(fields) if fields.contains_key("kind") => {
+            let params: Vec<String> = fields.iter()
+                .filter(|(key, _)| *key != "kind")
+                .map(|(key, value)| format!("{key}={}", plain(value)))
+                .collect();
+            format!("{} {}", plain(&fields["kind"]), params.join(" ")).trim_end().to_string()
+        }
+        other => other.to_string(),
+    }
+}
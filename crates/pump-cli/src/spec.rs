@@ -0,0 +1,146 @@
+//! Compact `kind:arg:arg` specs for the enum arguments of `initialize`, and
+//! the instruction names `set_pause` takes.
+
+use std::str::FromStr;
+
+use anchor_lang::prelude::Pubkey;
+use anyhow::{anyhow, bail, Context, Result};
+use pump::state::{pause, LaunchMode};
+use pump::utils::Curve;
+
+fn split(spec: &str) -> (&str, Vec<&str>) {
+    let mut parts = spec.split(':');
+    let kind = parts.next().unwrap_or_default();
+    (kind, parts.collect())
+}
+
+fn args<const N: usize>(kind: &str, args: &[&str], usage: &str) -> Result<[String; N]> {
+    let args: [&str; N] = args.try_into()
+        .map_err(|_| anyhow!("`{kind}` takes {N} arguments: {kind}:{usage}"))?;
+    Ok(args.map(str::to_string))
+}
+
+fn number<T: FromStr>(name: &str, value: &str) -> Result<T>
+where
+    T::Err: std::error::Error + Send + Sync + 'static,
+{
+    value.parse().with_context(|| format!("invalid {name} `{value}`"))
+}
+
+/// Parses `linear:BASE:SLOPE`, `exponential:BASE:GROWTH`,
+/// `sigmoid:MAX:MIDPOINT:WIDTH` or `constant-product:SOL:TOKENS`.
+pub fn parse_curve(spec: &str) -> Result<Curve> {
+    let (kind, rest) = split(spec);
+    let curve = match kind {
+        "linear" => {
+            let [base_price, slope] = args(kind, &rest, "BASE_PRICE:SLOPE")?;
+            Curve::Linear {
+                base_price: number("base price", &base_price)?,
+                slope: number("slope", &slope)?,
+            }
+        }
+        "exponential" => {
+            let [base_price, growth] = args(kind, &rest, "BASE_PRICE:GROWTH")?;
+            Curve::Exponential {
+                base_price: number("base price", &base_price)?,
+                growth: number("growth", &growth)?,
+            }
+        }
+        "sigmoid" => {
+            let [max_price, midpoint, width] = args(kind, &rest, "MAX_PRICE:MIDPOINT:WIDTH")?;
+            Curve::Sigmoid {
+                max_price: number("max price", &max_price)?,
+                midpoint: number("midpoint", &midpoint)?,
+                width: number("width", &width)?,
+            }
+        }
+        "constant-product" => {
+            let [virtual_sol, virtual_tokens] = args(kind, &rest, "VIRTUAL_SOL:VIRTUAL_TOKENS")?;
+            Curve::ConstantProduct {
+                virtual_sol: number("virtual SOL", &virtual_sol)?,
+                virtual_tokens: number("virtual tokens", &virtual_tokens)?,
+            }
+        }
+        _ => bail!("unknown curve `{kind}`, expected linear, exponential, sigmoid or constant-product"),
+    };
+    Ok(curve)
+}
+
+/// Parses `standard`,
+/// `sealed-bid:COMMIT_SECS:REVEAL_SECS:PENALTY_BPS:MIN_PRICE:PRICE_STEP`,
+/// `lottery:TICKET_PRICE:WINNERS:SALE_SECS:RANDOMNESS_ACCOUNT`
+/// or `lbp:START_WEIGHT_BPS:END_WEIGHT_BPS:DURATION_SECS`.
+pub fn parse_mode(spec: &str) -> Result<LaunchMode> {
+    let (kind, rest) = split(spec);
+    let mode = match kind {
+        "standard" => {
+            let [] = args(kind, &rest, "")?;
+            LaunchMode::Standard
+        }
+        "sealed-bid" => {
+            let [commit, reveal, penalty, min_price, step] =
+                args(kind, &rest, "COMMIT_SECS:REVEAL_SECS:PENALTY_BPS:MIN_PRICE:PRICE_STEP")?;
+            LaunchMode::SealedBid {
+                commit_duration: number("commit duration", &commit)?,
+                reveal_duration: number("reveal duration", &reveal)?,
+                penalty_bps: number("penalty", &penalty)?,
+                min_price: number("min price", &min_price)?,
+                price_step: number("price step", &step)?,
+            }
+        }
+        "lottery" => {
+            let [price, winners, duration, account] =
+                args(kind, &rest, "TICKET_PRICE:WINNERS:SALE_SECS:RANDOMNESS_ACCOUNT")?;
+            LaunchMode::Lottery {
+                ticket_price: number("ticket price", &price)?,
+                winning_tickets: number("winning tickets", &winners)?,
+                sale_duration: number("sale duration", &duration)?,
+                randomness_account: Pubkey::from_str(&account)
+                    .with_context(|| format!("invalid randomness account `{account}`"))?,
+            }
+        }
+        "lbp" => {
+            let [start, end, duration] = args(kind, &rest, "START_WEIGHT_BPS:END_WEIGHT_BPS:DURATION_SECS")?;
+            LaunchMode::Lbp {
+                start_weight_bps: number("start weight", &start)?,
+                end_weight_bps: number("end weight", &end)?,
+                duration: number("duration", &duration)?,
+            }
+        }
+        _ => bail!("unknown mode `{kind}`, expected standard, sealed-bid, lottery or lbp"),
+    };
+    Ok(mode)
+}
+
+const PAUSABLE: [(&str, u32); 14] = [
+    ("initialize", pause::INITIALIZE),
+    ("contribute", pause::CONTRIBUTE),
+    ("withdraw-contribution", pause::WITHDRAW_CONTRIBUTION),
+    ("extend-deadline", pause::EXTEND_DEADLINE),
+    ("finalize", pause::FINALIZE),
+    ("commit-bid", pause::COMMIT_BID),
+    ("reveal-bid", pause::REVEAL_BID),
+    ("settle-bid", pause::SETTLE_BID),
+    ("close-lottery", pause::CLOSE_LOTTERY),
+    ("draw-lottery", pause::DRAW_LOTTERY),
+    ("settle-tickets", pause::SETTLE_TICKETS),
+    ("start-lbp", pause::START_LBP),
+    ("buy", pause::BUY),
+    ("sell", pause::SELL),
+];
+
+/// Parses instruction names such as `contribute` or `finalize` into `pause`
+/// bits.
+pub fn parse_pause(names: &[String]) -> Result<u32> {
+    names.iter().try_fold(0, |bits, name| {
+        let (_, bit) = PAUSABLE.iter()
+            .find(|(pausable, _)| pausable == name)
+            .ok_or_else(|| anyhow!("`{name}` cannot be paused"))?;
+        Ok(bits | bit)
+    })
+}
+
+/// Names of the instructions set in `bits`.
+pub fn paused_names(bits: u32) -> Vec<&'static str> {
+    PAUSABLE.iter().filter(|(_, bit)| bits & bit != 0).map(|(name, _)| *name).collect()
+}
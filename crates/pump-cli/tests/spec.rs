@@ -0,0 +1,68 @@
+use anchor_lang::prelude::Pubkey;
+use pump::state::LaunchMode;
+use pump::utils::Curve;
+use pump_cli::output;
+use pump_cli::spec::{parse_curve, parse_mode, parse_pause, paused_names};
+
+#[test]
+fn parses_curves() {
+    assert_eq!(parse_curve("linear:1000:5").unwrap(), Curve::Linear { base_price: 1_000, slope: 5 });
+    assert_eq!(
+        parse_curve("sigmoid:100:50:10").unwrap(),
+        Curve::Sigmoid { max_price: 100, midpoint: 50, width: 10 }
+    );
+    assert_eq!(
+        parse_curve("constant-product:30000000000:1073000000000000").unwrap(),
+        Curve::ConstantProduct { virtual_sol: 30_000_000_000, virtual_tokens: 1_073_000_000_000_000 }
+    );
+
+    assert!(parse_curve("linear:1000").is_err());
+    assert!(parse_curve("linear:1000:-5").is_err());
+    assert!(parse_curve("quadratic:1:2").is_err());
+}
+
+#[test]
+fn parses_modes() {
+    assert_eq!(parse_mode("standard").unwrap(), LaunchMode::Standard);
+    assert_eq!(
+        parse_mode("lbp:9000:5000:3600").unwrap(),
+        LaunchMode::Lbp { start_weight_bps: 9_000, end_weight_bps: 5_000, duration: 3_600 }
+    );
+
+    let account = Pubkey::new_unique();
+    assert_eq!(
+        parse_mode(&format!("lottery:1000000:10:86400:{account}")).unwrap(),
+        LaunchMode::Lottery {
+            ticket_price: 1_000_000,
+            winning_tickets: 10,
+            sale_duration: 86_400,
+            randomness_account: account,
+        }
+    );
+
+    assert!(parse_mode("standard:1").is_err());
+    assert!(parse_mode("sealed-bid:60:60:100:1").is_err());
+    assert!(parse_mode("lottery:1:1:1:not-a-key").is_err());
+    assert!(parse_mode(&format!("lottery:1:1:1:{account}:{account}")).is_err());
+}
+
+#[test]
+fn renders_enum_arguments_with_their_kind() {
+    let mode = output::mode(&LaunchMode::Lbp { start_weight_bps: 9_000, end_weight_bps: 5_000, duration: 3_600 });
+    assert_eq!(mode["kind"], "lbp");
+    assert_eq!(mode["start_weight_bps"], 9_000);
+
+    let curve = output::curve(&Curve::Linear { base_price: 7, slope: 0 });
+    assert_eq!(curve, serde_json::json!({ "kind": "linear", "base_price": 7, "slope": 0 }));
+}
+
+#[test]
+fn parses_pause_names() {
+    let names = ["contribute".to_string(), "finalize".to_string()];
+    let bits = parse_pause(&names).unwrap();
+    assert_eq!(paused_names(bits), ["contribute", "finalize"]);
+    assert_eq!(parse_pause(&[]).unwrap(), 0);
+
+    // Refunds are never pausable.
+    assert!(parse_pause(&["refund".to_string()]).is_err());
+}
@@ -0,0 +1,50 @@
+//! Runs the binary against a local test validator with the program deployed
+//! at its declared id, paying with the Solana CLI keypair:
+//!
+//!     solana-test-validator --bpf-program <PROGRAM_ID> target/deploy/pump.so
+//!     cargo test -p pump-cli -- --ignored
+
+use std::process::Command;
+
+use serde_json::Value;
+
+fn cli(args: &[&str]) -> Value {
+    let url = std::env::var("PUMP_CLI_URL").unwrap_or_else(|_| "http://127.0.0.1:8899".to_string());
+    let output = Command::new(env!("CARGO_BIN_EXE_pump-cli"))
+        .args(["--url", &url, "--json"])
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+#[test]
+#[ignore = "needs a local validator with the program deployed"]
+fn creates_contributes_and_inspects_a_launch() {
+    // Tickers are per creator, so make one up per run.
+    let ticker = format!("T{}", std::process::id() % 100_000);
+    let created = cli(&[
+        "create",
+        "--name", "CLI test",
+        "--ticker", &ticker,
+        "--supply", "1000000000000000",
+        "--initial-contribution", "1000000000",
+        "--curve", "linear:200000000000000000:0",
+    ]);
+    let token = created["token"].as_str().unwrap();
+
+    let launch = cli(&["inspect", token]);
+    assert_eq!(launch["ticker"], ticker.as_str());
+    assert_eq!(launch["total_contributed"], 1_000_000_000);
+
+    let contributed = cli(&["contribute", token, "--amount", "500000000"]);
+    let contributors = cli(&["contributors", token]);
+    let addresses: Vec<&str> = contributors.as_array().unwrap().iter()
+        .map(|contribution| contribution["address"].as_str().unwrap())
+        .collect();
+    assert!(addresses.contains(&contributed["contribution"].as_str().unwrap()));
+
+    let launch = cli(&["inspect", "--ticker", &ticker]);
+    assert_eq!(launch["total_contributed"], 1_500_000_000);
+}
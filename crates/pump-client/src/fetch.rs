@@ -0,0 +1,93 @@
+//! Account fetchers over a blocking RPC client.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::{AccountDeserialize, Discriminator};
+use pump::state::{
+    BidBook, BidCommitment, Config, CreatorProfile, LiquidityPool, LotteryDraw, TokenDetails, UserContribution,
+};
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+
+use crate::{pda, Error, Result};
+
+/// Fetches `address` and deserializes it as a pump account of type `T`.
+pub fn account<T: AccountDeserialize>(client: &RpcClient, address: &Pubkey) -> Result<T> {
+    let data = client
+        .get_account_with_commitment(address, client.commitment())?
+        .value
+        .ok_or(Error::AccountNotFound(*address))?
+        .data;
+    deserialize(address, &data)
+}
+
+pub fn deserialize<T: AccountDeserialize>(address: &Pubkey, data: &[u8]) -> Result<T> {
+    T::try_deserialize(&mut &data[..]).map_err(|source| Error::InvalidAccount { address: *address, source: Box::new(source) })
+}
+
+pub fn config(client: &RpcClient) -> Result<Config> {
+    account(client, &pda::config().0)
+}
+
+pub fn launch(client: &RpcClient, token: &Pubkey) -> Result<TokenDetails> {
+    account(client, token)
+}
+
+pub fn creator_profile(client: &RpcClient, creator: &Pubkey) -> Result<CreatorProfile> {
+    account(client, &pda::creator_profile(creator).0)
+}
+
+/// The launch `creator` opened for `ticker`, with its address.
+pub fn launch_by_ticker(client: &RpcClient, creator: &Pubkey, ticker: &str) -> Result<(Pubkey, TokenDetails)> {
+    let address = pda::token(creator, ticker).0;
+    Ok((address, launch(client, &address)?))
+}
+
+pub fn contribution(client: &RpcClient, address: &Pubkey) -> Result<UserContribution> {
+    account(client, address)
+}
+
+/// Every contribution account recorded against `token`, refunded ones
+/// included, in contribution order.
+pub fn contributions(client: &RpcClient, token: &Pubkey) -> Result<Vec<(Pubkey, UserContribution)>> {
+    // Discriminator, then `user`, then `token`.
+    let token_offset = 8 + 32;
+    let config = RpcProgramAccountsConfig {
+        filters: Some(vec![
+            RpcFilterType::DataSize(UserContribution::ACCOUNT_SIZE as u64),
+            RpcFilterType::Memcmp(Memcmp::new_base58_encoded(0, &UserContribution::DISCRIMINATOR)),
+            RpcFilterType::Memcmp(Memcmp::new_base58_encoded(token_offset, token.as_ref())),
+        ]),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            commitment: Some(client.commitment()),
+            ..RpcAccountInfoConfig::default()
+        },
+        ..RpcProgramAccountsConfig::default()
+    };
+
+    let mut contributions = client
+        .get_program_accounts_with_config(&pump::ID, config)?
+        .into_iter()
+        .map(|(address, account)| Ok((address, deserialize::<UserContribution>(&address, &account.data)?)))
+        .collect::<Result<Vec<_>>>()?;
+    contributions.sort_by_key(|(_, contribution)| contribution.contribution_number);
+    Ok(contributions)
+}
+
+pub fn bid_book(client: &RpcClient, token: &Pubkey) -> Result<BidBook> {
+    account(client, &pda::bid_book(token).0)
+}
+
+pub fn bid(client: &RpcClient, token: &Pubkey, user: &Pubkey) -> Result<BidCommitment> {
+    account(client, &pda::bid(token, user).0)
+}
+
+pub fn lottery(client: &RpcClient, token: &Pubkey) -> Result<LotteryDraw> {
+    account(client, &pda::lottery(token).0)
+}
+
+pub fn liquidity_pool(client: &RpcClient, mint: &Pubkey) -> Result<LiquidityPool> {
+    account(client, &pda::liquidity_pool(mint).0)
+}
@@ -0,0 +1,692 @@
+//! Instruction builders. Each one fills in the program's full account list,
+//! deriving every PDA, so callers only pass the keys the program cannot
+//! derive itself.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::sysvar;
+use anchor_lang::{system_program, InstructionData, ToAccountMetas};
+use anchor_spl::associated_token::{self, get_associated_token_address};
+use anchor_spl::token::{self, spl_token::native_mint};
+use pump::state::{LaunchMode, ProtocolParams};
+use pump::utils::{raydium, Curve};
+use pump::{accounts, instruction};
+
+use crate::pda;
+
+fn build(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: pump::ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+fn event_authority() -> Pubkey {
+    pda::event_authority().0
+}
+
+/// Arguments of `initialize`. The creator's first buy is `initial_target`
+/// lamports, taken from `creator` along with the launch fee and the
+/// optional `bond`. Sealed-bid and lottery launches open without one, so
+/// it must be 0.
+#[derive(Clone, Debug)]
+pub struct LaunchParams {
+    pub name: String,
+    pub ticker: String,
+    pub total_supply: u64,
+    pub initial_target: u64,
+    pub decimals: u8,
+    pub mode: LaunchMode,
+    pub curve: Curve,
+    pub bond: u64,
+}
+
+pub fn initialize(creator: &Pubkey, params: LaunchParams) -> Instruction {
+    let mint = pda::mint(creator, &params.ticker).0;
+    build(
+        accounts::CreateToken {
+            token: pda::token(creator, &params.ticker).0,
+            config: pda::config().0,
+            mint,
+            token_account: pda::pool_token_account(&mint),
+            mint_authority: pda::mint_authority(&mint).0,
+            user: *creator,
+            program_account: pda::global().0,
+            user_contribution: pda::creator_contribution(creator, &params.ticker).0,
+            ticker_record: pda::ticker(&params.ticker).0,
+            creator_profile: pda::creator_profile(creator).0,
+            token_program: token::ID,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+            rent: sysvar::rent::ID,
+            event_authority: event_authority(),
+            program: pump::ID,
+        },
+        instruction::Initialize {
+            name: params.name,
+            ticker: params.ticker,
+            total_supply: params.total_supply,
+            initial_target: params.initial_target,
+            decimals: params.decimals,
+            mode: params.mode,
+            curve: params.curve,
+            bond: params.bond,
+        },
+    )
+}
+
+/// Creates the protocol config. `admin` must be the program's upgrade
+/// authority.
+pub fn initialize_config(admin: &Pubkey, params: ProtocolParams) -> Instruction {
+    build(
+        accounts::InitializeConfig {
+            config: pda::config().0,
+            admin: *admin,
+            program: pump::ID,
+            program_data: pda::program_data().0,
+            system_program: system_program::ID,
+        },
+        instruction::InitializeConfig { params },
+    )
+}
+
+/// Reserves `ticker`, in any case, against launches.
+pub fn reserve_ticker(admin: &Pubkey, ticker: &str) -> Instruction {
+    build(
+        accounts::ReserveTicker {
+            config: pda::config().0,
+            ticker_record: pda::ticker(ticker).0,
+            admin: *admin,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: pump::ID,
+        },
+        instruction::ReserveTicker { ticker: ticker.to_ascii_uppercase() },
+    )
+}
+
+pub fn release_ticker(admin: &Pubkey, ticker: &str) -> Instruction {
+    build(
+        accounts::ReleaseTicker {
+            config: pda::config().0,
+            ticker_record: pda::ticker(ticker).0,
+            admin: *admin,
+            event_authority: event_authority(),
+            program: pump::ID,
+        },
+        instruction::ReleaseTicker { ticker: ticker.to_ascii_uppercase() },
+    )
+}
+
+pub fn queue_config_change(admin: &Pubkey, params: ProtocolParams) -> Instruction {
+    build(
+        accounts::QueueConfigChange {
+            config: pda::config().0,
+            admin: *admin,
+            event_authority: event_authority(),
+            program: pump::ID,
+        },
+        instruction::QueueConfigChange { params },
+    )
+}
+
+/// Applies the queued change; anyone can send it once the timelock is over.
+pub fn execute_config_change() -> Instruction {
+    build(
+        accounts::ExecuteConfigChange {
+            config: pda::config().0,
+            event_authority: event_authority(),
+            program: pump::ID,
+        },
+        instruction::ExecuteConfigChange {},
+    )
+}
+
+pub fn cancel_config_change(admin: &Pubkey) -> Instruction {
+    build(
+        accounts::CancelConfigChange {
+            config: pda::config().0,
+            admin: *admin,
+            event_authority: event_authority(),
+            program: pump::ID,
+        },
+        instruction::CancelConfigChange {},
+    )
+}
+
+/// Sets the protocol's pause state. `authority` is the admin, or the pause
+/// guardian when it only adds to what is paused.
+pub fn set_pause(authority: &Pubkey, paused: bool, paused_instructions: u32) -> Instruction {
+    build(
+        accounts::SetPause {
+            config: pda::config().0,
+            authority: *authority,
+            event_authority: event_authority(),
+            program: pump::ID,
+        },
+        instruction::SetPause { paused, paused_instructions },
+    )
+}
+
+pub fn propose_admin(admin: &Pubkey, pending_admin: &Pubkey) -> Instruction {
+    build(
+        accounts::ProposeAdmin {
+            config: pda::config().0,
+            admin: *admin,
+            event_authority: event_authority(),
+            program: pump::ID,
+        },
+        instruction::ProposeAdmin { pending_admin: *pending_admin },
+    )
+}
+
+pub fn accept_admin(pending_admin: &Pubkey) -> Instruction {
+    build(
+        accounts::AcceptAdmin {
+            config: pda::config().0,
+            pending_admin: *pending_admin,
+            event_authority: event_authority(),
+            program: pump::ID,
+        },
+        instruction::AcceptAdmin {},
+    )
+}
+
+pub fn set_roles(admin: &Pubkey, fee_admin: &Pubkey, pause_guardian: &Pubkey) -> Instruction {
+    build(
+        accounts::SetRoles {
+            config: pda::config().0,
+            admin: *admin,
+            event_authority: event_authority(),
+            program: pump::ID,
+        },
+        instruction::SetRoles { fee_admin: *fee_admin, pause_guardian: *pause_guardian },
+    )
+}
+
+/// Queues `fee_recipient` behind the config timelock; it takes effect
+/// through `execute_config_change`.
+pub fn set_fee_recipient(fee_admin: &Pubkey, fee_recipient: &Pubkey) -> Instruction {
+    build(
+        accounts::SetFeeRecipient {
+            config: pda::config().0,
+            fee_admin: *fee_admin,
+            event_authority: event_authority(),
+            program: pump::ID,
+        },
+        instruction::SetFeeRecipient { fee_recipient: *fee_recipient },
+    )
+}
+
+/// `authority` is the launch's creator or the protocol admin; `creator`
+/// receives the fee rebate. The launch's `ticker` is released.
+pub fn cancel_launch(authority: &Pubkey, token: &Pubkey, creator: &Pubkey, mint: &Pubkey, ticker: &str, burn_supply: bool) -> Instruction {
+    build(
+        accounts::CancelLaunch {
+            token: *token,
+            config: pda::config().0,
+            creator_profile: pda::creator_profile(creator).0,
+            authority: *authority,
+            creator: *creator,
+            ticker_record: pda::ticker(ticker).0,
+            bid_book: pda::bid_book(token).0,
+            program_account: pda::global().0,
+            mint: *mint,
+            pool_token_account: pda::pool_token_account(mint),
+            token_program: token::ID,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: pump::ID,
+        },
+        instruction::CancelLaunch { burn_supply },
+    )
+}
+
+/// Fails a bonded launch left unfinalized past its grace period, releasing
+/// its `ticker`. Anyone can send it.
+pub fn slash_bond(token: &Pubkey, creator: &Pubkey, ticker: &str) -> Instruction {
+    build(
+        accounts::SlashBond {
+            token: *token,
+            creator_profile: pda::creator_profile(creator).0,
+            creator: *creator,
+            ticker_record: pda::ticker(ticker).0,
+            program_account: pda::global().0,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: pump::ID,
+        },
+        instruction::SlashBond {},
+    )
+}
+
+/// `contribution_number` is the launch's current `contribution_count`; the
+/// new contribution account is derived from it.
+pub fn contribute(user: &Pubkey, token: &Pubkey, contribution_number: u32, amount: u64) -> Instruction {
+    build(
+        accounts::Contribute {
+            token: *token,
+            config: pda::config().0,
+            user_contribution: pda::user_contribution(user, token, contribution_number).0,
+            user: *user,
+            program_account: pda::global().0,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: pump::ID,
+        },
+        instruction::Contribute { amount },
+    )
+}
+
+/// `creator` and `ticker` are the launch's; the refund that settles it as
+/// failed releases the ticker.
+pub fn refund(user: &Pubkey, token: &Pubkey, creator: &Pubkey, ticker: &str, user_contribution: &Pubkey) -> Instruction {
+    build(
+        accounts::Refund {
+            token: *token,
+            creator_profile: pda::creator_profile(creator).0,
+            creator: *creator,
+            ticker_record: pda::ticker(ticker).0,
+            user_contribution: *user_contribution,
+            user: *user,
+            program_account: pda::global().0,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: pump::ID,
+        },
+        instruction::Refund {},
+    )
+}
+
+/// Takes `amount` lamports back out of an open position. `fee_recipient` is
+/// the config's, which receives the penalty when it goes to the treasury.
+pub fn withdraw_contribution(
+    user: &Pubkey,
+    token: &Pubkey,
+    user_contribution: &Pubkey,
+    fee_recipient: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    build(
+        accounts::WithdrawContribution {
+            token: *token,
+            user_contribution: *user_contribution,
+            user: *user,
+            config: pda::config().0,
+            fee_recipient: *fee_recipient,
+            program_account: pda::global().0,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: pump::ID,
+        },
+        instruction::WithdrawContribution { amount },
+    )
+}
+
+/// Pushes the refund deadline back by `extension` seconds.
+pub fn extend_deadline(creator: &Pubkey, token: &Pubkey, extension: i64) -> Instruction {
+    build(
+        accounts::ExtendDeadline {
+            token: *token,
+            config: pda::config().0,
+            creator: *creator,
+            event_authority: event_authority(),
+            program: pump::ID,
+        },
+        instruction::ExtendDeadline { extension },
+    )
+}
+
+pub fn opt_out(user: &Pubkey, token: &Pubkey, user_contribution: &Pubkey) -> Instruction {
+    build(
+        accounts::OptOut {
+            token: *token,
+            user_contribution: *user_contribution,
+            user: *user,
+            program_account: pda::global().0,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: pump::ID,
+        },
+        instruction::OptOut {},
+    )
+}
+
+/// Accounts of the finalize steps that cannot be derived from the launch.
+/// The Raydium pool accounts are derived from the OpenBook `market` it opens on.
+#[derive(Clone, Debug)]
+pub struct FinalizeAccounts {
+    pub token: Pubkey,
+    pub creator: Pubkey,
+    /// The launch's ticker, released if a sealed-bid or lottery launch
+    /// settles below its target.
+    pub ticker: String,
+    /// Signs every step and collects its share of the crank reward.
+    pub cranker: Pubkey,
+    pub mint: Pubkey,
+    /// The protocol config's `fee_recipient`.
+    pub fee_recipient: Pubkey,
+    pub market: Pubkey,
+    /// Open positions to pay out, each with the token account of its owner
+    /// that receives its tokens. A batch may leave positions for later.
+    pub positions: Vec<(Pubkey, Pubkey)>,
+    /// Decides whether the launch's pool, bid book or lottery is passed along.
+    pub mode: LaunchMode,
+}
+
+/// Every finalize step, in order. A launch with many contributors needs
+/// `finalize_settle` sent in several batches instead.
+pub fn finalize(launch: &FinalizeAccounts) -> Vec<Instruction> {
+    vec![
+        finalize_settle(launch),
+        finalize_create_pool(launch),
+        finalize_lock_lp(launch),
+        finalize_complete(launch),
+    ]
+}
+
+pub fn finalize_settle(launch: &FinalizeAccounts) -> Instruction {
+    let mut ix = build(
+        accounts::FinalizeSettle {
+            token: launch.token,
+            config: pda::config().0,
+            creator_profile: pda::creator_profile(&launch.creator).0,
+            creator: launch.creator,
+            ticker_record: matches!(launch.mode, LaunchMode::SealedBid { .. } | LaunchMode::Lottery { .. })
+                .then(|| pda::ticker(&launch.ticker).0),
+            program_account: pda::global().0,
+            kom_wallet: launch.fee_recipient,
+            cranker: launch.cranker,
+            mint: launch.mint,
+            pool_token_account: pda::pool_token_account(&launch.mint),
+            liquidity_pool: matches!(launch.mode, LaunchMode::Lbp { .. })
+                .then(|| pda::liquidity_pool(&launch.mint).0),
+            bid_book: matches!(launch.mode, LaunchMode::SealedBid { .. })
+                .then(|| pda::bid_book(&launch.token).0),
+            lottery: matches!(launch.mode, LaunchMode::Lottery { .. })
+                .then(|| pda::lottery(&launch.token).0),
+            token_program: token::ID,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: pump::ID,
+        },
+        instruction::FinalizeSettle {},
+    );
+    ix.accounts.extend(launch.positions.iter().flat_map(|(position, recipient)| {
+        [AccountMeta::new(*position, false), AccountMeta::new(*recipient, false)]
+    }));
+    ix
+}
+
+pub fn finalize_create_pool(launch: &FinalizeAccounts) -> Instruction {
+    let global = pda::global().0;
+    let market = &launch.market;
+    let lp_mint = pda::raydium_pool(market, raydium::LP_MINT_SEED);
+    build(
+        accounts::FinalizeCreatePool {
+            token: launch.token,
+            config: pda::config().0,
+            cranker: launch.cranker,
+            amm_program: raydium::AMM_PROGRAM_ID,
+            amm: pda::raydium_pool(market, raydium::AMM_SEED),
+            amm_authority: pda::raydium(raydium::AUTHORITY_SEED).0,
+            amm_open_orders: pda::raydium_pool(market, raydium::OPEN_ORDERS_SEED),
+            lp_mint,
+            coin_mint: launch.mint,
+            pc_mint: native_mint::ID,
+            coin_vault: pda::raydium_pool(market, raydium::COIN_VAULT_SEED),
+            pc_vault: pda::raydium_pool(market, raydium::PC_VAULT_SEED),
+            target_orders: pda::raydium_pool(market, raydium::TARGET_ORDERS_SEED),
+            amm_config: pda::raydium(raydium::AMM_CONFIG_SEED).0,
+            fee_destination: raydium::CREATE_POOL_FEE_ID,
+            market_program: raydium::MARKET_PROGRAM_ID,
+            market: *market,
+            global_account: global,
+            user_token_coin: pda::pool_token_account(&launch.mint),
+            user_token_pc: get_associated_token_address(&global, &native_mint::ID),
+            user_token_lp: get_associated_token_address(&global, &lp_mint),
+            liquidity_pool: matches!(launch.mode, LaunchMode::Lbp { .. })
+                .then(|| pda::liquidity_pool(&launch.mint).0),
+            token_program: token::ID,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+            sysvar_rent: sysvar::rent::ID,
+            event_authority: event_authority(),
+            program: pump::ID,
+        },
+        instruction::FinalizeCreatePool {},
+    )
+}
+
+pub fn finalize_lock_lp(launch: &FinalizeAccounts) -> Instruction {
+    let global = pda::global().0;
+    let lp_mint = pda::raydium_pool(&launch.market, raydium::LP_MINT_SEED);
+    build(
+        accounts::FinalizeLockLp {
+            token: launch.token,
+            config: pda::config().0,
+            cranker: launch.cranker,
+            lp_mint,
+            global_account: global,
+            user_token_lp: get_associated_token_address(&global, &lp_mint),
+            token_program: token::ID,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: pump::ID,
+        },
+        instruction::FinalizeLockLp {},
+    )
+}
+
+pub fn finalize_complete(launch: &FinalizeAccounts) -> Instruction {
+    build(
+        accounts::FinalizeComplete {
+            token: launch.token,
+            config: pda::config().0,
+            creator_profile: pda::creator_profile(&launch.creator).0,
+            creator: launch.creator,
+            cranker: launch.cranker,
+            program_account: pda::global().0,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: pump::ID,
+        },
+        instruction::FinalizeComplete {},
+    )
+}
+
+pub fn commit_bid(user: &Pubkey, token: &Pubkey, commitment: [u8; 32], deposit: u64) -> Instruction {
+    build(
+        accounts::CommitBid {
+            token: *token,
+            config: pda::config().0,
+            bid_book: pda::bid_book(token).0,
+            bid: pda::bid(token, user).0,
+            user: *user,
+            program_account: pda::global().0,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: pump::ID,
+        },
+        instruction::CommitBid { commitment, deposit },
+    )
+}
+
+pub fn reveal_bid(user: &Pubkey, token: &Pubkey, amount: u64, price_limit: u64, salt: [u8; 32]) -> Instruction {
+    build(
+        accounts::RevealBid {
+            token: *token,
+            config: pda::config().0,
+            bid_book: pda::bid_book(token).0,
+            bid: pda::bid(token, user).0,
+            user: *user,
+            event_authority: event_authority(),
+            program: pump::ID,
+        },
+        instruction::RevealBid { amount, price_limit, salt },
+    )
+}
+
+/// Anyone may settle a bid; the tokens and any refund go to `user`.
+pub fn settle_bid(cranker: &Pubkey, user: &Pubkey, token: &Pubkey, mint: &Pubkey) -> Instruction {
+    build(
+        accounts::SettleBid {
+            token: *token,
+            config: pda::config().0,
+            bid_book: pda::bid_book(token).0,
+            bid: pda::bid(token, user).0,
+            user: *user,
+            cranker: *cranker,
+            program_account: pda::global().0,
+            mint: *mint,
+            pool_token_account: pda::pool_token_account(mint),
+            user_token_account: get_associated_token_address(user, mint),
+            token_program: token::ID,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: pump::ID,
+        },
+        instruction::SettleBid {},
+    )
+}
+
+pub fn close_lottery(payer: &Pubkey, token: &Pubkey) -> Instruction {
+    build(
+        accounts::CloseLottery {
+            token: *token,
+            config: pda::config().0,
+            lottery: pda::lottery(token).0,
+            payer: *payer,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: pump::ID,
+        },
+        instruction::CloseLottery {},
+    )
+}
+
+/// `randomness_account` must be the account the launch was configured with.
+pub fn draw_lottery(token: &Pubkey, randomness_account: &Pubkey) -> Instruction {
+    build(
+        accounts::DrawLottery {
+            token: *token,
+            config: pda::config().0,
+            lottery: pda::lottery(token).0,
+            randomness_account: *randomness_account,
+            event_authority: event_authority(),
+            program: pump::ID,
+        },
+        instruction::DrawLottery {},
+    )
+}
+
+/// Anyone may settle a position's tickets; the tokens and any refund go to
+/// `user`.
+pub fn settle_tickets(
+    cranker: &Pubkey,
+    user: &Pubkey,
+    token: &Pubkey,
+    mint: &Pubkey,
+    contribution_number: u32,
+) -> Instruction {
+    build(
+        accounts::SettleTickets {
+            token: *token,
+            config: pda::config().0,
+            lottery: pda::lottery(token).0,
+            user_contribution: pda::user_contribution(user, token, contribution_number).0,
+            user: *user,
+            cranker: *cranker,
+            program_account: pda::global().0,
+            mint: *mint,
+            pool_token_account: pda::pool_token_account(mint),
+            user_token_account: get_associated_token_address(user, mint),
+            token_program: token::ID,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: pump::ID,
+        },
+        instruction::SettleTickets {},
+    )
+}
+
+pub fn start_lbp(creator: &Pubkey, token: &Pubkey, mint: &Pubkey) -> Instruction {
+    build(
+        accounts::StartLbp {
+            token: *token,
+            config: pda::config().0,
+            liquidity_pool: pda::liquidity_pool(mint).0,
+            creator: *creator,
+            mint: *mint,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: pump::ID,
+        },
+        instruction::StartLbp {},
+    )
+}
+
+/// Bootstrapping pools trade against their pool, every other launch
+/// against its curve.
+fn trade_pool(mode: &LaunchMode, mint: &Pubkey) -> Option<Pubkey> {
+    matches!(mode, LaunchMode::Lbp { .. }).then(|| pda::liquidity_pool(mint).0)
+}
+
+pub fn buy(
+    user: &Pubkey,
+    token: &Pubkey,
+    mint: &Pubkey,
+    mode: &LaunchMode,
+    amount_in: u64,
+    minimum_amount_out: u64,
+) -> Instruction {
+    build(
+        accounts::Buy {
+            token: *token,
+            liquidity_pool: trade_pool(mode, mint),
+            user: *user,
+            program_account: pda::global().0,
+            mint: *mint,
+            pool_token_account: pda::pool_token_account(mint),
+            user_token_account: get_associated_token_address(user, mint),
+            token_program: token::ID,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+            config: pda::config().0,
+            event_authority: event_authority(),
+            program: pump::ID,
+        },
+        instruction::Buy { amount_in, minimum_amount_out },
+    )
+}
+
+pub fn sell(
+    user: &Pubkey,
+    token: &Pubkey,
+    mint: &Pubkey,
+    mode: &LaunchMode,
+    amount_in: u64,
+    minimum_amount_out: u64,
+) -> Instruction {
+    build(
+        accounts::Sell {
+            token: *token,
+            liquidity_pool: trade_pool(mode, mint),
+            user: *user,
+            program_account: pda::global().0,
+            mint: *mint,
+            pool_token_account: pda::pool_token_account(mint),
+            user_token_account: get_associated_token_address(user, mint),
+            token_program: token::ID,
+            system_program: system_program::ID,
+            config: pda::config().0,
+            event_authority: event_authority(),
+            program: pump::ID,
+        },
+        instruction::Sell { amount_in, minimum_amount_out },
+    )
+}
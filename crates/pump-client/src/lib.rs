@@ -0,0 +1,43 @@
+//! Rust client for the pump program: PDA derivation, instruction builders,
+//! account fetchers and unsigned transactions for offline signing.
+
+pub mod fetch;
+pub mod instructions;
+pub mod pda;
+
+use anchor_lang::prelude::Pubkey;
+use solana_client::client_error::ClientError;
+use solana_sdk::hash::Hash;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::message::Message;
+use solana_sdk::transaction::Transaction;
+
+pub use pump::ID as PROGRAM_ID;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Rpc(Box<ClientError>),
+    #[error("account {0} does not exist")]
+    AccountNotFound(Pubkey),
+    #[error("account {address} could not be deserialized: {source}")]
+    InvalidAccount {
+        address: Pubkey,
+        source: Box<anchor_lang::error::Error>,
+    },
+}
+
+impl From<ClientError> for Error {
+    fn from(error: ClientError) -> Self {
+        Error::Rpc(Box::new(error))
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Wraps `instructions` in a transaction paid for by `payer`, ready to be
+/// signed elsewhere. Offline signers supply the blockhash, usually a durable
+/// nonce.
+pub fn unsigned_transaction(instructions: &[Instruction], payer: &Pubkey, recent_blockhash: Hash) -> Transaction {
+    Transaction::new_unsigned(Message::new_with_blockhash(instructions, Some(payer), &recent_blockhash))
+}
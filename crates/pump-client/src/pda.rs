@@ -0,0 +1,102 @@
+//! Program derived addresses, mirroring the seeds in the program's account
+//! constraints.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::bpf_loader_upgradeable;
+use anchor_spl::associated_token::get_associated_token_address;
+use pump::state::LiquidityPool;
+use pump::utils::raydium;
+
+fn find(seeds: &[&[u8]]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(seeds, &pump::ID)
+}
+
+/// The program's custody account. It holds every launch's SOL and supply.
+pub fn global() -> (Pubkey, u8) {
+    find(&[b"global"])
+}
+
+/// Protocol settings, including the fee recipient `finalize_settle` pays.
+pub fn config() -> (Pubkey, u8) {
+    find(&[b"config"])
+}
+
+/// The program's `ProgramData`, whose upgrade authority may create the config.
+pub fn program_data() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[pump::ID.as_ref()], &bpf_loader_upgradeable::ID)
+}
+
+/// `TokenDetails` of the launch `creator` opened for `ticker`.
+pub fn token(creator: &Pubkey, ticker: &str) -> (Pubkey, u8) {
+    find(&[b"token", creator.as_ref(), ticker.as_bytes()])
+}
+
+/// Track record of `creator` across their launches.
+pub fn creator_profile(creator: &Pubkey) -> (Pubkey, u8) {
+    find(&[b"creator-profile", creator.as_ref()])
+}
+
+/// Registry entry for `ticker`, shared by every spelling that differs only in
+/// case.
+pub fn ticker(ticker: &str) -> (Pubkey, u8) {
+    find(&[b"ticker", ticker.to_ascii_uppercase().as_bytes()])
+}
+
+pub fn mint(creator: &Pubkey, ticker: &str) -> (Pubkey, u8) {
+    find(&[b"mint", creator.as_ref(), ticker.as_bytes()])
+}
+
+pub fn mint_authority(mint: &Pubkey) -> (Pubkey, u8) {
+    find(&[b"mint-authority", mint.as_ref()])
+}
+
+/// Contribution number `number` to `token`. Numbers are handed out from the
+/// launch's `contribution_count`.
+pub fn user_contribution(user: &Pubkey, token: &Pubkey, number: u32) -> (Pubkey, u8) {
+    find(&[b"user-contribution", user.as_ref(), token.as_ref(), &number.to_le_bytes()])
+}
+
+/// The creator's own contribution, recorded by `initialize`. It is keyed by
+/// ticker because the launch account does not exist yet when it is derived.
+pub fn creator_contribution(creator: &Pubkey, ticker: &str) -> (Pubkey, u8) {
+    find(&[b"user-contribution", creator.as_ref(), ticker.as_bytes(), &[0]])
+}
+
+pub fn bid_book(token: &Pubkey) -> (Pubkey, u8) {
+    find(&[b"bid-book", token.as_ref()])
+}
+
+pub fn bid(token: &Pubkey, user: &Pubkey) -> (Pubkey, u8) {
+    find(&[b"bid", token.as_ref(), user.as_ref()])
+}
+
+pub fn lottery(token: &Pubkey) -> (Pubkey, u8) {
+    find(&[b"lottery", token.as_ref()])
+}
+
+pub fn liquidity_pool(mint: &Pubkey) -> (Pubkey, u8) {
+    find(&[LiquidityPool::POOL_SEED_PREFIX.as_bytes(), mint.as_ref()])
+}
+
+/// Signer the program uses for its event CPIs.
+pub fn event_authority() -> (Pubkey, u8) {
+    find(&[b"__event_authority"])
+}
+
+/// The global account's token account for `mint`, which holds the unsold
+/// supply.
+pub fn pool_token_account(mint: &Pubkey) -> Pubkey {
+    get_associated_token_address(&global().0, mint)
+}
+
+/// Raydium pool account for `market`, keyed by one of the seeds in
+/// `pump::utils::raydium`.
+pub fn raydium_pool(market: &Pubkey, seed: &[u8]) -> Pubkey {
+    let program = raydium::AMM_PROGRAM_ID;
+    Pubkey::find_program_address(&[program.as_ref(), market.as_ref(), seed], &program).0
+}
+
+/// Raydium account keyed by `seed` alone: the AMM authority or its config.
+pub fn raydium(seed: &[u8]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[seed], &raydium::AMM_PROGRAM_ID)
+}
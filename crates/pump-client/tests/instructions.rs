@@ -0,0 +1,128 @@
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_lang::{AnchorDeserialize, Discriminator};
+use pump::state::LaunchMode;
+use pump::utils::Curve;
+use pump::utils::raydium;
+use pump_client::instructions::{self, FinalizeAccounts, LaunchParams};
+use pump_client::{pda, unsigned_transaction};
+use solana_sdk::hash::Hash;
+
+fn key(byte: u8) -> Pubkey {
+    Pubkey::new_from_array([byte; 32])
+}
+
+#[test]
+fn initialize_derives_the_launch_accounts() {
+    let creator = key(1);
+    let ix = instructions::initialize(&creator, LaunchParams {
+        name: "Launch".to_string(),
+        ticker: "LNCH".to_string(),
+        total_supply: 1_000_000_000_000_000,
+        initial_target: 1_000_000_000,
+        decimals: 6,
+        mode: LaunchMode::Standard,
+        curve: Curve::Linear { base_price: 1_000_000, slope: 0 },
+        bond: 0,
+    });
+
+    let mint = pda::mint(&creator, "LNCH").0;
+    let keys: Vec<Pubkey> = ix.accounts.iter().map(|meta| meta.pubkey).collect();
+    assert_eq!(keys[..8], [
+        pda::token(&creator, "LNCH").0,
+        pda::config().0,
+        mint,
+        pda::pool_token_account(&mint),
+        pda::mint_authority(&mint).0,
+        creator,
+        pda::global().0,
+        pda::creator_contribution(&creator, "LNCH").0,
+    ]);
+    assert_eq!(keys[8..10], [pda::ticker("lnch").0, pda::creator_profile(&creator).0]);
+    assert_eq!(keys[keys.len() - 2..], [pda::event_authority().0, pump::ID]);
+    assert!(ix.accounts[5].is_signer && ix.accounts[5].is_writable);
+
+    let (discriminator, mut args) = ix.data.split_at(8);
+    assert_eq!(discriminator, pump::instruction::Initialize::DISCRIMINATOR);
+    let args = pump::instruction::Initialize::deserialize(&mut args).unwrap();
+    assert_eq!((args.ticker.as_str(), args.decimals), ("LNCH", 6));
+}
+
+#[test]
+fn contribute_uses_the_next_contribution_number() {
+    let (user, token) = (key(1), key(2));
+    let ix = instructions::contribute(&user, &token, 3, 500);
+
+    assert_eq!(ix.accounts[2].pubkey, pda::user_contribution(&user, &token, 3).0);
+    assert_ne!(ix.accounts[2].pubkey, pda::user_contribution(&user, &token, 2).0);
+    assert_eq!(pump::instruction::Contribute::deserialize(&mut &ix.data[8..]).unwrap().amount, 500);
+}
+
+#[test]
+fn trades_pass_the_pool_only_for_bootstrapping_launches() {
+    let (user, token, mint) = (key(1), key(2), key(4));
+
+    let curve = instructions::buy(&user, &token, &mint, &LaunchMode::Standard, 10, 0);
+    assert_eq!(curve.accounts[1].pubkey, pump::ID);
+    assert!(!curve.accounts[1].is_writable);
+
+    let lbp = LaunchMode::Lbp { start_weight_bps: 9_000, end_weight_bps: 5_000, duration: 3_600 };
+    let pool = instructions::sell(&user, &token, &mint, &lbp, 10, 0);
+    assert_eq!(pool.accounts[1].pubkey, pda::liquidity_pool(&mint).0);
+    assert!(pool.accounts[1].is_writable);
+}
+
+#[test]
+fn finalize_derives_the_pool_and_appends_positions() {
+    let (token, mint, market) = (key(2), key(4), key(5));
+    let positions = vec![(key(6), key(7)), (key(9), key(10))];
+    let steps = instructions::finalize(&FinalizeAccounts {
+        token,
+        creator: key(1),
+        ticker: "LNCH".to_string(),
+        cranker: key(8),
+        mint,
+        fee_recipient: key(3),
+        market,
+        positions: positions.clone(),
+        mode: LaunchMode::Standard,
+    });
+    assert_eq!(steps.len(), 4);
+    let keys_of = |ix: &Instruction| ix.accounts.iter().map(|meta| meta.pubkey).collect::<Vec<Pubkey>>();
+
+    let settle = keys_of(&steps[0]);
+    assert!(settle.contains(&pda::config().0));
+    assert!(settle.contains(&key(3)));
+    assert!(!settle.contains(&pda::liquidity_pool(&mint).0));
+    assert_eq!(settle[settle.len() - 4..], [key(6), key(7), key(9), key(10)]);
+    assert!(steps[0].accounts[settle.len() - 4..].iter().all(|meta| meta.is_writable && !meta.is_signer));
+
+    let create_pool = keys_of(&steps[1]);
+    assert!(create_pool.contains(&pda::raydium_pool(&market, raydium::AMM_SEED)));
+    assert!(!create_pool.contains(&positions[0].1));
+
+    let lp_mint = pda::raydium_pool(&market, raydium::LP_MINT_SEED);
+    let lock_lp = keys_of(&steps[2]);
+    assert!(lock_lp.contains(&lp_mint));
+    assert!(lock_lp.contains(&get_associated_token_address(&pda::global().0, &lp_mint)));
+
+    let complete = keys_of(&steps[3]);
+    assert!(complete.contains(&pda::creator_profile(&key(1)).0));
+    // Every step pays its share of the crank reward.
+    for step in &steps {
+        let cranker = step.accounts.iter().find(|meta| meta.pubkey == key(8)).unwrap();
+        assert!(cranker.is_signer && cranker.is_writable);
+    }
+}
+
+#[test]
+fn unsigned_transactions_are_paid_by_the_payer() {
+    let user = key(1);
+    let ix = instructions::refund(&user, &key(2), &key(3), "LNCH", &key(4));
+    let tx = unsigned_transaction(&[ix], &user, Hash::new_unique());
+
+    assert_eq!(tx.message.account_keys[0], user);
+    assert_eq!(tx.signatures.len(), 1);
+    assert!(!tx.is_signed());
+}
@@ -0,0 +1,409 @@
+//! SQLite schema and the writes for each record. Amounts are stored as
+//! INTEGER, so a value above `i64::MAX` fails the write instead of wrapping.
+
+use std::path::Path;
+
+use anyhow::Result;
+use pump::state::LaunchMode;
+use pump::utils::Curve;
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::decode::{DecodedTransaction, Record};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS transactions (
+    signature TEXT PRIMARY KEY,
+    slot INTEGER NOT NULL,
+    block_time INTEGER
+);
+CREATE TABLE IF NOT EXISTS launches (
+    token TEXT PRIMARY KEY,
+    mint TEXT NOT NULL,
+    creator TEXT NOT NULL,
+    name TEXT NOT NULL,
+    ticker TEXT NOT NULL,
+    total_supply INTEGER NOT NULL,
+    decimals INTEGER NOT NULL,
+    target INTEGER NOT NULL,
+    mode TEXT NOT NULL,
+    mode_params TEXT NOT NULL,
+    curve TEXT NOT NULL,
+    curve_params TEXT NOT NULL,
+    initial_contribution INTEGER NOT NULL,
+    initial_tokens INTEGER NOT NULL,
+    total_contributed INTEGER NOT NULL,
+    tokens_sold INTEGER NOT NULL,
+    contribution_count INTEGER NOT NULL DEFAULT 1,
+    finalized INTEGER NOT NULL DEFAULT 0,
+    fee INTEGER,
+    liquidity_sol INTEGER,
+    liquidity_tokens INTEGER,
+    amm TEXT,
+    lp_mint TEXT,
+    created_at INTEGER NOT NULL,
+    finalized_at INTEGER,
+    cancelled_at INTEGER,
+    failed_at INTEGER,
+    refund_deadline INTEGER,
+    opt_out_deadline INTEGER,
+    bond INTEGER,
+    bond_returned INTEGER,
+    bond_slashed INTEGER,
+    lp_burned INTEGER,
+    signature TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS contributions (
+    user_contribution TEXT PRIMARY KEY,
+    token TEXT NOT NULL,
+    user TEXT NOT NULL,
+    contribution_number INTEGER NOT NULL,
+    amount INTEGER NOT NULL,
+    tokens INTEGER NOT NULL,
+    first_ticket INTEGER NOT NULL,
+    ticket_count INTEGER NOT NULL,
+    refunded INTEGER NOT NULL DEFAULT 0,
+    created_at INTEGER NOT NULL,
+    signature TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS refunds (
+    signature TEXT NOT NULL,
+    seq INTEGER NOT NULL,
+    token TEXT NOT NULL,
+    user TEXT NOT NULL,
+    user_contribution TEXT NOT NULL,
+    amount INTEGER NOT NULL,
+    created_at INTEGER NOT NULL,
+    PRIMARY KEY (signature, seq)
+);
+CREATE TABLE IF NOT EXISTS claims (
+    signature TEXT NOT NULL,
+    seq INTEGER NOT NULL,
+    token TEXT NOT NULL,
+    user TEXT NOT NULL,
+    amount INTEGER NOT NULL,
+    refund INTEGER NOT NULL,
+    created_at INTEGER NOT NULL,
+    PRIMARY KEY (signature, seq)
+);
+CREATE TABLE IF NOT EXISTS fees (
+    signature TEXT NOT NULL,
+    seq INTEGER NOT NULL,
+    token TEXT NOT NULL,
+    payer TEXT NOT NULL,
+    recipient TEXT NOT NULL,
+    amount INTEGER NOT NULL,
+    created_at INTEGER NOT NULL,
+    PRIMARY KEY (signature, seq)
+);
+CREATE TABLE IF NOT EXISTS crank_rewards (
+    signature TEXT NOT NULL,
+    seq INTEGER NOT NULL,
+    token TEXT NOT NULL,
+    cranker TEXT NOT NULL,
+    amount INTEGER NOT NULL,
+    created_at INTEGER NOT NULL,
+    PRIMARY KEY (signature, seq)
+);
+CREATE TABLE IF NOT EXISTS trades (
+    signature TEXT NOT NULL,
+    seq INTEGER NOT NULL,
+    token TEXT NOT NULL,
+    user TEXT NOT NULL,
+    side TEXT NOT NULL,
+    amount_in INTEGER NOT NULL,
+    amount_out INTEGER NOT NULL,
+    slot INTEGER NOT NULL,
+    PRIMARY KEY (signature, seq)
+);
+CREATE INDEX IF NOT EXISTS contributions_by_token ON contributions (token);
+CREATE INDEX IF NOT EXISTS trades_by_token ON trades (token);
+";
+
+pub struct Database {
+    conn: Connection,
+}
+
+impl Database {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::with_connection(Connection::open(path)?)
+    }
+
+    pub fn in_memory() -> Result<Self> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    fn with_connection(conn: Connection) -> Result<Self> {
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn })
+    }
+
+    pub fn connection(&self) -> &Connection {
+        &self.conn
+    }
+
+    pub fn is_indexed(&self, signature: &str) -> Result<bool> {
+        let found = self.conn
+            .query_row("SELECT 1 FROM transactions WHERE signature = ?1", [signature], |_| Ok(()))
+            .optional()?;
+        Ok(found.is_some())
+    }
+
+    /// Writes a decoded transaction and all its records atomically. Replaying
+    /// a transaction that is already indexed is a no-op.
+    pub fn apply(&mut self, decoded: &DecodedTransaction) -> Result<bool> {
+        if self.is_indexed(&decoded.signature)? {
+            return Ok(false);
+        }
+
+        let tx = self.conn.transaction()?;
+        tx.execute(
+            "INSERT INTO transactions (signature, slot, block_time) VALUES (?1, ?2, ?3)",
+            params![decoded.signature, decoded.slot, decoded.block_time],
+        )?;
+        for (seq, record) in decoded.records.iter().enumerate() {
+            apply_record(&tx, decoded, seq, record)?;
+        }
+        tx.commit()?;
+        Ok(true)
+    }
+}
+
+fn apply_record(tx: &Connection, decoded: &DecodedTransaction, seq: usize, record: &Record) -> Result<()> {
+    let signature = &decoded.signature;
+    match record {
+        Record::LaunchCreated(event) => {
+            tx.execute(
+                "INSERT INTO launches (
+                    token, mint, creator, name, ticker, total_supply, decimals, target,
+                    mode, mode_params, curve, curve_params, initial_contribution, initial_tokens,
+                    total_contributed, tokens_sold, created_at, signature
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?13, ?14, ?15, ?16)",
+                params![
+                    event.token.to_string(),
+                    event.mint.to_string(),
+                    event.creator.to_string(),
+                    event.name,
+                    event.ticker,
+                    event.total_supply,
+                    event.decimals,
+                    event.target,
+                    mode_name(&event.mode),
+                    format!("{:?}", event.mode),
+                    curve_name(&event.curve),
+                    format!("{:?}", event.curve),
+                    event.initial_contribution,
+                    event.initial_tokens,
+                    event.timestamp,
+                    signature,
+                ],
+            )?;
+        }
+        Record::Contributed(event) => {
+            tx.execute(
+                "INSERT INTO contributions (
+                    user_contribution, token, user, contribution_number, amount, tokens,
+                    first_ticket, ticket_count, created_at, signature
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                params![
+                    event.user_contribution.to_string(),
+                    event.token.to_string(),
+                    event.user.to_string(),
+                    event.contribution_number,
+                    event.amount,
+                    event.tokens,
+                    event.first_ticket,
+                    event.ticket_count,
+                    event.timestamp,
+                    signature,
+                ],
+            )?;
+            tx.execute(
+                "UPDATE launches
+                 SET total_contributed = ?2, tokens_sold = tokens_sold + ?3,
+                     contribution_count = MAX(contribution_count, ?4 + 1)
+                 WHERE token = ?1",
+                params![event.token.to_string(), event.total_contributed, event.tokens, event.contribution_number],
+            )?;
+        }
+        Record::Refunded(event) => {
+            tx.execute(
+                "INSERT INTO refunds (signature, seq, token, user, user_contribution, amount, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    signature,
+                    seq,
+                    event.token.to_string(),
+                    event.user.to_string(),
+                    event.user_contribution.to_string(),
+                    event.amount,
+                    event.timestamp,
+                ],
+            )?;
+            // Standard launches hand the refunded position back to the curve.
+            tx.execute(
+                "UPDATE launches
+                 SET total_contributed = ?2,
+                     tokens_sold = tokens_sold - CASE WHEN mode = 'standard' THEN COALESCE(
+                         (SELECT tokens FROM contributions WHERE user_contribution = ?3), 0
+                     ) ELSE 0 END
+                 WHERE token = ?1",
+                params![event.token.to_string(), event.total_contributed, event.user_contribution.to_string()],
+            )?;
+            tx.execute(
+                "UPDATE contributions SET refunded = 1 WHERE user_contribution = ?1",
+                [event.user_contribution.to_string()],
+            )?;
+        }
+        Record::TokensClaimed(event) => {
+            tx.execute(
+                "INSERT INTO claims (signature, seq, token, user, amount, refund, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    signature,
+                    seq,
+                    event.token.to_string(),
+                    event.user.to_string(),
+                    event.amount,
+                    event.refund,
+                    event.timestamp,
+                ],
+            )?;
+        }
+        Record::FeeCollected(event) => {
+            tx.execute(
+                "INSERT INTO fees (signature, seq, token, payer, recipient, amount, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    signature,
+                    seq,
+                    event.token.to_string(),
+                    event.payer.to_string(),
+                    event.recipient.to_string(),
+                    event.amount,
+                    event.timestamp,
+                ],
+            )?;
+        }
+        Record::LiquidityProvided(event) => {
+            tx.execute(
+                "UPDATE launches SET amm = ?2, lp_mint = ?3 WHERE token = ?1",
+                params![event.token.to_string(), event.amm.to_string(), event.lp_mint.to_string()],
+            )?;
+        }
+        Record::Finalized(event) => {
+            tx.execute(
+                "UPDATE launches
+                 SET finalized = 1, total_contributed = ?2, fee = ?3, liquidity_sol = ?4,
+                     liquidity_tokens = ?5, finalized_at = ?6
+                 WHERE token = ?1",
+                params![
+                    event.token.to_string(),
+                    event.total_contributed,
+                    event.fee,
+                    event.liquidity_sol,
+                    event.liquidity_tokens,
+                    event.timestamp,
+                ],
+            )?;
+        }
+        Record::LaunchCancelled(event) => {
+            tx.execute(
+                "UPDATE launches SET cancelled_at = ?2 WHERE token = ?1",
+                params![event.token.to_string(), event.timestamp],
+            )?;
+        }
+        Record::LaunchFailed(event) => {
+            tx.execute(
+                "UPDATE launches SET failed_at = ?2 WHERE token = ?1",
+                params![event.token.to_string(), event.timestamp],
+            )?;
+        }
+        Record::ContributionWithdrawn(event) => {
+            tx.execute(
+                "UPDATE launches SET total_contributed = ?2, tokens_sold = tokens_sold - ?3 WHERE token = ?1",
+                params![event.token.to_string(), event.total_contributed, event.tokens],
+            )?;
+            tx.execute(
+                "UPDATE contributions SET amount = amount - ?2, tokens = tokens - ?3 WHERE user_contribution = ?1",
+                params![event.user_contribution.to_string(), event.amount, event.tokens],
+            )?;
+        }
+        Record::DeadlineExtended(event) => {
+            tx.execute(
+                "UPDATE launches SET refund_deadline = ?2, opt_out_deadline = ?3 WHERE token = ?1",
+                params![event.token.to_string(), event.refund_deadline, event.opt_out_deadline],
+            )?;
+        }
+        Record::BondPosted(event) => {
+            tx.execute(
+                "UPDATE launches SET bond = ?2 WHERE token = ?1",
+                params![event.token.to_string(), event.amount],
+            )?;
+        }
+        Record::BondSettled(event) => {
+            tx.execute(
+                "UPDATE launches SET bond_returned = ?2, bond_slashed = ?3 WHERE token = ?1",
+                params![event.token.to_string(), event.returned, event.slashed],
+            )?;
+        }
+        Record::CrankRewardPaid(event) => {
+            tx.execute(
+                "INSERT INTO crank_rewards (signature, seq, token, cranker, amount, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    signature,
+                    seq,
+                    event.token.to_string(),
+                    event.cranker.to_string(),
+                    event.amount,
+                    event.timestamp,
+                ],
+            )?;
+        }
+        Record::LiquidityLocked(event) => {
+            tx.execute(
+                "UPDATE launches SET lp_burned = ?2 WHERE token = ?1",
+                params![event.token.to_string(), event.amount],
+            )?;
+        }
+        Record::Trade(event) => {
+            tx.execute(
+                "INSERT INTO trades (signature, seq, token, user, side, amount_in, amount_out, slot)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![
+                    signature,
+                    seq,
+                    event.token.to_string(),
+                    event.user.to_string(),
+                    if event.is_buy { "buy" } else { "sell" },
+                    event.amount_in,
+                    event.amount_out,
+                    decoded.slot,
+                ],
+            )?;
+            tx.execute(
+                "UPDATE launches SET total_contributed = ?2, tokens_sold = ?3 WHERE token = ?1",
+                params![event.token.to_string(), event.total_contributed, event.tokens_sold],
+            )?;
+        }
+    }
+    Ok(())
+}
+
+pub fn mode_name(mode: &LaunchMode) -> &'static str {
+    match mode {
+        LaunchMode::Standard => "standard",
+        LaunchMode::SealedBid { .. } => "sealed_bid",
+        LaunchMode::Lottery { .. } => "lottery",
+        LaunchMode::Lbp { .. } => "lbp",
+    }
+}
+
+pub fn curve_name(curve: &Curve) -> &'static str {
+    match curve {
+        Curve::Linear { .. } => "linear",
+        Curve::Exponential { .. } => "exponential",
+        Curve::Sigmoid { .. } => "sigmoid",
+        Curve::ConstantProduct { .. } => "constant_product",
+    }
+}
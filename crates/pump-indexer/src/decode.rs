@@ -0,0 +1,114 @@
+//! Turns pump transactions into indexer records, one per event the program
+//! emits through event CPI. Events are read from inner instructions, so trades
+//! and contributions made through another program's CPI are indexed too.
+
+use anchor_lang::event::EVENT_IX_TAG_LE;
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::{AnchorDeserialize, Discriminator};
+use anyhow::{anyhow, Result};
+use pump::events::{
+    BondPosted, BondSettled, Contributed, ContributionWithdrawn, CrankRewardPaid, DeadlineExtended,
+    FeeCollected, Finalized, LaunchCancelled, LaunchCreated, LaunchFailed, LiquidityLocked,
+    LiquidityProvided, Refunded, TokensClaimed, Trade,
+};
+
+use crate::transaction::{CompiledInstruction, EncodedTransaction};
+
+pub enum Record {
+    LaunchCreated(LaunchCreated),
+    Contributed(Contributed),
+    Refunded(Refunded),
+    TokensClaimed(TokensClaimed),
+    FeeCollected(FeeCollected),
+    LiquidityProvided(LiquidityProvided),
+    Finalized(Finalized),
+    LaunchCancelled(LaunchCancelled),
+    LaunchFailed(LaunchFailed),
+    DeadlineExtended(DeadlineExtended),
+    ContributionWithdrawn(ContributionWithdrawn),
+    BondPosted(BondPosted),
+    BondSettled(BondSettled),
+    CrankRewardPaid(CrankRewardPaid),
+    LiquidityLocked(LiquidityLocked),
+    Trade(Trade),
+}
+
+pub struct DecodedTransaction {
+    pub signature: String,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    pub records: Vec<Record>,
+}
+
+/// Decodes every pump record in `tx`, in execution order. Failed transactions
+/// decode to no records.
+pub fn decode_transaction(tx: &EncodedTransaction, program_id: &Pubkey) -> Result<DecodedTransaction> {
+    let signature = tx.signature()?.to_string();
+    let mut records = Vec::new();
+
+    if tx.succeeded() {
+        let keys = tx.account_keys()?;
+        let inner = tx.meta.as_ref().and_then(|meta| meta.inner_instructions.as_ref());
+
+        for index in 0..tx.transaction.message.instructions.len() {
+            let inner_instructions = inner.iter()
+                .flat_map(|inner| inner.iter())
+                .filter(|inner| inner.index == index)
+                .flat_map(|inner| inner.instructions.iter());
+            for instruction in inner_instructions {
+                if is_program(&keys, instruction, program_id) {
+                    if let Some(record) = decode_event(&instruction.decoded_data()?)? {
+                        records.push(record);
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(DecodedTransaction {
+        signature,
+        slot: tx.slot,
+        block_time: tx.block_time,
+        records,
+    })
+}
+
+/// Decodes the payload of a self-CPI emitted by `emit_cpi!`. Instructions that
+/// are not event CPIs, or carry events the indexer does not track, decode to
+/// `None`.
+pub fn decode_event(data: &[u8]) -> Result<Option<Record>> {
+    let Some(event) = data.strip_prefix(&EVENT_IX_TAG_LE[..]) else {
+        return Ok(None);
+    };
+    if event.len() < 8 {
+        return Err(anyhow!("event payload is shorter than its discriminator"));
+    }
+    let (discriminator, mut body) = event.split_at(8);
+
+    let record = match discriminator {
+        d if d == LaunchCreated::DISCRIMINATOR => Record::LaunchCreated(LaunchCreated::deserialize(&mut body)?),
+        d if d == Contributed::DISCRIMINATOR => Record::Contributed(Contributed::deserialize(&mut body)?),
+        d if d == Refunded::DISCRIMINATOR => Record::Refunded(Refunded::deserialize(&mut body)?),
+        d if d == TokensClaimed::DISCRIMINATOR => Record::TokensClaimed(TokensClaimed::deserialize(&mut body)?),
+        d if d == FeeCollected::DISCRIMINATOR => Record::FeeCollected(FeeCollected::deserialize(&mut body)?),
+        d if d == LiquidityProvided::DISCRIMINATOR => Record::LiquidityProvided(LiquidityProvided::deserialize(&mut body)?),
+        d if d == Finalized::DISCRIMINATOR => Record::Finalized(Finalized::deserialize(&mut body)?),
+        d if d == LaunchCancelled::DISCRIMINATOR => Record::LaunchCancelled(LaunchCancelled::deserialize(&mut body)?),
+        d if d == LaunchFailed::DISCRIMINATOR => Record::LaunchFailed(LaunchFailed::deserialize(&mut body)?),
+        d if d == ContributionWithdrawn::DISCRIMINATOR => {
+            Record::ContributionWithdrawn(ContributionWithdrawn::deserialize(&mut body)?)
+        }
+        d if d == DeadlineExtended::DISCRIMINATOR => Record::DeadlineExtended(DeadlineExtended::deserialize(&mut body)?),
+        d if d == BondPosted::DISCRIMINATOR => Record::BondPosted(BondPosted::deserialize(&mut body)?),
+        d if d == BondSettled::DISCRIMINATOR => Record::BondSettled(BondSettled::deserialize(&mut body)?),
+        d if d == CrankRewardPaid::DISCRIMINATOR => Record::CrankRewardPaid(CrankRewardPaid::deserialize(&mut body)?),
+        d if d == LiquidityLocked::DISCRIMINATOR => Record::LiquidityLocked(LiquidityLocked::deserialize(&mut body)?),
+        d if d == Trade::DISCRIMINATOR => Record::Trade(Trade::deserialize(&mut body)?),
+        _ => return Ok(None),
+    };
+    Ok(Some(record))
+}
+
+fn is_program(keys: &[Pubkey], instruction: &CompiledInstruction, program_id: &Pubkey) -> bool {
+    keys.get(instruction.program_id_index) == Some(program_id)
+}
@@ -0,0 +1,27 @@
+pub mod db;
+pub mod decode;
+pub mod rpc;
+pub mod transaction;
+
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+use crate::transaction::EncodedTransaction;
+
+/// Reads a transaction dump: a single `getTransaction` result, an array of
+/// them, or one result per line.
+pub fn load_dump(path: &Path) -> Result<Vec<EncodedTransaction>> {
+    let contents = fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+    let trimmed = contents.trim_start();
+
+    let transactions = if trimmed.starts_with('[') {
+        serde_json::from_str(trimmed)?
+    } else {
+        serde_json::Deserializer::from_str(trimmed)
+            .into_iter::<EncodedTransaction>()
+            .collect::<serde_json::Result<Vec<_>>>()?
+    };
+    Ok(transactions)
+}
@@ -0,0 +1,72 @@
+use std::path::PathBuf;
+
+use anchor_lang::prelude::Pubkey;
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+use pump_indexer::db::Database;
+use pump_indexer::decode::decode_transaction;
+use pump_indexer::load_dump;
+use pump_indexer::rpc::RpcSource;
+
+#[derive(Parser)]
+#[command(about = "Index pump launches into a local SQLite database")]
+struct Cli {
+    /// SQLite database to create or update.
+    #[arg(long, default_value = "pump-index.db")]
+    db: PathBuf,
+
+    /// Program whose transactions are indexed.
+    #[arg(long, default_value_t = pump::ID)]
+    program_id: Pubkey,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Replay the program's history from an RPC node.
+    Replay {
+        #[arg(long, default_value = "http://127.0.0.1:8899")]
+        rpc: String,
+
+        /// Most recent transactions to fetch.
+        #[arg(long, default_value_t = 10_000)]
+        limit: usize,
+    },
+    /// Load JSON transaction dumps.
+    Load {
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+    },
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let mut db = Database::open(&cli.db)?;
+    let mut indexed = 0;
+
+    match cli.command {
+        Command::Replay { rpc, limit } => {
+            let source = RpcSource::new(rpc);
+            for signature in source.signatures(&cli.program_id, limit)? {
+                if db.is_indexed(&signature)? {
+                    continue;
+                }
+                if let Some(tx) = source.transaction(&signature)? {
+                    indexed += db.apply(&decode_transaction(&tx, &cli.program_id)?)? as usize;
+                }
+            }
+        }
+        Command::Load { files } => {
+            for file in files {
+                for tx in load_dump(&file)? {
+                    indexed += db.apply(&decode_transaction(&tx, &cli.program_id)?)? as usize;
+                }
+            }
+        }
+    }
+
+    println!("indexed {indexed} new transactions into {}", cli.db.display());
+    Ok(())
+}
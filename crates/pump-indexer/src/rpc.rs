@@ -0,0 +1,75 @@
+//! Minimal JSON-RPC client for replaying a cluster's history, usually a
+//! local test validator.
+
+use anchor_lang::prelude::Pubkey;
+use anyhow::{anyhow, Context, Result};
+use serde_json::{json, Value};
+
+use crate::transaction::EncodedTransaction;
+
+const SIGNATURE_PAGE: usize = 1000;
+
+pub struct RpcSource {
+    url: String,
+}
+
+impl RpcSource {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self { url: url.into() }
+    }
+
+    fn call(&self, method: &str, params: Value) -> Result<Value> {
+        let response: Value = ureq::post(&self.url)
+            .send_json(json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params }))
+            .with_context(|| format!("{method} request to {} failed", self.url))?
+            .into_json()?;
+
+        if let Some(error) = response.get("error") {
+            return Err(anyhow!("{method} failed: {error}"));
+        }
+        response.get("result").cloned().ok_or_else(|| anyhow!("{method} returned no result"))
+    }
+
+    /// Signatures of successful and failed transactions touching `address`,
+    /// oldest first, up to `limit` of the most recent.
+    pub fn signatures(&self, address: &Pubkey, limit: usize) -> Result<Vec<String>> {
+        let mut signatures = Vec::new();
+        let mut before: Option<String> = None;
+
+        while signatures.len() < limit {
+            let page = (limit - signatures.len()).min(SIGNATURE_PAGE);
+            let mut config = json!({ "limit": page, "commitment": "confirmed" });
+            if let Some(before) = &before {
+                config["before"] = json!(before);
+            }
+
+            let result = self.call("getSignaturesForAddress", json!([address.to_string(), config]))?;
+            let entries = result.as_array().ok_or_else(|| anyhow!("unexpected signature list"))?;
+            for entry in entries {
+                let signature = entry["signature"].as_str().ok_or_else(|| anyhow!("signature missing"))?;
+                signatures.push(signature.to_string());
+            }
+
+            if entries.len() < page {
+                break;
+            }
+            before = signatures.last().cloned();
+        }
+
+        signatures.reverse();
+        Ok(signatures)
+    }
+
+    pub fn transaction(&self, signature: &str) -> Result<Option<EncodedTransaction>> {
+        let config = json!({
+            "encoding": "json",
+            "commitment": "confirmed",
+            "maxSupportedTransactionVersion": 0,
+        });
+        let result = self.call("getTransaction", json!([signature, config]))?;
+        if result.is_null() {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_value(result).context("unexpected transaction encoding")?))
+    }
+}
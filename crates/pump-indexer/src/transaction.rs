@@ -0,0 +1,93 @@
+//! The subset of a `getTransaction` response (`"encoding": "json"`) the
+//! indexer reads. JSON transaction dumps use the same shape.
+
+use std::str::FromStr;
+
+use anchor_lang::prelude::Pubkey;
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EncodedTransaction {
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    pub transaction: TransactionBody,
+    pub meta: Option<Meta>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct TransactionBody {
+    pub signatures: Vec<String>,
+    pub message: Message,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Message {
+    pub account_keys: Vec<String>,
+    pub instructions: Vec<CompiledInstruction>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompiledInstruction {
+    pub program_id_index: usize,
+    pub accounts: Vec<usize>,
+    pub data: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Meta {
+    pub err: Option<serde_json::Value>,
+    #[serde(default)]
+    pub inner_instructions: Option<Vec<InnerInstructions>>,
+    #[serde(default)]
+    pub loaded_addresses: Option<LoadedAddresses>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct InnerInstructions {
+    pub index: usize,
+    pub instructions: Vec<CompiledInstruction>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct LoadedAddresses {
+    pub writable: Vec<String>,
+    pub readonly: Vec<String>,
+}
+
+impl EncodedTransaction {
+    pub fn signature(&self) -> Result<&str> {
+        self.transaction.signatures.first()
+            .map(String::as_str)
+            .ok_or_else(|| anyhow!("transaction has no signatures"))
+    }
+
+    pub fn succeeded(&self) -> bool {
+        self.meta.as_ref().is_none_or(|meta| meta.err.is_none())
+    }
+
+    /// Static keys followed by any addresses loaded from lookup tables, in
+    /// the order instruction account indexes refer to them.
+    pub fn account_keys(&self) -> Result<Vec<Pubkey>> {
+        let loaded = self.meta.as_ref()
+            .and_then(|meta| meta.loaded_addresses.clone())
+            .unwrap_or_default();
+
+        self.transaction.message.account_keys.iter()
+            .chain(&loaded.writable)
+            .chain(&loaded.readonly)
+            .map(|key| Pubkey::from_str(key).with_context(|| format!("invalid account key {key}")))
+            .collect()
+    }
+}
+
+impl CompiledInstruction {
+    pub fn decoded_data(&self) -> Result<Vec<u8>> {
+        bs58::decode(&self.data).into_vec()
+            .with_context(|| format!("invalid instruction data {}", self.data))
+    }
+}
@@ -0,0 +1,233 @@
+use std::path::PathBuf;
+
+use anchor_lang::event::EVENT_IX_TAG_LE;
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::Event;
+use pump::events::{BondPosted, BondSettled, CrankRewardPaid, LiquidityLocked, Trade};
+use pump_indexer::db::Database;
+use pump_indexer::decode::{decode_event, decode_transaction, Record};
+use pump_indexer::load_dump;
+use pump_indexer::transaction::EncodedTransaction;
+use serde_json::json;
+
+fn fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(name)
+}
+
+fn key(byte: u8) -> String {
+    Pubkey::new_from_array([byte; 32]).to_string()
+}
+
+/// The instruction data `emit_cpi!` sends for `event`.
+fn event_cpi(event: &impl Event) -> Vec<u8> {
+    [&EVENT_IX_TAG_LE[..], &event.data()].concat()
+}
+
+fn index(db: &mut Database, name: &str) -> usize {
+    load_dump(&fixture(name)).unwrap()
+        .iter()
+        .map(|tx| db.apply(&decode_transaction(tx, &pump::ID).unwrap()).unwrap() as usize)
+        .sum()
+}
+
+#[test]
+fn indexes_a_standard_launch() {
+    let mut db = Database::in_memory().unwrap();
+    assert_eq!(index(&mut db, "standard_launch.json"), 6);
+    let conn = db.connection();
+
+    let (mode, curve, total_contributed, tokens_sold): (String, String, u64, u64) = conn
+        .query_row(
+            "SELECT mode, curve, total_contributed, tokens_sold FROM launches WHERE token = ?1",
+            [key(2)],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        )
+        .unwrap();
+    assert_eq!((mode.as_str(), curve.as_str()), ("standard", "linear"));
+    assert_eq!(total_contributed, 1_300_000_001);
+    assert_eq!(tokens_sold, 1_300_000_000);
+
+    // The failed contribution left nothing behind.
+    let (contributions, refunded): (u32, u32) = conn
+        .query_row("SELECT COUNT(*), SUM(refunded) FROM contributions", [], |row| Ok((row.get(0)?, row.get(1)?)))
+        .unwrap();
+    assert_eq!((contributions, refunded), (1, 1));
+
+    let refund: u64 = conn.query_row("SELECT amount FROM refunds", [], |row| row.get(0)).unwrap();
+    assert_eq!(refund, 2_000_000_000);
+
+    let fee: u64 = conn.query_row("SELECT amount FROM fees", [], |row| row.get(0)).unwrap();
+    assert_eq!(fee, 100_000_000);
+
+    let trades: Vec<(String, u64, u64)> = conn
+        .prepare("SELECT side, amount_in, amount_out FROM trades ORDER BY slot").unwrap()
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?))).unwrap()
+        .collect::<Result<_, _>>().unwrap();
+    assert_eq!(trades, vec![
+        ("buy".to_string(), 500_000_000, 500_000_000),
+        ("sell".to_string(), 200_000_000, 199_999_999),
+    ]);
+}
+
+#[test]
+fn indexes_a_finalized_launch_from_json_lines() {
+    let mut db = Database::in_memory().unwrap();
+    assert_eq!(index(&mut db, "finalized_launch.jsonl"), 2);
+    let conn = db.connection();
+
+    let (mode, finalized, liquidity_tokens, amm): (String, bool, u64, String) = conn
+        .query_row(
+            "SELECT mode, finalized, liquidity_tokens, amm FROM launches WHERE token = ?1",
+            [key(20)],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        )
+        .unwrap();
+    assert_eq!(mode, "sealed_bid");
+    assert!(finalized);
+    assert_eq!(liquidity_tokens, 500_000_000_001);
+    assert_eq!(amm, key(12));
+
+    let (claims, claimed): (u32, u64) = conn
+        .query_row("SELECT COUNT(*), SUM(amount) FROM claims", [], |row| Ok((row.get(0)?, row.get(1)?)))
+        .unwrap();
+    assert_eq!((claims, claimed), (2, 499_999_999_999));
+}
+
+#[test]
+fn replaying_is_idempotent() {
+    let mut db = Database::in_memory().unwrap();
+    index(&mut db, "standard_launch.json");
+    assert_eq!(index(&mut db, "standard_launch.json"), 0);
+
+    let trades: u32 = db.connection().query_row("SELECT COUNT(*) FROM trades", [], |row| row.get(0)).unwrap();
+    assert_eq!(trades, 2);
+}
+
+#[test]
+fn indexes_every_trade_in_a_transaction() {
+    let mut db = Database::in_memory().unwrap();
+    index(&mut db, "standard_launch.json");
+
+    // A router program (key 9) buys and sells through CPI in one transaction,
+    // so neither trade is a top-level pump instruction.
+    let trade = |is_buy, amount_in, amount_out, tokens_sold, total_contributed| {
+        let data = event_cpi(&Trade {
+            token: Pubkey::new_from_array([2; 32]),
+            user: Pubkey::new_from_array([1; 32]),
+            is_buy,
+            amount_in,
+            amount_out,
+            tokens_sold,
+            total_contributed,
+            timestamp: 1_700_000_100,
+        });
+        json!({ "programIdIndex": 3, "accounts": [2], "data": bs58::encode(data).into_string() })
+    };
+    let tx: EncodedTransaction = serde_json::from_value(json!({
+        "slot": 110,
+        "blockTime": 1_700_000_100,
+        "transaction": {
+            "signatures": ["routed"],
+            "message": {
+                "accountKeys": [key(1), key(9), key(7), pump::ID.to_string()],
+                "instructions": [{ "programIdIndex": 1, "accounts": [0, 3], "data": "" }],
+            },
+        },
+        "meta": {
+            "err": null,
+            "innerInstructions": [{
+                "index": 0,
+                "instructions": [
+                    trade(true, 1_000_000_000, 1_000_000_000, 2_300_000_000, 2_300_000_001),
+                    trade(false, 400_000_000, 400_000_000, 1_900_000_000, 1_900_000_001),
+                ],
+            }],
+        },
+    }))
+    .unwrap();
+    assert!(db.apply(&decode_transaction(&tx, &pump::ID).unwrap()).unwrap());
+
+    let conn = db.connection();
+    let trades: Vec<(String, u64, u64)> = conn
+        .prepare("SELECT side, amount_in, amount_out FROM trades WHERE signature = 'routed' ORDER BY seq").unwrap()
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?))).unwrap()
+        .collect::<Result<_, _>>().unwrap();
+    assert_eq!(trades, vec![
+        ("buy".to_string(), 1_000_000_000, 1_000_000_000),
+        ("sell".to_string(), 400_000_000, 400_000_000),
+    ]);
+
+    let (total_contributed, tokens_sold): (u64, u64) = conn
+        .query_row(
+            "SELECT total_contributed, tokens_sold FROM launches WHERE token = ?1",
+            [key(2)],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .unwrap();
+    assert_eq!((total_contributed, tokens_sold), (1_900_000_001, 1_900_000_000));
+}
+
+#[test]
+fn decodes_bond_posted() {
+    let data = event_cpi(&BondPosted {
+        token: Pubkey::new_from_array([2; 32]),
+        creator: Pubkey::new_from_array([1; 32]),
+        amount: 1_000_000_000,
+        timestamp: 1_700_000_000,
+    });
+    let Some(Record::BondPosted(event)) = decode_event(&data).unwrap() else {
+        panic!("expected BondPosted");
+    };
+    assert_eq!(event.token.to_string(), key(2));
+    assert_eq!(event.creator.to_string(), key(1));
+    assert_eq!((event.amount, event.timestamp), (1_000_000_000, 1_700_000_000));
+}
+
+#[test]
+fn decodes_bond_settled() {
+    let data = event_cpi(&BondSettled {
+        token: Pubkey::new_from_array([2; 32]),
+        creator: Pubkey::new_from_array([1; 32]),
+        returned: 250_000_000,
+        slashed: 750_000_000,
+        timestamp: 1_700_000_000,
+    });
+    let Some(Record::BondSettled(event)) = decode_event(&data).unwrap() else {
+        panic!("expected BondSettled");
+    };
+    assert_eq!(event.token.to_string(), key(2));
+    assert_eq!(event.creator.to_string(), key(1));
+    assert_eq!((event.returned, event.slashed), (250_000_000, 750_000_000));
+}
+
+#[test]
+fn decodes_crank_reward_paid() {
+    let data = event_cpi(&CrankRewardPaid {
+        token: Pubkey::new_from_array([2; 32]),
+        cranker: Pubkey::new_from_array([9; 32]),
+        amount: 5_000_000,
+        timestamp: 1_700_000_000,
+    });
+    let Some(Record::CrankRewardPaid(event)) = decode_event(&data).unwrap() else {
+        panic!("expected CrankRewardPaid");
+    };
+    assert_eq!(event.token.to_string(), key(2));
+    assert_eq!(event.cranker.to_string(), key(9));
+    assert_eq!(event.amount, 5_000_000);
+}
+
+#[test]
+fn decodes_liquidity_locked() {
+    let data = event_cpi(&LiquidityLocked {
+        token: Pubkey::new_from_array([2; 32]),
+        lp_mint: Pubkey::new_from_array([13; 32]),
+        amount: 42_000_000,
+        timestamp: 1_700_000_000,
+    });
+    let Some(Record::LiquidityLocked(event)) = decode_event(&data).unwrap() else {
+        panic!("expected LiquidityLocked");
+    };
+    assert_eq!(event.token.to_string(), key(2));
+    assert_eq!(event.lp_mint.to_string(), key(13));
+    assert_eq!(event.amount, 42_000_000);
+}
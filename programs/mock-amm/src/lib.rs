@@ -0,0 +1,255 @@
+//! Stand-in for the Raydium AMM v4 program, for tests. It accepts
+//! `initialize2` in Raydium's instruction layout, moves the opening liquidity
+//! out of the caller's token accounts and records the call in the pool
+//! account. Like Raydium, it takes the quote side from `user_token_pc`, so
+//! the caller has to wrap its SOL first.
+//!
+//! On a validator the pool accounts cannot be written up front, so a pool
+//! whose `amm` account does not exist yet is opened the way Raydium does it:
+//! the vaults, LP mint and LP token account are created at Raydium's
+//! addresses, paid for by the wallet, and the wallet is minted the LP tokens.
+//! Program tests that want exact balances set the accounts up beforehand.
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    declare_id,
+    entrypoint::ProgramResult,
+    msg,
+    instruction::{AccountMeta, Instruction},
+    program::{invoke, invoke_signed},
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    system_instruction,
+    sysvar::{rent::Rent, Sysvar},
+};
+
+declare_id!("9VQkCeka26r3EAPc2nTD4s9fT9eMYD4HAL9rXxi8r3fV");
+
+#[cfg(not(feature = "no-entrypoint"))]
+solana_program::entrypoint!(process_instruction);
+
+pub const INITIALIZE2: u8 = 1;
+
+// Raydium's seeds, as in `pump::utils::raydium`.
+const AUTHORITY_SEED: &[u8] = b"amm authority";
+const AMM_SEED: &[u8] = b"amm_associated_seed";
+const LP_MINT_SEED: &[u8] = b"lp_mint_associated_seed";
+const COIN_VAULT_SEED: &[u8] = b"coin_vault_associated_seed";
+const PC_VAULT_SEED: &[u8] = b"pc_vault_associated_seed";
+/// `CreateIdempotent` in the associated token program.
+const CREATE_ATA_IDEMPOTENT: u8 = 1;
+
+/// Inputs of the last `initialize2` call, stored at the start of the pool
+/// account.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PoolRecord {
+    pub nonce: u8,
+    pub open_time: u64,
+    pub init_pc_amount: u64,
+    pub init_coin_amount: u64,
+    pub coin_mint: Pubkey,
+    pub pc_mint: Pubkey,
+    pub lp_mint: Pubkey,
+    pub user_wallet: Pubkey,
+}
+
+impl PoolRecord {
+    pub const LEN: usize = 1 + 8 + 8 + 8 + 32 * 4;
+
+    pub fn pack(&self, dst: &mut [u8]) -> Result<(), ProgramError> {
+        let dst = dst.get_mut(..Self::LEN).ok_or(ProgramError::AccountDataTooSmall)?;
+        dst[0] = self.nonce;
+        dst[1..9].copy_from_slice(&self.open_time.to_le_bytes());
+        dst[9..17].copy_from_slice(&self.init_pc_amount.to_le_bytes());
+        dst[17..25].copy_from_slice(&self.init_coin_amount.to_le_bytes());
+        for (chunk, key) in dst[25..].chunks_mut(32).zip([&self.coin_mint, &self.pc_mint, &self.lp_mint, &self.user_wallet]) {
+            chunk.copy_from_slice(key.as_ref());
+        }
+        Ok(())
+    }
+
+    pub fn unpack(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = src.get(..Self::LEN).ok_or(ProgramError::AccountDataTooSmall)?;
+        let u64_at = |at: usize| u64::from_le_bytes(src[at..at + 8].try_into().unwrap());
+        let key_at = |at: usize| Pubkey::new_from_array(src[at..at + 32].try_into().unwrap());
+        Ok(Self {
+            nonce: src[0],
+            open_time: u64_at(1),
+            init_pc_amount: u64_at(9),
+            init_coin_amount: u64_at(17),
+            coin_mint: key_at(25),
+            pc_mint: key_at(57),
+            lp_mint: key_at(89),
+            user_wallet: key_at(121),
+        })
+    }
+}
+
+pub fn process_instruction(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let (&tag, args) = data.split_first().ok_or(ProgramError::InvalidInstructionData)?;
+    if tag != INITIALIZE2 || args.len() != 1 + 8 * 3 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let u64_at = |at: usize| u64::from_le_bytes(args[at..at + 8].try_into().unwrap());
+    let (nonce, open_time, init_pc_amount, init_coin_amount) = (args[0], u64_at(1), u64_at(9), u64_at(17));
+
+    let accounts = &mut accounts.iter();
+    let token_program = next_account_info(accounts)?;
+    let associated_token_program = next_account_info(accounts)?;
+    let system_program = next_account_info(accounts)?;
+    let _rent = next_account_info(accounts)?;
+    let amm = next_account_info(accounts)?;
+    let amm_authority = next_account_info(accounts)?;
+    let _amm_open_orders = next_account_info(accounts)?;
+    let lp_mint = next_account_info(accounts)?;
+    let coin_mint = next_account_info(accounts)?;
+    let pc_mint = next_account_info(accounts)?;
+    let coin_vault = next_account_info(accounts)?;
+    let pc_vault = next_account_info(accounts)?;
+    let _target_orders = next_account_info(accounts)?;
+    let _amm_config = next_account_info(accounts)?;
+    let _fee_destination = next_account_info(accounts)?;
+    let _market_program = next_account_info(accounts)?;
+    let market = next_account_info(accounts)?;
+    let user_wallet = next_account_info(accounts)?;
+    let user_token_coin = next_account_info(accounts)?;
+    let user_token_pc = next_account_info(accounts)?;
+    let user_token_lp = next_account_info(accounts)?;
+
+    if !user_wallet.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    let (authority, authority_bump) = Pubkey::find_program_address(&[AUTHORITY_SEED], program_id);
+    if *amm_authority.key != authority {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    if amm.data_is_empty() {
+        let market = market.key.as_ref();
+        create_pda(program_id, user_wallet, system_program, amm, &[program_id.as_ref(), market, AMM_SEED], PoolRecord::LEN, program_id)?;
+        let seeds: &[&[u8]] = &[program_id.as_ref(), market, LP_MINT_SEED];
+        create_pda(program_id, user_wallet, system_program, lp_mint, seeds, spl_token::state::Mint::LEN, token_program.key)?;
+        invoke(
+            &spl_token::instruction::initialize_mint2(token_program.key, lp_mint.key, &authority, None, 9)?,
+            &[lp_mint.clone(), token_program.clone()],
+        )?;
+        for (vault, seed, mint) in [(coin_vault, COIN_VAULT_SEED, coin_mint), (pc_vault, PC_VAULT_SEED, pc_mint)] {
+            let seeds: &[&[u8]] = &[program_id.as_ref(), market, seed];
+            create_pda(program_id, user_wallet, system_program, vault, seeds, spl_token::state::Account::LEN, token_program.key)?;
+            invoke(
+                &spl_token::instruction::initialize_account3(token_program.key, vault.key, mint.key, &authority)?,
+                &[vault.clone(), mint.clone(), token_program.clone()],
+            )?;
+        }
+
+        invoke(
+            &Instruction {
+                program_id: *associated_token_program.key,
+                accounts: vec![
+                    AccountMeta::new(*user_wallet.key, true),
+                    AccountMeta::new(*user_token_lp.key, false),
+                    AccountMeta::new_readonly(*user_wallet.key, false),
+                    AccountMeta::new_readonly(*lp_mint.key, false),
+                    AccountMeta::new_readonly(*system_program.key, false),
+                    AccountMeta::new_readonly(*token_program.key, false),
+                ],
+                data: vec![CREATE_ATA_IDEMPOTENT],
+            },
+            &[
+                user_wallet.clone(),
+                user_token_lp.clone(),
+                lp_mint.clone(),
+                system_program.clone(),
+                token_program.clone(),
+                associated_token_program.clone(),
+            ],
+        )?;
+        let lp_tokens = sqrt(init_pc_amount as u128 * init_coin_amount as u128) as u64;
+        invoke_signed(
+            &spl_token::instruction::mint_to(token_program.key, lp_mint.key, user_token_lp.key, &authority, &[], lp_tokens)?,
+            &[lp_mint.clone(), user_token_lp.clone(), amm_authority.clone(), token_program.clone()],
+            &[&[AUTHORITY_SEED, &[authority_bump]]],
+        )?;
+    }
+    if amm.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    invoke(
+        &spl_token::instruction::transfer(
+            token_program.key,
+            user_token_coin.key,
+            coin_vault.key,
+            user_wallet.key,
+            &[],
+            init_coin_amount,
+        )?,
+        &[user_token_coin.clone(), coin_vault.clone(), user_wallet.clone(), token_program.clone()],
+    )?;
+    invoke(
+        &spl_token::instruction::transfer(
+            token_program.key,
+            user_token_pc.key,
+            pc_vault.key,
+            user_wallet.key,
+            &[],
+            init_pc_amount,
+        )?,
+        &[user_token_pc.clone(), pc_vault.clone(), user_wallet.clone(), token_program.clone()],
+    )?;
+
+    PoolRecord {
+        nonce,
+        open_time,
+        init_pc_amount,
+        init_coin_amount,
+        coin_mint: *coin_mint.key,
+        pc_mint: *pc_mint.key,
+        lp_mint: *lp_mint.key,
+        user_wallet: *user_wallet.key,
+    }
+    .pack(&mut amm.try_borrow_mut_data()?)?;
+
+    msg!("initialize2: {} quote, {} coin", init_pc_amount, init_coin_amount);
+    Ok(())
+}
+
+/// Creates `account` at the address `seeds` derive under this program,
+/// paid for by `payer`.
+fn create_pda<'a>(
+    program_id: &Pubkey,
+    payer: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    seeds: &[&[u8]],
+    space: usize,
+    owner: &Pubkey,
+) -> ProgramResult {
+    let (address, bump) = Pubkey::find_program_address(seeds, program_id);
+    if address != *account.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let bump = [bump];
+    let mut signer_seeds = seeds.to_vec();
+    signer_seeds.push(&bump);
+    invoke_signed(
+        &system_instruction::create_account(payer.key, account.key, Rent::get()?.minimum_balance(space), space as u64, owner),
+        &[payer.clone(), account.clone(), system_program.clone()],
+        &[signer_seeds.as_slice()],
+    )
+}
+
+/// Integer square root, rounding down, for the LP tokens of a new pool.
+fn sqrt(value: u128) -> u128 {
+    if value < 2 {
+        return value;
+    }
+    let mut root = value;
+    let mut next = (root + 1) / 2;
+    while next < root {
+        root = next;
+        next = (root + value / root) / 2;
+    }
+    root
+}
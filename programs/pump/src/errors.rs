@@ -47,4 +47,172 @@ pub enum CustomError {
 
     #[msg("Overflow or underflow occured")]
     OverflowOrUnderflowOccurred,
+
+    #[msg("Instruction is not available in this launch mode")]
+    InvalidLaunchMode,
+
+    #[msg("Invalid sealed bid parameters")]
+    InvalidBidParameters,
+
+    #[msg("The commit phase is over")]
+    CommitPhaseClosed,
+
+    #[msg("The reveal phase is not active")]
+    RevealPhaseNotActive,
+
+    #[msg("The reveal phase is not over")]
+    RevealPhaseNotOver,
+
+    #[msg("Revealed bid does not match the commitment")]
+    CommitmentMismatch,
+
+    #[msg("Bid is already revealed")]
+    BidAlreadyRevealed,
+
+    #[msg("Bid amount exceeds the deposit")]
+    BidExceedsDeposit,
+
+    #[msg("Bids have not been cleared")]
+    BidsNotCleared,
+
+    #[msg("Bid is already settled")]
+    BidAlreadySettled,
+
+    #[msg("Invalid lottery parameters")]
+    InvalidLotteryParameters,
+
+    #[msg("Contribution must buy a whole number of tickets")]
+    InvalidTicketAmount,
+
+    #[msg("The sale is closed")]
+    SaleClosed,
+
+    #[msg("The sale is still open")]
+    SaleStillOpen,
+
+    #[msg("Invalid randomness account")]
+    InvalidRandomnessAccount,
+
+    #[msg("Randomness has not been fulfilled")]
+    RandomnessNotFulfilled,
+
+    #[msg("The lottery has not been drawn")]
+    LotteryNotDrawn,
+
+    #[msg("The lottery is already drawn")]
+    LotteryAlreadyDrawn,
+
+    #[msg("The launch has not been finalized")]
+    LaunchNotFinalized,
+
+    #[msg("Nothing to settle")]
+    NothingToSettle,
+
+    #[msg("Invalid liquidity bootstrapping parameters")]
+    InvalidLbpParameters,
+
+    #[msg("The pool is not open for trading")]
+    PoolNotActive,
+
+    #[msg("The pool is still open for trading")]
+    PoolStillActive,
+
+    #[msg("Output is below the minimum amount")]
+    SlippageExceeded,
+
+    #[msg("Liquidity pool does not belong to this launch")]
+    InvalidPool,
+
+    #[msg("Invalid bonding curve parameters")]
+    InvalidCurve,
+
+    #[msg("Signer is not allowed to perform this action")]
+    Unauthorized,
+
+    #[msg("Fee recipient does not match the protocol config")]
+    InvalidFeeRecipient,
+
+    #[msg("Token account does not belong to the contributor")]
+    InvalidRecipient,
+
+    #[msg("Contribution account does not belong to this launch")]
+    InvalidContribution,
+
+    #[msg("The launch has been cancelled")]
+    LaunchCancelled,
+
+    #[msg("The launch missed its target and is being refunded")]
+    LaunchFailed,
+
+    #[msg("The launch has already been finalized")]
+    LaunchAlreadyFinalized,
+
+    #[msg("Invalid protocol config parameters")]
+    InvalidConfig,
+
+    #[msg("The refund deadline has already been extended")]
+    DeadlineAlreadyExtended,
+
+    #[msg("Deadline extension is out of range")]
+    InvalidDeadlineExtension,
+
+    #[msg("The opt-out window is closed")]
+    OptOutWindowClosed,
+
+    #[msg("Only contributors from before the extension can opt out")]
+    NotEligibleForOptOut,
+
+    #[msg("This instruction is paused")]
+    ProgramPaused,
+
+    #[msg("Signer is not the pending admin")]
+    NotPendingAdmin,
+
+    #[msg("No config change is queued")]
+    NoPendingConfigChange,
+
+    #[msg("The config change is still timelocked")]
+    ConfigChangeTimelocked,
+
+    #[msg("Tickers must be 1 to 10 ASCII letters or digits")]
+    InvalidTicker,
+
+    #[msg("This ticker is reserved")]
+    TickerReserved,
+
+    #[msg("This ticker is not reserved")]
+    TickerNotReserved,
+
+    #[msg("Names must be 1 to 32 bytes of printable text")]
+    InvalidName,
+
+    #[msg("Decimals must be at most 9")]
+    InvalidDecimals,
+
+    #[msg("Supply must be between one and a trillion whole tokens")]
+    InvalidSupply,
+
+    #[msg("The launch target must be above zero")]
+    InvalidTarget,
+
+    #[msg("Launches this large need a creator with a graduated launch")]
+    UnprovenCreator,
+
+    #[msg("The launch has not reached its target")]
+    TargetNotReached,
+
+    #[msg("The creator is still within the finalize grace period")]
+    GracePeriodNotOver,
+
+    #[msg("The launch is being finalized")]
+    FinalizeInProgress,
+
+    #[msg("An earlier finalize step has not run yet")]
+    FinalizeStepOutOfOrder,
+
+    #[msg("Contributions can no longer be withdrawn")]
+    WithdrawalWindowClosed,
+
+    #[msg("Ticker record does not belong to this launch")]
+    InvalidTickerRecord,
 }
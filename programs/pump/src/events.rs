@@ -0,0 +1,282 @@
+use anchor_lang::prelude::*;
+use crate::state::{LaunchMode, ProtocolParams};
+use crate::utils::Curve;
+
+#[event]
+pub struct LaunchCreated {
+    pub token: Pubkey,
+    pub mint: Pubkey,
+    pub creator: Pubkey,
+    pub name: String,
+    pub ticker: String,
+    pub total_supply: u64,
+    pub decimals: u8,
+    pub target: u64,
+    pub initial_contribution: u64,
+    pub initial_tokens: u64,
+    pub mode: LaunchMode,
+    pub curve: Curve,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct Contributed {
+    pub token: Pubkey,
+    pub user: Pubkey,
+    pub user_contribution: Pubkey,
+    pub contribution_number: u32,
+    pub amount: u64,
+    pub tokens: u64,
+    pub first_ticket: u32,
+    pub ticket_count: u32,
+    pub total_contributed: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct Refunded {
+    pub token: Pubkey,
+    pub user: Pubkey,
+    pub user_contribution: Pubkey,
+    pub amount: u64,
+    pub total_contributed: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FeeCollected {
+    pub token: Pubkey,
+    pub payer: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CrankRewardPaid {
+    pub token: Pubkey,
+    pub cranker: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LiquidityProvided {
+    pub token: Pubkey,
+    pub amm: Pubkey,
+    pub lp_mint: Pubkey,
+    pub sol_amount: u64,
+    pub token_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LiquidityLocked {
+    pub token: Pubkey,
+    pub lp_mint: Pubkey,
+    /// LP tokens burned.
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TokensClaimed {
+    pub token: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+    pub refund: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct Finalized {
+    pub token: Pubkey,
+    pub mint: Pubkey,
+    pub total_contributed: u64,
+    pub fee: u64,
+    pub liquidity_sol: u64,
+    pub liquidity_tokens: u64,
+    pub distributed_tokens: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ContributionWithdrawn {
+    pub token: Pubkey,
+    pub user: Pubkey,
+    pub user_contribution: Pubkey,
+    pub amount: u64,
+    pub penalty: u64,
+    pub tokens: u64,
+    pub total_contributed: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DeadlineExtended {
+    pub token: Pubkey,
+    pub refund_deadline: i64,
+    pub opt_out_deadline: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ConfigChangeQueued {
+    pub params: ProtocolParams,
+    pub eta: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ConfigChangeExecuted {
+    pub params: ProtocolParams,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ConfigChangeCancelled {
+    pub params: ProtocolParams,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AdminProposed {
+    pub admin: Pubkey,
+    pub pending_admin: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AdminAccepted {
+    pub previous_admin: Pubkey,
+    pub admin: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RolesUpdated {
+    pub fee_admin: Pubkey,
+    pub pause_guardian: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PauseUpdated {
+    pub authority: Pubkey,
+    pub paused: bool,
+    pub paused_instructions: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LaunchCancelled {
+    pub token: Pubkey,
+    pub authority: Pubkey,
+    pub fee_rebate: u64,
+    pub burned_tokens: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TickerReservationUpdated {
+    pub ticker: String,
+    pub reserved: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LaunchFailed {
+    pub token: Pubkey,
+    pub creator: Pubkey,
+    pub total_contributed: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BondPosted {
+    pub token: Pubkey,
+    pub creator: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BondSettled {
+    pub token: Pubkey,
+    pub creator: Pubkey,
+    /// Lamports paid back to the creator.
+    pub returned: u64,
+    /// Lamports left to the contributors.
+    pub slashed: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct Trade {
+    pub token: Pubkey,
+    pub user: Pubkey,
+    pub is_buy: bool,
+    /// Lamports paid on a buy, tokens sold back on a sell.
+    pub amount_in: u64,
+    /// Tokens bought on a buy, lamports paid out on a sell.
+    pub amount_out: u64,
+    /// Tokens out of the sale after the trade: sold along the curve, or no
+    /// longer held by a bootstrapping pool.
+    pub tokens_sold: u64,
+    pub total_contributed: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BidCommitted {
+    pub token: Pubkey,
+    pub user: Pubkey,
+    pub bid: Pubkey,
+    pub deposit: u64,
+    pub total_deposits: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BidRevealed {
+    pub token: Pubkey,
+    pub user: Pubkey,
+    pub bid: Pubkey,
+    pub amount: u64,
+    pub price_limit: u64,
+    /// `None` for bids under the minimum price.
+    pub tier: Option<u8>,
+    pub revealed_deposits: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LotteryClosed {
+    pub token: Pubkey,
+    pub total_tickets: u32,
+    pub close_slot: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LotteryDrawn {
+    pub token: Pubkey,
+    pub total_tickets: u32,
+    pub winning_tickets: u32,
+    pub randomness: [u8; 32],
+    /// What the winning tickets raised.
+    pub total_contributed: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LbpStarted {
+    pub token: Pubkey,
+    pub liquidity_pool: Pubkey,
+    pub token_reserve: u64,
+    pub sol_reserve: u64,
+    pub start_weight_bps: u16,
+    pub end_weight_bps: u16,
+    pub end_time: i64,
+    pub timestamp: i64,
+}
@@ -0,0 +1,35 @@
+use anchor_lang::prelude::*;
+use crate::errors::CustomError;
+use crate::events::AdminAccepted;
+use crate::state::Config;
+
+/// Completes an admin transfer. Requiring the new admin's signature means a
+/// mistyped key can never end up holding the protocol.
+pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    let previous_admin = config.admin;
+    config.admin = config.pending_admin;
+    config.pending_admin = Pubkey::default();
+
+    emit_cpi!(AdminAccepted {
+        previous_admin,
+        admin: config.admin,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = pending_admin @ CustomError::NotPendingAdmin,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    pub pending_admin: Signer<'info>,
+}
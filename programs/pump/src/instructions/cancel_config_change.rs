@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+use crate::errors::CustomError;
+use crate::events::ConfigChangeCancelled;
+use crate::state::Config;
+
+pub fn cancel_config_change(ctx: Context<CancelConfigChange>) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    let change = config.pending_change.take().ok_or(CustomError::NoPendingConfigChange)?;
+
+    emit_cpi!(ConfigChangeCancelled {
+        params: change.params,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CancelConfigChange<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ CustomError::Unauthorized,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    pub admin: Signer<'info>,
+}
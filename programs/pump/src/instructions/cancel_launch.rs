@@ -0,0 +1,176 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{self, Burn, Mint, Token, TokenAccount},
+};
+use crate::errors::CustomError;
+use crate::events::{BondSettled, LaunchCancelled};
+use crate::state::{Config, CreatorProfile, LaunchMode, LaunchStatus, TickerRecord, TokenDetails};
+use crate::utils::math::{mul_div_u64, Rounding};
+
+pub fn cancel_launch(ctx: Context<CancelLaunch>, burn_supply: bool) -> Result<()> {
+    let token = &ctx.accounts.token;
+    let authority = ctx.accounts.authority.key();
+
+    require!(token.is_virtual, CustomError::LaunchAlreadyFinalized);
+    token.require_active()?;
+    // A bootstrapping pool's reserves belong to its traders, so it runs its course.
+    require!(!matches!(token.mode, LaunchMode::Lbp { .. }), CustomError::InvalidLaunchMode);
+
+    // The creator can only walk away before anyone else has put money in;
+    // the admin can pull an abusive launch at any point.
+    let untouched = token.contribution_count == 1 &&
+        token.total_contributed == token.refundable &&
+        ctx.accounts.bid_book.data_is_empty();
+    let by_creator = authority == token.creator && untouched;
+    require!(by_creator || authority == ctx.accounts.config.admin, CustomError::Unauthorized);
+
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        "global".as_bytes(),
+        &[ctx.bumps.program_account],
+    ]];
+
+    let burned_tokens = if burn_supply { ctx.accounts.pool_token_account.amount } else { 0 };
+    if burned_tokens > 0 {
+        token::burn(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Burn {
+                    mint: ctx.accounts.mint.to_account_info(),
+                    from: ctx.accounts.pool_token_account.to_account_info(),
+                    authority: ctx.accounts.program_account.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            burned_tokens,
+        )?;
+    }
+
+    // Only a creator who cancels their own launch gets part of the fee back.
+    let fee_rebate = if by_creator {
+        mul_div_u64(token.terms.launch_fee, token.terms.cancel_rebate_bps as u64, 10_000, Rounding::Down)?
+    } else {
+        0
+    };
+    if fee_rebate > 0 {
+        system_program::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.program_account.to_account_info(),
+                    to: ctx.accounts.creator.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            fee_rebate,
+        )?;
+    }
+
+    // Positions, the creator's included, are paid back through `refund`.
+    let token = &mut ctx.accounts.token;
+    token.status = LaunchStatus::Cancelled;
+    let profile = &mut ctx.accounts.creator_profile;
+    profile.cancellations = profile.cancellations.checked_add(1)
+        .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+    TickerRecord::release(&ctx.accounts.ticker_record, &token.key(), &ctx.accounts.creator)?;
+
+    // Pulling a launch others have put money into costs the creator part of
+    // the bond, shared by those positions as they refund.
+    let bond = token.bond;
+    let (bond_returned, bond_slashed) = token.settle_bond(!untouched)?;
+
+    let timestamp = Clock::get()?.unix_timestamp;
+    emit_cpi!(LaunchCancelled {
+        token: token.key(),
+        authority,
+        fee_rebate,
+        burned_tokens,
+        timestamp,
+    });
+
+    if bond > 0 {
+        if bond_returned > 0 {
+            system_program::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.program_account.to_account_info(),
+                        to: ctx.accounts.creator.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                bond_returned,
+            )?;
+        }
+        emit_cpi!(BondSettled {
+            token: ctx.accounts.token.key(),
+            creator: ctx.accounts.creator.key(),
+            returned: bond_returned,
+            slashed: bond_slashed,
+            timestamp,
+        });
+    }
+
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CancelLaunch<'info> {
+    #[account(mut, has_one = mint)]
+    pub token: Box<Account<'info, TokenDetails>>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        mut,
+        seeds = [b"creator-profile", token.creator.as_ref()],
+        bump = creator_profile.bump,
+    )]
+    pub creator_profile: Box<Account<'info, CreatorProfile>>,
+
+    /// The launch's creator or the protocol admin.
+    pub authority: Signer<'info>,
+
+    #[account(mut, address = token.creator)]
+    /// CHECK: Receives the fee rebate and what is left of the bond
+    pub creator: AccountInfo<'info>,
+
+    #[account(mut)]
+    /// CHECK: The launch's ticker record, checked and closed by `TickerRecord::release`
+    pub ticker_record: AccountInfo<'info>,
+
+    #[account(
+        seeds = [b"bid-book", token.key().as_ref()],
+        bump,
+    )]
+    /// CHECK: Only checked for existence; the first sealed bid creates it
+    pub bid_book: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"global"],
+        bump,
+    )]
+    /// CHECK: PDA holding launch SOL and supply
+    pub program_account: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = program_account,
+    )]
+    pub pool_token_account: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,55 @@
+use anchor_lang::prelude::*;
+use crate::errors::CustomError;
+use crate::events::LotteryClosed;
+use crate::state::{pause, Config, LotteryDraw, TokenDetails};
+
+pub fn close_lottery(ctx: Context<CloseLottery>) -> Result<()> {
+    let token = &ctx.accounts.token;
+    token.require_active()?;
+    let sale_end = token.lottery_sale_end().ok_or(CustomError::InvalidLaunchMode)?;
+    let clock = Clock::get()?;
+    require!(clock.unix_timestamp >= sale_end, CustomError::SaleStillOpen);
+
+    // Randomness must be fulfilled after this slot to be accepted by the draw.
+    let lottery = &mut ctx.accounts.lottery;
+    lottery.token = token.key();
+    lottery.close_slot = clock.slot;
+    lottery.total_tickets = token.tickets_sold;
+    lottery.bump = ctx.bumps.lottery;
+
+    emit_cpi!(LotteryClosed {
+        token: token.key(),
+        total_tickets: lottery.total_tickets,
+        close_slot: lottery.close_slot,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CloseLottery<'info> {
+    pub token: Box<Account<'info, TokenDetails>>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.is_paused(pause::CLOSE_LOTTERY) @ CustomError::ProgramPaused,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = LotteryDraw::ACCOUNT_SIZE,
+        seeds = [b"lottery", token.key().as_ref()],
+        bump
+    )]
+    pub lottery: Box<Account<'info, LotteryDraw>>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
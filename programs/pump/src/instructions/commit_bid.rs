@@ -0,0 +1,97 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use crate::errors::CustomError;
+use crate::events::BidCommitted;
+use crate::state::{pause, BidBook, BidCommitment, Config, TokenDetails};
+
+pub fn commit_bid(ctx: Context<CommitBid>, commitment: [u8; 32], deposit: u64) -> Result<()> {
+    let token = &ctx.accounts.token;
+    token.require_active()?;
+    let (commit_end, _) = token.sealed_bid_phases().ok_or(CustomError::InvalidLaunchMode)?;
+
+    require!(Clock::get()?.unix_timestamp < commit_end, CustomError::CommitPhaseClosed);
+    require!(deposit > 0 && deposit <= token.terms.max_contribution, CustomError::InvalidInputValue);
+
+    let bid_book = &mut ctx.accounts.bid_book;
+    if bid_book.token == Pubkey::default() {
+        bid_book.token = token.key();
+        bid_book.bump = ctx.bumps.bid_book;
+    }
+    bid_book.total_deposits = bid_book.total_deposits.checked_add(deposit)
+        .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+    bid_book.reveal_pause_time = ctx.accounts.config.reveal_pause_time(Clock::get()?.unix_timestamp);
+
+    let bid = &mut ctx.accounts.bid;
+    bid.user = ctx.accounts.user.key();
+    bid.token = token.key();
+    bid.commitment = commitment;
+    bid.deposit = deposit;
+    bid.timestamp = Clock::get()?.unix_timestamp;
+    bid.bump = ctx.bumps.bid;
+
+    // Transfer deposit
+    let transfer_ctx = CpiContext::new(
+        ctx.accounts.system_program.to_account_info(),
+        system_program::Transfer {
+            from: ctx.accounts.user.to_account_info(),
+            to: ctx.accounts.program_account.to_account_info(),
+        },
+    );
+
+    system_program::transfer(transfer_ctx, deposit)?;
+
+    emit_cpi!(BidCommitted {
+        token: ctx.accounts.token.key(),
+        user: ctx.accounts.user.key(),
+        bid: ctx.accounts.bid.key(),
+        deposit,
+        total_deposits: ctx.accounts.bid_book.total_deposits,
+        timestamp: ctx.accounts.bid.timestamp,
+    });
+
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CommitBid<'info> {
+    pub token: Box<Account<'info, TokenDetails>>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.is_paused(pause::COMMIT_BID) @ CustomError::ProgramPaused,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = BidBook::ACCOUNT_SIZE,
+        seeds = [b"bid-book", token.key().as_ref()],
+        bump
+    )]
+    pub bid_book: Box<Account<'info, BidBook>>,
+
+    #[account(
+        init,
+        payer = user,
+        space = BidCommitment::ACCOUNT_SIZE,
+        seeds = [b"bid", token.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub bid: Box<Account<'info, BidCommitment>>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"global"],
+        bump,
+    )]
+    /// CHECK: PDA holding launch SOL and supply
+    pub program_account: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+}
@@ -1,28 +1,68 @@
 use anchor_lang::prelude::*;
 use anchor_lang::system_program;
 use crate::errors::CustomError; 
-use crate::state::{TokenDetails, UserContribution};
+use crate::events::Contributed;
+use crate::state::{pause, Config, LaunchMode, LotteryDraw, TokenDetails, UserContribution};
 
 pub fn contribute(ctx: Context<Contribute>, amount: u64) -> Result<()> {
     let token = &mut ctx.accounts.token;
+    let user_contribution = &mut ctx.accounts.user_contribution;
     let user = &ctx.accounts.user;
 
+    token.require_active()?;
+    // Every position counts towards what `finalize_settle` has to pay out,
+    // so an empty one would hold it up for nothing.
+    require!(amount > 0, CustomError::InvalidAmount);
+
     require!(
-        user_contribution.amount + amount <= 10 * 1_000_000_000,
+        user_contribution.amount + amount <= token.terms.max_contribution,
         CustomError::TargetExceeded
     );
-    require!(token.total_contributed + amount <= token.target, CustomError::TargetExceeded);
+    match token.mode {
+        LaunchMode::Standard => {
+            require!(token.total_contributed + amount <= token.target, CustomError::TargetExceeded);
+
+            let tokens = token.curve.amount_out(token.tokens_sold, amount, token.decimals)?;
+            token.tokens_sold = token.tokens_sold.checked_add(tokens)
+                .filter(|sold| *sold <= token.total_supply - token.total_supply / 2)
+                .ok_or(CustomError::TargetExceeded)?;
+            user_contribution.total_tokens = tokens;
+        }
+        // Lottery sales may be oversubscribed; losing tickets are refunded after the draw.
+        LaunchMode::Lottery { ticket_price, .. } => {
+            let sale_end = token.lottery_sale_end().ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+            require!(Clock::get()?.unix_timestamp < sale_end, CustomError::SaleClosed);
+
+            let tickets = amount / ticket_price;
+            require!(
+                tickets * ticket_price == amount && tickets > 0 && tickets <= LotteryDraw::MAX_TICKETS_PER_ENTRY,
+                CustomError::InvalidTicketAmount
+            );
+
+            user_contribution.first_ticket = token.tickets_sold;
+            user_contribution.ticket_count = tickets as u32;
+            token.tickets_sold = token.tickets_sold.checked_add(tickets as u32)
+                .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+        }
+        LaunchMode::SealedBid { .. } | LaunchMode::Lbp { .. } => return err!(CustomError::InvalidLaunchMode),
+    }
 
     token.total_contributed = token.total_contributed.checked_add(amount)
         .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+    token.note_target_reached(Clock::get()?.unix_timestamp);
+    token.refundable = token.refundable.checked_add(amount)
+        .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
     
     user_contribution.amount = user_contribution.amount.checked_add(amount)
         .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
 
     token.contribution_count += 1;
+    token.open_positions = token.open_positions.checked_add(1)
+        .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
 
-    let user_contribution = &mut ctx.accounts.user_contribution;
-    user_contribution.total_tokens = calculate_tokens(user_contribution.amount, token.total_supply, token.target);
+    user_contribution.user = user.key();
+    user_contribution.token = token.key();
+    user_contribution.bump = ctx.bumps.user_contribution;
     user_contribution.contribution_number = token.contribution_count - 1;
     user_contribution.timestamp = Clock::get()?.unix_timestamp;
 
@@ -37,19 +77,38 @@ pub fn contribute(ctx: Context<Contribute>, amount: u64) -> Result<()> {
 
     system_program::transfer(transfer_ctx, amount)?;
 
-    Ok(())
-}
+    let token = &ctx.accounts.token;
+    let user_contribution = &ctx.accounts.user_contribution;
+    emit_cpi!(Contributed {
+        token: token.key(),
+        user: user.key(),
+        user_contribution: user_contribution.key(),
+        contribution_number: user_contribution.contribution_number,
+        amount,
+        tokens: user_contribution.total_tokens,
+        first_ticket: user_contribution.first_ticket,
+        ticket_count: user_contribution.ticket_count,
+        total_contributed: token.total_contributed,
+        timestamp: user_contribution.timestamp,
+    });
 
-fn calculate_tokens(contribution: u64, total_supply: u64, target: u64) -> u64 {
-    ((contribution as u128) * (total_supply as u128) / (target as u128)) as u64
+    Ok(())
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 #[instruction(amount: u64)]
 pub struct Contribute<'info> {
     #[account(mut)]
     pub token: Box<Account<'info, TokenDetails>>,
 
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.is_paused(pause::CONTRIBUTE) @ CustomError::ProgramPaused,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
     #[account(
         init,
         payer = user,
@@ -67,8 +126,12 @@ pub struct Contribute<'info> {
     #[account(mut)]
     pub user: Signer<'info>,
 
-    #[account(mut)]
-    /// CHECK: This is not dangerous because we don't read or write from this account
+    #[account(
+        mut,
+        seeds = [b"global"],
+        bump,
+    )]
+    /// CHECK: PDA holding launch SOL and supply
     pub program_account: AccountInfo<'info>,
 
     pub system_program: Program<'info, System>,
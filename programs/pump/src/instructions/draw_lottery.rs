@@ -0,0 +1,62 @@
+use anchor_lang::prelude::*;
+use crate::errors::CustomError;
+use crate::events::LotteryDrawn;
+use crate::state::{pause, Config, LaunchMode, LotteryDraw, TokenDetails};
+use crate::utils::{RandomnessSource, VrfAccount};
+
+pub fn draw_lottery(ctx: Context<DrawLottery>) -> Result<()> {
+    let token = &mut ctx.accounts.token;
+    let lottery = &mut ctx.accounts.lottery;
+    token.require_active()?;
+    let LaunchMode::Lottery { ticket_price, winning_tickets, randomness_account, .. } = token.mode else {
+        return err!(CustomError::InvalidLaunchMode);
+    };
+    require!(!lottery.drawn, CustomError::LotteryAlreadyDrawn);
+
+    let randomness_program = ctx.accounts.config.params.randomness_program;
+    let source = VrfAccount::load(&ctx.accounts.randomness_account, &randomness_account, &randomness_program)?;
+    let randomness = source.fulfilled_randomness(lottery.close_slot)?;
+    lottery.draw(randomness, winning_tickets);
+
+    // Only winning tickets stay in the raise; the rest is refunded on settlement.
+    let losing_tickets = (lottery.total_tickets - lottery.winning_tickets) as u64;
+    let refunds = losing_tickets.checked_mul(ticket_price)
+        .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+    token.total_contributed = token.total_contributed.checked_sub(refunds)
+        .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+
+    emit_cpi!(LotteryDrawn {
+        token: token.key(),
+        total_tickets: lottery.total_tickets,
+        winning_tickets: lottery.winning_tickets,
+        randomness: lottery.randomness,
+        total_contributed: token.total_contributed,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct DrawLottery<'info> {
+    #[account(mut)]
+    pub token: Box<Account<'info, TokenDetails>>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.is_paused(pause::DRAW_LOTTERY) @ CustomError::ProgramPaused,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        mut,
+        seeds = [b"lottery", token.key().as_ref()],
+        bump = lottery.bump
+    )]
+    pub lottery: Box<Account<'info, LotteryDraw>>,
+
+    /// CHECK: Checked against the launch's randomness account and the protocol's randomness program
+    pub randomness_account: AccountInfo<'info>,
+}
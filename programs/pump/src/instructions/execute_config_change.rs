@@ -0,0 +1,31 @@
+use anchor_lang::prelude::*;
+use crate::errors::CustomError;
+use crate::events::ConfigChangeExecuted;
+use crate::state::Config;
+
+/// Applies the queued change once its timelock has run out. Anyone can
+/// execute it: the admin already committed to it when queueing.
+pub fn execute_config_change(ctx: Context<ExecuteConfigChange>) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    let change = config.pending_change.ok_or(CustomError::NoPendingConfigChange)?;
+    let timestamp = Clock::get()?.unix_timestamp;
+    require!(timestamp >= change.eta, CustomError::ConfigChangeTimelocked);
+
+    config.params = change.params;
+    config.pending_change = None;
+
+    emit_cpi!(ConfigChangeExecuted { params: change.params, timestamp });
+
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ExecuteConfigChange<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+}
@@ -0,0 +1,53 @@
+use anchor_lang::prelude::*;
+use crate::errors::CustomError;
+use crate::events::DeadlineExtended;
+use crate::state::{pause, Config, LaunchMode, TokenDetails};
+
+/// How long contributors have to opt out once the deadline is extended.
+pub const OPT_OUT_WINDOW: i64 = 2 * 24 * 60 * 60;
+
+pub fn extend_deadline(ctx: Context<ExtendDeadline>, extension: i64) -> Result<()> {
+    let token = &mut ctx.accounts.token;
+
+    require!(token.is_virtual, CustomError::LaunchAlreadyFinalized);
+    token.require_active()?;
+    // Only standard positions can be paid back through `opt_out`.
+    require!(token.mode == LaunchMode::Standard, CustomError::InvalidLaunchMode);
+    require!(token.extended_at == 0, CustomError::DeadlineAlreadyExtended);
+    require!(extension > 0 && extension <= token.terms.max_deadline_extension, CustomError::InvalidDeadlineExtension);
+
+    // Once refunds are open the raise has failed; extending it then would
+    // lock contributors back in.
+    let current_time = Clock::get()?.unix_timestamp;
+    require!(current_time <= token.refund_deadline, CustomError::InvalidDeadlineExtension);
+
+    token.refund_deadline = token.refund_deadline.checked_add(extension)
+        .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+    token.extended_at = current_time;
+    token.opt_out_deadline = current_time + OPT_OUT_WINDOW;
+
+    emit_cpi!(DeadlineExtended {
+        token: token.key(),
+        refund_deadline: token.refund_deadline,
+        opt_out_deadline: token.opt_out_deadline,
+        timestamp: current_time,
+    });
+
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ExtendDeadline<'info> {
+    #[account(mut, has_one = creator)]
+    pub token: Box<Account<'info, TokenDetails>>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.is_paused(pause::EXTEND_DEADLINE) @ CustomError::ProgramPaused,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    pub creator: Signer<'info>,
+}
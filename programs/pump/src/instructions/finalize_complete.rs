@@ -0,0 +1,125 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+
+use crate::{
+    errors::CustomError,
+    events::{BondSettled, CrankRewardPaid, Finalized},
+    state::{pause, Config, CreatorProfile, FinalizeStep, LaunchMode, TokenDetails},
+};
+
+/// Last finalize step: marks the launch graduated, returns the creator's
+/// bond and pays the `cranker` what is left of the launch's `crank_reward`.
+/// Anyone can send it.
+pub fn finalize_complete(ctx: Context<FinalizeComplete>) -> Result<()> {
+    if !ctx.accounts.token.finalize_step_due(FinalizeStep::LpLocked)? {
+        return Ok(());
+    }
+
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        "global".as_bytes(),
+        &[ctx.bumps.program_account],
+    ]];
+    let timestamp = Clock::get()?.unix_timestamp;
+    let (fee, _) = ctx.accounts.token.finalize_costs()?;
+    let earned = ctx.accounts.token.crank_reward_earned()?;
+
+    ctx.accounts.token.is_virtual = false;
+    ctx.accounts.token.finalize_step = FinalizeStep::Complete;
+    let reward = ctx.accounts.token.pay_crank_reward(
+        earned,
+        &ctx.accounts.program_account,
+        &ctx.accounts.cranker.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        ctx.bumps.program_account,
+    )?;
+    if reward > 0 {
+        emit_cpi!(CrankRewardPaid {
+            token: ctx.accounts.token.key(),
+            cranker: ctx.accounts.cranker.key(),
+            amount: reward,
+            timestamp,
+        });
+    }
+
+    let token = &mut ctx.accounts.token;
+    let profile = &mut ctx.accounts.creator_profile;
+    profile.graduations = profile.graduations.checked_add(1)
+        .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+    profile.total_raised = profile.total_raised.saturating_add(token.total_contributed);
+
+    // The launch graduated, so the creator's bond comes back in full.
+    if token.bond > 0 {
+        let (returned, slashed) = token.settle_bond(false)?;
+        system_program::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.program_account.to_account_info(),
+                    to: ctx.accounts.creator.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            returned,
+        )?;
+        emit_cpi!(BondSettled {
+            token: ctx.accounts.token.key(),
+            creator: ctx.accounts.creator.key(),
+            returned,
+            slashed,
+            timestamp,
+        });
+    }
+
+    // Only standard launches pay the sale side out on settlement; what they
+    // did not pay went into the pool.
+    let token = &ctx.accounts.token;
+    let distributed = match token.mode {
+        LaunchMode::Standard => token.total_supply - token.liquidity_tokens,
+        _ => 0,
+    };
+    emit_cpi!(Finalized {
+        token: token.key(),
+        mint: token.mint,
+        total_contributed: token.total_contributed,
+        fee,
+        liquidity_sol: token.liquidity_sol,
+        liquidity_tokens: token.liquidity_tokens,
+        distributed_tokens: distributed,
+        timestamp,
+    });
+
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct FinalizeComplete<'info> {
+    #[account(mut)]
+    pub token: Box<Account<'info, TokenDetails>>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.is_paused(pause::FINALIZE) @ CustomError::ProgramPaused,
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        mut,
+        seeds = [b"creator-profile", token.creator.as_ref()],
+        bump = creator_profile.bump,
+    )]
+    pub creator_profile: Box<Account<'info, CreatorProfile>>,
+    #[account(mut, address = token.creator)]
+    /// CHECK: Receives the creator's bond back
+    pub creator: AccountInfo<'info>,
+    /// Whoever sends the transaction; paid what is left of the crank reward.
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"global"],
+        bump,
+    )]
+    /// CHECK: PDA holding launch SOL and supply
+    pub program_account: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,271 @@
+use anchor_lang::prelude::*;
+use anchor_lang::{solana_program::program::invoke_signed, system_program};
+
+use anchor_spl::{
+    associated_token::{get_associated_token_address, AssociatedToken},
+    token::{self, spl_token::native_mint, Mint, SyncNative, Token, TokenAccount},
+};
+
+use crate::{
+    errors::CustomError,
+    events::{CrankRewardPaid, LiquidityProvided},
+    state::{pause, Config, FinalizeStep, LaunchMode, TokenDetails, LiquidityPool},
+};
+
+use crate::utils::raydium;
+
+use raydium_contract_instructions::amm_instruction;
+
+/// Second finalize step: opens the Raydium pool on `market` with the
+/// liquidity `finalize_settle` set aside. Raydium takes the quote side as
+/// wrapped SOL, so the lamports are wrapped into the custody account's wSOL
+/// account first. Anyone can send it and is paid a share of the crank reward.
+pub fn finalize_create_pool(ctx: Context<FinalizeCreatePool>) -> Result<()> {
+    if !ctx.accounts.token.finalize_step_due(FinalizeStep::Settled)? {
+        return Ok(());
+    }
+    // A bootstrapping pool's reserves move into the Raydium pool here, so it
+    // must be passed for those launches and for no others.
+    let is_lbp = matches!(ctx.accounts.token.mode, LaunchMode::Lbp { .. });
+    require!(ctx.accounts.liquidity_pool.is_some() == is_lbp, CustomError::InvalidPool);
+    let earned = ctx.accounts.token.crank_reward_earned()?;
+
+    let seeds = &[
+        "global".as_bytes(),
+        &[ctx.bumps.global_account]
+    ];
+    let signed_seeds = &[&seeds[..]];
+    let sol_amount = ctx.accounts.token.liquidity_sol;
+    let token_amount = ctx.accounts.token.liquidity_tokens;
+
+    system_program::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.global_account.to_account_info(),
+                to: ctx.accounts.user_token_pc.to_account_info(),
+            },
+            signed_seeds,
+        ),
+        sol_amount,
+    )?;
+    token::sync_native(CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        SyncNative {
+            account: ctx.accounts.user_token_pc.to_account_info(),
+        },
+    ))?;
+
+    msg!("Providing liquidity on Raydium");
+    let initialize_ix = amm_instruction::initialize2(
+        ctx.accounts.amm_program.key,
+        ctx.accounts.amm.key,
+        ctx.accounts.amm_authority.key,
+        ctx.accounts.amm_open_orders.key,
+        ctx.accounts.lp_mint.key,
+        &ctx.accounts.coin_mint.key(),
+        &ctx.accounts.pc_mint.key(),
+        ctx.accounts.coin_vault.key,
+        ctx.accounts.pc_vault.key,
+        ctx.accounts.target_orders.key,
+        ctx.accounts.amm_config.key,
+        ctx.accounts.fee_destination.key,
+        ctx.accounts.market_program.key,
+        ctx.accounts.market.key,
+        ctx.accounts.global_account.key,
+        ctx.accounts.user_token_coin.key,
+        &ctx.accounts.user_token_pc.key(),
+        &ctx.accounts.user_token_lp.key(),
+        ctx.bumps.global_account,
+        Clock::get()?.unix_timestamp as u64,
+        sol_amount,
+        token_amount,
+    )?;
+    let account_infos = [
+        ctx.accounts.amm_program.clone(),
+        ctx.accounts.amm.clone(),
+        ctx.accounts.amm_authority.clone(),
+        ctx.accounts.amm_open_orders.clone(),
+        ctx.accounts.lp_mint.clone(),
+        ctx.accounts.coin_mint.to_account_info().clone(),
+        ctx.accounts.pc_mint.to_account_info().clone(),
+        ctx.accounts.coin_vault.clone(),
+        ctx.accounts.pc_vault.clone(),
+        ctx.accounts.target_orders.clone(),
+        ctx.accounts.amm_config.clone(),
+        ctx.accounts.fee_destination.clone(),
+        ctx.accounts.market_program.clone(),
+        ctx.accounts.market.clone(),
+        ctx.accounts.global_account.clone(),
+        ctx.accounts.user_token_coin.clone(),
+        ctx.accounts.user_token_pc.to_account_info(),
+        ctx.accounts.user_token_lp.clone(),
+        ctx.accounts.token_program.to_account_info().clone(),
+        ctx.accounts.system_program.to_account_info().clone(),
+        ctx.accounts.associated_token_program.to_account_info().clone(),
+        ctx.accounts.sysvar_rent.to_account_info().clone(),
+    ];
+    invoke_signed(&initialize_ix, &account_infos, signed_seeds)?;
+
+    if let Some(pool) = ctx.accounts.liquidity_pool.as_mut() {
+        pool.reserve_one = 0;
+        pool.reserve_two = 0;
+    }
+    let token = &mut ctx.accounts.token;
+    token.market = ctx.accounts.market.key();
+    token.finalize_step = FinalizeStep::PoolCreated;
+
+    emit_cpi!(LiquidityProvided {
+        token: ctx.accounts.token.key(),
+        amm: ctx.accounts.amm.key(),
+        lp_mint: ctx.accounts.lp_mint.key(),
+        sol_amount,
+        token_amount,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    let reward = ctx.accounts.token.pay_crank_reward(
+        earned,
+        &ctx.accounts.global_account,
+        &ctx.accounts.cranker.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        ctx.bumps.global_account,
+    )?;
+    if reward > 0 {
+        emit_cpi!(CrankRewardPaid {
+            token: ctx.accounts.token.key(),
+            cranker: ctx.accounts.cranker.key(),
+            amount: reward,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+    }
+
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct FinalizeCreatePool<'info> {
+    #[account(mut)]
+    pub token: Box<Account<'info, TokenDetails>>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.is_paused(pause::FINALIZE) @ CustomError::ProgramPaused,
+    )]
+    pub config: Box<Account<'info, Config>>,
+    /// Whoever sends the transaction; paid a share of the crank reward, and
+    /// pays for the custody account's wSOL account the first time a pool is
+    /// opened.
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+    /// CHECK: Pinned to the Raydium AMM program
+    #[account(address = raydium::AMM_PROGRAM_ID)]
+    pub amm_program: AccountInfo<'info>,
+    /// CHECK: Raydium pool PDA for `market`, created by the AMM
+    #[account(
+        mut,
+        seeds = [raydium::AMM_PROGRAM_ID.as_ref(), market.key().as_ref(), raydium::AMM_SEED],
+        bump,
+        seeds::program = raydium::AMM_PROGRAM_ID,
+    )]
+    pub amm: AccountInfo<'info>,
+    /// CHECK: Raydium's authority PDA
+    #[account(
+        seeds = [raydium::AUTHORITY_SEED],
+        bump,
+        seeds::program = raydium::AMM_PROGRAM_ID,
+    )]
+    pub amm_authority: AccountInfo<'info>,
+    /// CHECK: Raydium pool PDA for `market`, created by the AMM
+    #[account(
+        mut,
+        seeds = [raydium::AMM_PROGRAM_ID.as_ref(), market.key().as_ref(), raydium::OPEN_ORDERS_SEED],
+        bump,
+        seeds::program = raydium::AMM_PROGRAM_ID,
+    )]
+    pub amm_open_orders: AccountInfo<'info>,
+    /// CHECK: Raydium pool PDA for `market`, created by the AMM
+    #[account(
+        mut,
+        seeds = [raydium::AMM_PROGRAM_ID.as_ref(), market.key().as_ref(), raydium::LP_MINT_SEED],
+        bump,
+        seeds::program = raydium::AMM_PROGRAM_ID,
+    )]
+    pub lp_mint: AccountInfo<'info>,
+    #[account(address = token.mint)]
+    pub coin_mint: Box<Account<'info, Mint>>,
+    #[account(address = native_mint::ID)]
+    pub pc_mint: Box<Account<'info, Mint>>,
+    /// CHECK: Raydium pool PDA for `market`, created by the AMM
+    #[account(
+        mut,
+        seeds = [raydium::AMM_PROGRAM_ID.as_ref(), market.key().as_ref(), raydium::COIN_VAULT_SEED],
+        bump,
+        seeds::program = raydium::AMM_PROGRAM_ID,
+    )]
+    pub coin_vault: AccountInfo<'info>,
+    /// CHECK: Raydium pool PDA for `market`, created by the AMM
+    #[account(
+        mut,
+        seeds = [raydium::AMM_PROGRAM_ID.as_ref(), market.key().as_ref(), raydium::PC_VAULT_SEED],
+        bump,
+        seeds::program = raydium::AMM_PROGRAM_ID,
+    )]
+    pub pc_vault: AccountInfo<'info>,
+    /// CHECK: Raydium pool PDA for `market`, created by the AMM
+    #[account(
+        mut,
+        seeds = [raydium::AMM_PROGRAM_ID.as_ref(), market.key().as_ref(), raydium::TARGET_ORDERS_SEED],
+        bump,
+        seeds::program = raydium::AMM_PROGRAM_ID,
+    )]
+    pub target_orders: AccountInfo<'info>,
+    /// CHECK: Raydium's config PDA
+    #[account(
+        seeds = [raydium::AMM_CONFIG_SEED],
+        bump,
+        seeds::program = raydium::AMM_PROGRAM_ID,
+    )]
+    pub amm_config: AccountInfo<'info>,
+    /// CHECK: Pinned to Raydium's pool creation fee account
+    #[account(mut, address = raydium::CREATE_POOL_FEE_ID)]
+    pub fee_destination: AccountInfo<'info>,
+    /// CHECK: Pinned to the OpenBook program
+    #[account(address = raydium::MARKET_PROGRAM_ID)]
+    pub market_program: AccountInfo<'info>,
+    /// CHECK: Any OpenBook market; the AMM checks its mints against the pool's
+    #[account(owner = raydium::MARKET_PROGRAM_ID)]
+    pub market: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [b"global"],
+        bump,
+    )]
+    /// CHECK: PDA holding launch SOL and supply, signing for the pool deposit
+    pub global_account: AccountInfo<'info>,
+    /// CHECK: The launch's supply, deposited into the pool
+    #[account(mut, address = get_associated_token_address(&global_account.key(), &coin_mint.key()))]
+    pub user_token_coin: AccountInfo<'info>,
+    /// Wrapped SOL account of the global PDA, funded with the liquidity SOL
+    #[account(
+        init_if_needed,
+        payer = cranker,
+        associated_token::mint = pc_mint,
+        associated_token::authority = global_account,
+    )]
+    pub user_token_pc: Box<Account<'info, TokenAccount>>,
+    /// CHECK: LP token account of the global PDA, where the LP tokens wait for `finalize_lock_lp`
+    #[account(mut, address = get_associated_token_address(&global_account.key(), &lp_mint.key()))]
+    pub user_token_lp: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [LiquidityPool::POOL_SEED_PREFIX.as_bytes(), coin_mint.key().as_ref()],
+        bump = liquidity_pool.bump,
+    )]
+    pub liquidity_pool: Option<Box<Account<'info, LiquidityPool>>>, // Only for bootstrapping pools
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub sysvar_rent: Sysvar<'info, Rent>,
+}
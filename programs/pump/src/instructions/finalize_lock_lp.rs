@@ -0,0 +1,104 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Burn, Mint, Token, TokenAccount};
+
+use crate::{
+    errors::CustomError,
+    events::{CrankRewardPaid, LiquidityLocked},
+    state::{pause, Config, FinalizeStep, TokenDetails},
+};
+
+use crate::utils::raydium;
+
+/// Third finalize step: burns the LP tokens the pool minted to the custody
+/// account, so the launch's liquidity can never be pulled. Anyone can send it
+/// and is paid a share of the crank reward.
+pub fn finalize_lock_lp(ctx: Context<FinalizeLockLp>) -> Result<()> {
+    if !ctx.accounts.token.finalize_step_due(FinalizeStep::PoolCreated)? {
+        return Ok(());
+    }
+    let earned = ctx.accounts.token.crank_reward_earned()?;
+
+    let amount = ctx.accounts.user_token_lp.amount;
+    if amount > 0 {
+        token::burn(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Burn {
+                    mint: ctx.accounts.lp_mint.to_account_info(),
+                    from: ctx.accounts.user_token_lp.to_account_info(),
+                    authority: ctx.accounts.global_account.to_account_info(),
+                },
+                &[&[
+                    "global".as_bytes(),
+                    &[ctx.bumps.global_account],
+                ]],
+            ),
+            amount,
+        )?;
+    }
+    ctx.accounts.token.finalize_step = FinalizeStep::LpLocked;
+
+    emit_cpi!(LiquidityLocked {
+        token: ctx.accounts.token.key(),
+        lp_mint: ctx.accounts.lp_mint.key(),
+        amount,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    let reward = ctx.accounts.token.pay_crank_reward(
+        earned,
+        &ctx.accounts.global_account,
+        &ctx.accounts.cranker.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        ctx.bumps.global_account,
+    )?;
+    if reward > 0 {
+        emit_cpi!(CrankRewardPaid {
+            token: ctx.accounts.token.key(),
+            cranker: ctx.accounts.cranker.key(),
+            amount: reward,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+    }
+
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct FinalizeLockLp<'info> {
+    #[account(mut)]
+    pub token: Box<Account<'info, TokenDetails>>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.is_paused(pause::FINALIZE) @ CustomError::ProgramPaused,
+    )]
+    pub config: Box<Account<'info, Config>>,
+    /// Whoever sends the transaction; paid a share of the crank reward.
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+    /// The LP mint of the pool opened on the launch's market.
+    #[account(
+        mut,
+        seeds = [raydium::AMM_PROGRAM_ID.as_ref(), token.market.as_ref(), raydium::LP_MINT_SEED],
+        bump,
+        seeds::program = raydium::AMM_PROGRAM_ID,
+    )]
+    pub lp_mint: Box<Account<'info, Mint>>,
+    #[account(
+        mut,
+        seeds = [b"global"],
+        bump,
+    )]
+    /// CHECK: PDA holding the LP tokens and the crank reward, signing for the burn
+    pub global_account: AccountInfo<'info>,
+    #[account(
+        mut,
+        associated_token::mint = lp_mint,
+        associated_token::authority = global_account,
+    )]
+    pub user_token_lp: Box<Account<'info, TokenAccount>>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,323 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+
+use crate::{
+    errors::CustomError,
+    events::{CrankRewardPaid, FeeCollected, LaunchFailed, TokensClaimed},
+    state::{pause, BidBook, Config, CreatorProfile, FinalizeStep, LaunchMode, LaunchStatus, LotteryDraw, TickerRecord, TokenDetails, UserContribution, LiquidityPool},
+};
+
+/// First finalize step. Once a launch qualifies it pays each open position of
+/// a standard launch the tokens it bought along the curve. Remaining accounts
+/// come in pairs: the position, then the owner's token account for the mint.
+/// Positions can be paid in any order over several calls; the call that
+/// settles the last one collects the fee and sets the liquidity aside for
+/// `finalize_create_pool`. Anyone can send it, and each call pays the
+/// `cranker` the share of the crank reward its batch earned.
+pub fn finalize_settle<'info>(mut ctx: Context<'_, '_, '_, 'info, FinalizeSettle<'info>>) -> Result<()> {
+    if ctx.accounts.token.finalize_step >= FinalizeStep::Settled {
+        return Ok(());
+    }
+
+    let mode = ctx.accounts.token.mode;
+    let earned = ctx.accounts.token.crank_reward_earned()?;
+    if ctx.accounts.token.finalize_step == FinalizeStep::Pending {
+        ctx.accounts.token.require_active()?;
+        match mode {
+            LaunchMode::Standard => {}
+            LaunchMode::SealedBid { .. } => clear_bids(&mut ctx)?,
+            LaunchMode::Lottery { .. } => {
+                let lottery = ctx.accounts.lottery.as_ref().ok_or(CustomError::LotteryNotDrawn)?;
+                require!(lottery.drawn, CustomError::LotteryNotDrawn);
+            }
+            LaunchMode::Lbp { .. } => {
+                let pool = ctx.accounts.liquidity_pool.as_ref().ok_or(CustomError::InvalidPool)?;
+                require!(
+                    pool.end_time > 0 && Clock::get()?.unix_timestamp >= pool.end_time,
+                    CustomError::PoolStillActive
+                );
+            }
+        }
+
+        // A bootstrapping pool migrates at the end of its schedule whatever it raised.
+        let token = &ctx.accounts.token;
+        if !matches!(mode, LaunchMode::Lbp { .. }) && token.total_contributed < token.target {
+            // Cleared bids and drawn tickets are final, so those launches
+            // have failed; they are refunded through `settle_bid` and
+            // `settle_tickets`. A standard launch keeps selling until its
+            // deadline and is failed by the first `refund`.
+            if matches!(mode, LaunchMode::SealedBid { .. } | LaunchMode::Lottery { .. }) {
+                fail_launch(&mut ctx)?;
+            } else {
+                msg!("Total contributions have not reached the target. Users receive virtual tokens.");
+            }
+            return Ok(());
+        }
+        ctx.accounts.token.finalize_step = FinalizeStep::Settling;
+    }
+
+    if mode == LaunchMode::Standard {
+        pay_contributors(&mut ctx)?;
+        if ctx.accounts.token.contributions_settled < ctx.accounts.token.open_positions {
+            return pay_crank_reward(&mut ctx, earned);
+        }
+    }
+
+    let token = &ctx.accounts.token;
+    let tokens_for_users = token.total_supply - token.total_supply / 2;
+    let (fee, reward) = token.finalize_costs()?;
+    system_program::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.program_account.to_account_info(),
+                to: ctx.accounts.kom_wallet.to_account_info(),
+            },
+            &[&[
+                "global".as_bytes(),
+                &[ctx.bumps.program_account],
+            ]],
+        ),
+        fee,
+    )?;
+    emit_cpi!(FeeCollected {
+        token: token.key(),
+        payer: ctx.accounts.program_account.key(),
+        recipient: ctx.accounts.kom_wallet.key(),
+        amount: fee,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    // Penalties kept from early exits go to the pool the remaining
+    // contributors now hold. The crank reward stays in custody and is paid
+    // out step by step.
+    let liquidity_sol = token.total_contributed - fee - reward + token.withdrawal_penalties;
+
+    // A bootstrapping pool hands its closing reserves over as liquidity.
+    let tokens_for_liquidity = match mode {
+        LaunchMode::Lbp { .. } => ctx.accounts.liquidity_pool.as_ref().ok_or(CustomError::InvalidPool)?.reserve_one,
+        _ => token.total_supply / 2,
+    };
+
+    // Sealed bids and lottery tickets are delivered one by one on settlement.
+    // Whatever the sale side leaves behind, rounding dust or unsold supply,
+    // is added to the liquidity so the vault is fully accounted for.
+    let dust = match mode {
+        // Positions were just paid what they bought and curve buyers took
+        // theirs at the time, so `tokens_sold` covers both.
+        LaunchMode::Standard => tokens_for_users - token.tokens_sold,
+        LaunchMode::SealedBid { .. } => {
+            let bid_book = ctx.accounts.bid_book.as_ref().ok_or(CustomError::BidsNotCleared)?;
+            tokens_for_users.saturating_sub(bid_book.tokens_sold)
+        }
+        LaunchMode::Lottery { .. } => {
+            let lottery = ctx.accounts.lottery.as_ref().ok_or(CustomError::LotteryNotDrawn)?;
+            tokens_for_users - lottery.tokens_per_ticket(tokens_for_users) * lottery.winning_tickets as u64
+        }
+        LaunchMode::Lbp { .. } => 0,
+    };
+    let liquidity_tokens = tokens_for_liquidity.checked_add(dust)
+        .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+
+    let token = &mut ctx.accounts.token;
+    token.liquidity_sol = liquidity_sol;
+    token.liquidity_tokens = liquidity_tokens;
+    token.withdrawal_penalties = 0;
+    token.finalize_step = FinalizeStep::Settled;
+
+    pay_crank_reward(&mut ctx, earned)
+}
+
+fn pay_crank_reward(ctx: &mut Context<FinalizeSettle>, earned: u64) -> Result<()> {
+    let reward = ctx.accounts.token.pay_crank_reward(
+        earned,
+        &ctx.accounts.program_account,
+        &ctx.accounts.cranker.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        ctx.bumps.program_account,
+    )?;
+    if reward > 0 {
+        emit_cpi!(CrankRewardPaid {
+            token: ctx.accounts.token.key(),
+            cranker: ctx.accounts.cranker.key(),
+            amount: reward,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+    }
+    Ok(())
+}
+
+fn fail_launch(ctx: &mut Context<FinalizeSettle>) -> Result<()> {
+    let token = &mut ctx.accounts.token;
+    token.status = LaunchStatus::Failed;
+    ctx.accounts.creator_profile.record_failure()?;
+    let ticker_record = ctx.accounts.ticker_record.as_ref().ok_or(CustomError::InvalidTickerRecord)?;
+    TickerRecord::release(ticker_record, &token.key(), &ctx.accounts.creator)?;
+
+    emit_cpi!(LaunchFailed {
+        token: token.key(),
+        creator: token.creator,
+        total_contributed: token.total_contributed,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+fn clear_bids(ctx: &mut Context<FinalizeSettle>) -> Result<()> {
+    let token = &mut ctx.accounts.token;
+    let (_, reveal_end) = token.sealed_bid_phases().ok_or(CustomError::InvalidLaunchMode)?;
+    let LaunchMode::SealedBid { penalty_bps, min_price, price_step, .. } = token.mode else {
+        return err!(CustomError::InvalidLaunchMode);
+    };
+
+    let bid_book = ctx.accounts.bid_book.as_mut().ok_or(CustomError::BidsNotCleared)?;
+    if bid_book.cleared {
+        return Ok(());
+    }
+    let now = Clock::get()?.unix_timestamp;
+    require!(now >= bid_book.reveal_end(reveal_end, &ctx.accounts.config, now), CustomError::RevealPhaseNotOver);
+
+    // Bids compete for the same half of the supply that contributors get.
+    let sale_supply = token.total_supply - token.total_supply / 2;
+    bid_book.clear(min_price, price_step, sale_supply, token.decimals)?;
+    let forfeited = bid_book.forfeited_deposits(penalty_bps)?;
+
+    token.total_contributed = token.total_contributed
+        .checked_add(bid_book.filled_amount)
+        .and_then(|total| total.checked_add(forfeited))
+        .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+
+    Ok(())
+}
+
+/// Pays each position passed its `total_tokens` and marks it settled. Each
+/// position must be an open one of this launch, and its tokens go to the
+/// token account passed after it, which must be the owner's and hold the
+/// launch's mint.
+fn pay_contributors<'info>(ctx: &mut Context<'_, '_, '_, 'info, FinalizeSettle<'info>>) -> Result<()> {
+    require!(ctx.remaining_accounts.len() % 2 == 0, CustomError::InvalidRecipient);
+    let batch_len = ctx.remaining_accounts.len() / 2;
+    // A call that still has contributors to pay has to make progress.
+    require!(
+        batch_len > 0 || ctx.accounts.token.contributions_settled >= ctx.accounts.token.open_positions,
+        CustomError::InvalidRecipient
+    );
+
+    for pair in ctx.remaining_accounts.chunks(2) {
+        let (position, recipient) = (&pair[0], &pair[1]);
+        let mut user_contribution = Account::<UserContribution>::try_from(position)?;
+        require!(
+            user_contribution.token == ctx.accounts.token.key()
+                && user_contribution.address(&ctx.accounts.token)? == position.key()
+                && user_contribution.amount > 0
+                && !user_contribution.settled,
+            CustomError::InvalidContribution
+        );
+
+        let recipient_account = Account::<TokenAccount>::try_from(recipient)?;
+        require!(
+            recipient_account.owner == user_contribution.user && recipient_account.mint == ctx.accounts.token.mint,
+            CustomError::InvalidRecipient
+        );
+
+        let tokens = user_contribution.total_tokens;
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.pool_token_account.to_account_info(),
+                    to: recipient.clone(),
+                    authority: ctx.accounts.program_account.to_account_info(),
+                },
+                &[&[
+                    "global".as_bytes(),
+                    &[ctx.bumps.program_account],
+                ]],
+            ),
+            tokens,
+        )?;
+
+        emit_cpi!(TokensClaimed {
+            token: ctx.accounts.token.key(),
+            user: user_contribution.user,
+            amount: tokens,
+            refund: 0,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        // Written back straight away, so a position passed twice in one
+        // call is turned away the second time.
+        user_contribution.settled = true;
+        user_contribution.exit(&crate::ID)?;
+    }
+
+    ctx.accounts.token.contributions_settled += batch_len as u32;
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct FinalizeSettle<'info> {
+    #[account(mut, has_one = mint)]
+    pub token: Box<Account<'info, TokenDetails>>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.is_paused(pause::FINALIZE) @ CustomError::ProgramPaused,
+    )]
+    pub config: Box<Account<'info, Config>>,
+    /// The creator's, charged with the failure of a sealed-bid or lottery
+    /// launch that settles below its target.
+    #[account(
+        mut,
+        seeds = [b"creator-profile", token.creator.as_ref()],
+        bump = creator_profile.bump,
+    )]
+    pub creator_profile: Box<Account<'info, CreatorProfile>>,
+    #[account(mut, address = token.creator)]
+    /// CHECK: Gets the ticker record's rent back if the launch fails
+    pub creator: AccountInfo<'info>,
+    #[account(mut)]
+    /// CHECK: The launch's ticker record, released if a sealed-bid or lottery launch fails
+    pub ticker_record: Option<UncheckedAccount<'info>>, // Only for sealed-bid and lottery launches
+    #[account(
+        mut,
+        seeds = [b"global"],
+        bump,
+    )]
+    /// CHECK: PDA holding launch SOL and supply
+    pub program_account: AccountInfo<'info>,
+    #[account(mut, address = config.params.fee_recipient @ CustomError::InvalidFeeRecipient)]
+    /// CHECK: Fee recipient named in the protocol config
+    pub kom_wallet: AccountInfo<'info>,
+    /// Whoever sends the transaction; paid the batch's share of the crank reward.
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+    pub mint: Box<Account<'info, Mint>>,
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = program_account,
+    )]
+    pub pool_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        seeds = [LiquidityPool::POOL_SEED_PREFIX.as_bytes(), mint.key().as_ref()],
+        bump = liquidity_pool.bump,
+    )]
+    pub liquidity_pool: Option<Box<Account<'info, LiquidityPool>>>, // Only for bootstrapping pools
+    #[account(
+        mut,
+        seeds = [b"bid-book", token.key().as_ref()],
+        bump = bid_book.bump,
+    )]
+    pub bid_book: Option<Box<Account<'info, BidBook>>>, // Only for sealed-bid launches
+    #[account(
+        seeds = [b"lottery", token.key().as_ref()],
+        bump = lottery.bump,
+    )]
+    pub lottery: Option<Box<Account<'info, LotteryDraw>>>, // Only for lottery launches
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
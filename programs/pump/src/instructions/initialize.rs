@@ -4,21 +4,143 @@ use anchor_spl::{
     token::{self, Token, Mint, MintTo, TokenAccount},
     associated_token::AssociatedToken,
 };
-use crate::state::{TokenDetails, UserContribution};
+use crate::events::{BondPosted, FeeCollected, LaunchCreated};
+use crate::state::{pause, Config, CreatorProfile, LaunchMode, LaunchStatus, ProtocolParams, TickerRecord, TokenDetails, UserContribution};
+use crate::utils::math::{mul_div, unit, Rounding};
+use crate::utils::Curve;
 use crate::{errors::CustomError};
 
-pub fn initialize(mut ctx: Context<CreateToken>, name: String, ticker: String, total_supply: u64, initial_target: u64, decimals: u8) -> Result<()> {
-    require!(initial_target > 0 && initial_target <= 10 * 1_000_000_000, CustomError::InvalidInputValue);
+/// `bond` is optional, 0 for none. Only standard launches take one, since
+/// only they can be held to a finalize window once the target is reached.
+#[allow(clippy::too_many_arguments)]
+pub fn initialize(mut ctx: Context<CreateToken>, name: String, ticker: String, total_supply: u64, initial_target: u64, decimals: u8, mode: LaunchMode, curve: Curve, bond: u64) -> Result<()> {
+    let params = ctx.accounts.config.params;
+    require!(params.target > 0, CustomError::InvalidTarget);
+    validate_name(&name)?;
+    let normalized_ticker = TickerRecord::normalize(&ticker)?;
+    validate_supply(total_supply, decimals)?;
+    validate_mode(&mode, &params)?;
+    validate_opening(initial_target, &mode, &params)?;
+    require!(bond == 0 || mode == LaunchMode::Standard, CustomError::InvalidLaunchMode);
+    curve.validate(total_supply - total_supply / 2, decimals)?;
 
-    process_transfers(&ctx, initial_target)?;
-    initialize_token(&mut ctx, name, ticker, total_supply, initial_target, decimals)?;
-    initialize_user_contribution(&mut ctx, initial_target, total_supply)?;
+    process_transfers(&ctx, initial_target, bond)?;
+    claim_ticker(&ctx, normalized_ticker)?;
+    record_launch(&mut ctx, total_supply, decimals, &curve)?;
+    initialize_token(&mut ctx, name, ticker, total_supply, initial_target, decimals, mode, curve)?;
+    initialize_user_contribution(&mut ctx, initial_target)?;
+    ctx.accounts.token.bond = bond;
 
+    let token = &ctx.accounts.token;
+    let timestamp = Clock::get()?.unix_timestamp;
+    emit_cpi!(FeeCollected {
+        token: token.key(),
+        payer: ctx.accounts.user.key(),
+        recipient: ctx.accounts.program_account.key(),
+        amount: params.launch_fee,
+        timestamp,
+    });
+    emit_cpi!(LaunchCreated {
+        token: token.key(),
+        mint: token.mint,
+        creator: token.creator,
+        name: token.name.clone(),
+        ticker: token.ticker.clone(),
+        total_supply: token.total_supply,
+        decimals: token.decimals,
+        target: token.target,
+        initial_contribution: initial_target,
+        initial_tokens: ctx.accounts.user_contribution.total_tokens,
+        mode: token.mode,
+        curve: token.curve,
+        timestamp,
+    });
+    if bond > 0 {
+        emit_cpi!(BondPosted {
+            token: token.key(),
+            creator: token.creator,
+            amount: bond,
+            timestamp,
+        });
+    }
+
+    Ok(())
+}
+
+/// Names are free-form text, but must fit the account and show up as
+/// something in a wallet: no control characters and no padding.
+fn validate_name(name: &str) -> Result<()> {
+    require!(
+        !name.is_empty() &&
+            name.len() <= TokenDetails::MAX_NAME_LENGTH &&
+            name.trim() == name &&
+            !name.chars().any(char::is_control),
+        CustomError::InvalidName
+    );
+    Ok(())
+}
+
+/// Bounds the supply in whole tokens, so it is at least one token whatever
+/// the decimals.
+fn validate_supply(total_supply: u64, decimals: u8) -> Result<()> {
+    require!(decimals <= TokenDetails::MAX_DECIMALS, CustomError::InvalidDecimals);
+    let unit = 10_u64.pow(decimals as u32);
+    require!(
+        total_supply >= unit && total_supply / unit <= TokenDetails::MAX_SUPPLY_TOKENS,
+        CustomError::InvalidSupply
+    );
+    Ok(())
+}
+
+fn validate_mode(mode: &LaunchMode, params: &ProtocolParams) -> Result<()> {
+    match *mode {
+        LaunchMode::Standard => {}
+        LaunchMode::SealedBid { commit_duration, reveal_duration, penalty_bps, min_price, price_step } => {
+            require!(
+                commit_duration > 0 && reveal_duration > 0 && penalty_bps <= 10_000 && min_price > 0 && price_step > 0,
+                CustomError::InvalidBidParameters
+            );
+        }
+        LaunchMode::Lottery { ticket_price, winning_tickets, sale_duration, .. } => {
+            require!(
+                ticket_price > 0 && winning_tickets > 0 && sale_duration > 0 &&
+                    params.randomness_program != Pubkey::default(),
+                CustomError::InvalidLotteryParameters
+            );
+        }
+        LaunchMode::Lbp { start_weight_bps, end_weight_bps, duration } => {
+            require!(
+                start_weight_bps > 0 && start_weight_bps < 10_000 &&
+                end_weight_bps > 0 && end_weight_bps < 10_000 &&
+                duration > 0,
+                CustomError::InvalidLbpParameters
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// The creator's opening position is bought off the curve and paid out by
+/// `finalize_settle`, which only happens for standard launches and seeds a
+/// bootstrapping pool. Sealed-bid and lottery sales are settled bid by bid
+/// and ticket by ticket, so there the creator takes part like anyone else
+/// rather than opening with a position.
+fn validate_opening(initial_target: u64, mode: &LaunchMode, params: &ProtocolParams) -> Result<()> {
+    match mode {
+        LaunchMode::SealedBid { .. } | LaunchMode::Lottery { .. } => {
+            require!(initial_target == 0, CustomError::InvalidInputValue)
+        }
+        _ => require!(
+            initial_target > 0 && initial_target <= params.max_contribution,
+            CustomError::InvalidInputValue
+        ),
+    }
     Ok(())
 }
 
-fn process_transfers(ctx: &Context<CreateToken>, initial_target: u64) -> Result<()> {
-    let fee = 100_000_000;
+fn process_transfers(ctx: &Context<CreateToken>, initial_target: u64, bond: u64) -> Result<()> {
+    let fee = ctx.accounts.config.params.launch_fee;
     let user = &ctx.accounts.user;
     
     // Transfer fee
@@ -41,9 +163,91 @@ fn process_transfers(ctx: &Context<CreateToken>, initial_target: u64) -> Result<
     );
     system_program::transfer(initial_target_transfer_ctx, initial_target)?;
 
+    // Transfer the bond, kept in custody alongside the contributions
+    if bond > 0 {
+        let bond_transfer_ctx = CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: user.to_account_info(),
+                to: ctx.accounts.program_account.to_account_info(),
+            },
+        );
+        system_program::transfer(bond_transfer_ctx, bond)?;
+    }
+
+    Ok(())
+}
+
+/// Registers the launch under its normalized ticker. The record cannot be an
+/// `init` account because its seeds are the normalized ticker, so this checks
+/// the address and creates it the way `init` would.
+fn claim_ticker(ctx: &Context<CreateToken>, normalized: String) -> Result<()> {
+    let (address, bump) = Pubkey::find_program_address(&[b"ticker", normalized.as_bytes()], ctx.program_id);
+    let record = &ctx.accounts.ticker_record;
+    require_keys_eq!(record.key(), address, CustomError::InvalidTicker);
+    if record.owner == ctx.program_id {
+        let existing = TickerRecord::try_deserialize(&mut &record.try_borrow_data()?[..])?;
+        require!(!existing.reserved, CustomError::TickerReserved);
+        return err!(CustomError::DuplicateTokenNotAllowed);
+    }
+
+    // Top up rather than create, in case someone has already sent lamports
+    // to the address.
+    let system_program = ctx.accounts.system_program.to_account_info();
+    let rent = Rent::get()?.minimum_balance(TickerRecord::ACCOUNT_SIZE);
+    let shortfall = rent.saturating_sub(record.lamports());
+    if shortfall > 0 {
+        system_program::transfer(
+            CpiContext::new(system_program.clone(), system_program::Transfer {
+                from: ctx.accounts.user.to_account_info(),
+                to: record.to_account_info(),
+            }),
+            shortfall,
+        )?;
+    }
+    let signer_seeds: &[&[&[u8]]] = &[&[b"ticker", normalized.as_bytes(), &[bump]]];
+    system_program::allocate(
+        CpiContext::new_with_signer(system_program.clone(), system_program::Allocate {
+            account_to_allocate: record.to_account_info(),
+        }, signer_seeds),
+        TickerRecord::ACCOUNT_SIZE as u64,
+    )?;
+    system_program::assign(
+        CpiContext::new_with_signer(system_program, system_program::Assign {
+            account_to_assign: record.to_account_info(),
+        }, signer_seeds),
+        ctx.program_id,
+    )?;
+
+    let entry = TickerRecord {
+        ticker: normalized,
+        token: ctx.accounts.token.key(),
+        reserved: false,
+        bump,
+    };
+    entry.try_serialize(&mut &mut record.try_borrow_mut_data()?[..])?;
+
+    Ok(())
+}
+
+/// Counts the launch on the creator's profile, creating it on their first.
+/// While they have no graduated launch the opening valuation is capped.
+fn record_launch(ctx: &mut Context<CreateToken>, total_supply: u64, decimals: u8, curve: &Curve) -> Result<()> {
+    let max_valuation = ctx.accounts.config.params.max_unproven_valuation;
+    let profile = &mut ctx.accounts.creator_profile;
+    if max_valuation > 0 && profile.graduations == 0 {
+        let valuation = mul_div(curve.price(0, decimals)? as u128, total_supply as u128, unit(decimals)?, Rounding::Up)?;
+        require!(valuation <= max_valuation as u128, CustomError::UnprovenCreator);
+    }
+
+    profile.creator = ctx.accounts.user.key();
+    profile.bump = ctx.bumps.creator_profile;
+    profile.launches = profile.launches.checked_add(1).ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 fn initialize_token(
     ctx: &mut Context<CreateToken>,
     name: String,
@@ -51,6 +255,8 @@ fn initialize_token(
     total_supply: u64,
     initial_target: u64,
     decimals: u8,
+    mode: LaunchMode,
+    curve: Curve,
 ) -> Result<()> {
 
     let cpi_accounts = MintTo {
@@ -59,7 +265,13 @@ fn initialize_token(
         authority: ctx.accounts.mint_authority.to_account_info(),
     };
     let cpi_program = ctx.accounts.token_program.to_account_info();
-    let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+    let mint_key = ctx.accounts.mint.key();
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        b"mint-authority",
+        mint_key.as_ref(),
+        &[ctx.bumps.mint_authority],
+    ]];
+    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
 
     token::mint_to(cpi_ctx, total_supply)?;
 
@@ -67,13 +279,24 @@ fn initialize_token(
     token.name = name;
     token.ticker = ticker;
     token.total_contributed = initial_target;
-    token.target = 200 * 1_000_000_000;
+    token.refundable = initial_target;
+    let params = ctx.accounts.config.params;
+    token.target = params.target;
     token.creation_time = Clock::get()?.unix_timestamp;
     token.total_supply = total_supply;
     token.is_virtual = true;
     token.decimals = decimals;
     token.contribution_count = 1;
+    token.open_positions = (initial_target > 0) as u32;
     token.bump = ctx.bumps.token;
+    token.creator = ctx.accounts.user.key();
+    token.mint = ctx.accounts.mint.key();
+    token.mode = mode;
+    token.curve = curve;
+    token.status = LaunchStatus::Active;
+    token.refund_deadline = token.creation_time + params.refund_delay;
+    // Later config changes leave the launch on the terms it opened under.
+    token.terms = params.terms();
 
     Ok(())
 }
@@ -81,16 +304,24 @@ fn initialize_token(
 fn initialize_user_contribution(
     ctx: &mut Context<CreateToken>,
     initial_target: u64,
-    total_supply: u64,
 ) -> Result<()> {
     let user_contribution = &mut ctx.accounts.user_contribution;
     let user = &ctx.accounts.user;
-    let token = &ctx.accounts.token;
+    let token = &mut ctx.accounts.token;
+
+    // The creator buys in first, at the bottom of the curve.
+    let tokens = if initial_target > 0 {
+        token.curve.amount_out(0, initial_target, token.decimals)?
+    } else {
+        0
+    };
+    require!(tokens <= token.total_supply - token.total_supply / 2, CustomError::TargetExceeded);
+    token.tokens_sold = tokens;
 
     user_contribution.user = user.key();
     user_contribution.token = token.key();
     user_contribution.amount = initial_target;
-    user_contribution.total_tokens = calculate_tokens(initial_target, total_supply, token.target);
+    user_contribution.total_tokens = tokens;
     user_contribution.contribution_number = 0;
     user_contribution.timestamp = Clock::get()?.unix_timestamp;
     user_contribution.bump = ctx.bumps.user_contribution;
@@ -98,12 +329,9 @@ fn initialize_user_contribution(
     Ok(())
 }
 
-fn calculate_tokens(contribution: u64, total_supply: u64, target: u64) -> u64 {
-    ((contribution as u128) * (total_supply as u128) / (target as u128)) as u64
-}
-
+#[event_cpi]
 #[derive(Accounts)]
-#[instruction(name: String, ticker: String, total_supply: u64, initial_target: u64, decimals: u8)]
+#[instruction(name: String, ticker: String, total_supply: u64, initial_target: u64, decimals: u8, mode: LaunchMode, curve: Curve)]
 pub struct CreateToken<'info> {
     #[account(
         init,
@@ -113,6 +341,13 @@ pub struct CreateToken<'info> {
         bump
     )]
     pub token: Box<Account<'info, TokenDetails>>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.is_paused(pause::INITIALIZE) @ CustomError::ProgramPaused,
+    )]
+    pub config: Box<Account<'info, Config>>,
     
     #[account(
         init,
@@ -124,11 +359,12 @@ pub struct CreateToken<'info> {
     )]
     pub mint: Account<'info, Mint>,
 
+    /// Launch supply is held by the program until it is distributed.
     #[account(
         init,
         payer = user,
         associated_token::mint = mint,
-        associated_token::authority = user,
+        associated_token::authority = program_account,
     )]
     pub token_account: Account<'info, TokenAccount>,
 
@@ -142,8 +378,12 @@ pub struct CreateToken<'info> {
     #[account(mut)]
     pub user: Signer<'info>,
 
-    #[account(mut)]
-    /// CHECK: This is not dangerous because we don't read or write from this account
+    #[account(
+        mut,
+        seeds = [b"global"],
+        bump,
+    )]
+    /// CHECK: PDA holding launch SOL and supply
     pub program_account: AccountInfo<'info>,
 
     #[account(
@@ -159,7 +399,21 @@ pub struct CreateToken<'info> {
         bump
     )]
     pub user_contribution: Box<Account<'info, UserContribution>>,
-    
+
+    /// CHECK: registry entry for the normalized ticker, checked and created
+    /// by `claim_ticker`.
+    #[account(mut)]
+    pub ticker_record: AccountInfo<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = CreatorProfile::ACCOUNT_SIZE,
+        seeds = [b"creator-profile", user.key().as_ref()],
+        bump
+    )]
+    pub creator_profile: Box<Account<'info, CreatorProfile>>,
+
     pub token_program: Program<'info, Token>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
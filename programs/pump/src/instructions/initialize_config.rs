@@ -0,0 +1,42 @@
+use anchor_lang::prelude::*;
+use crate::errors::CustomError;
+use crate::program::Pump;
+use crate::state::{Config, ProtocolParams};
+
+pub fn initialize_config(ctx: Context<InitializeConfig>, params: ProtocolParams) -> Result<()> {
+    params.validate()?;
+
+    let config = &mut ctx.accounts.config;
+    config.admin = ctx.accounts.admin.key();
+    config.fee_admin = ctx.accounts.admin.key();
+    config.pause_guardian = ctx.accounts.admin.key();
+    config.params = params;
+    config.bump = ctx.bumps.config;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = Config::ACCOUNT_SIZE,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    /// Only the program's upgrade authority can set up the config, so it
+    /// cannot be claimed by whoever calls first after a deploy.
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, Pump>,
+
+    #[account(constraint = program_data.upgrade_authority_address == Some(admin.key()) @ CustomError::Unauthorized)]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub system_program: Program<'info, System>,
+}
@@ -1,9 +1,65 @@
 pub mod contribute;
 pub mod refund;
-pub mod finalize;
+pub mod finalize_settle;
+pub mod finalize_create_pool;
+pub mod finalize_lock_lp;
+pub mod finalize_complete;
+pub mod cancel_launch;
+pub mod slash_bond;
+pub mod extend_deadline;
+pub mod opt_out;
+pub mod withdraw_contribution;
 pub mod initialize;
+pub mod initialize_config;
+pub mod queue_config_change;
+pub mod execute_config_change;
+pub mod cancel_config_change;
+pub mod set_pause;
+pub mod propose_admin;
+pub mod accept_admin;
+pub mod set_roles;
+pub mod set_fee_recipient;
+pub mod reserve_ticker;
+pub mod release_ticker;
+pub mod commit_bid;
+pub mod reveal_bid;
+pub mod settle_bid;
+pub mod close_lottery;
+pub mod draw_lottery;
+pub mod settle_tickets;
+pub mod start_lbp;
+pub mod buy;
+pub mod sell;
 
 pub use initialize::*;
+pub use initialize_config::*;
+pub use queue_config_change::*;
+pub use execute_config_change::*;
+pub use cancel_config_change::*;
+pub use set_pause::*;
+pub use propose_admin::*;
+pub use accept_admin::*;
+pub use set_roles::*;
+pub use set_fee_recipient::*;
+pub use reserve_ticker::*;
+pub use release_ticker::*;
 pub use contribute::*;
 pub use refund::*;
-pub use finalize::*;
\ No newline at end of file
+pub use finalize_settle::*;
+pub use finalize_create_pool::*;
+pub use finalize_lock_lp::*;
+pub use finalize_complete::*;
+pub use cancel_launch::*;
+pub use slash_bond::*;
+pub use extend_deadline::*;
+pub use opt_out::*;
+pub use withdraw_contribution::*;
+pub use commit_bid::*;
+pub use reveal_bid::*;
+pub use settle_bid::*;
+pub use close_lottery::*;
+pub use draw_lottery::*;
+pub use settle_tickets::*;
+pub use start_lbp::*;
+pub use buy::*;
+pub use sell::*;
\ No newline at end of file
@@ -0,0 +1,92 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use crate::errors::CustomError;
+use crate::events::Refunded;
+use crate::state::{TokenDetails, UserContribution};
+
+/// Pays back a position opened before the creator extended the refund
+/// deadline, for contributors who do not accept the new one.
+pub fn opt_out(ctx: Context<OptOut>) -> Result<()> {
+    let token = &ctx.accounts.token;
+    let user = &ctx.accounts.user;
+
+    // A cancelled launch refunds everyone through `refund` instead.
+    token.require_active()?;
+    require!(token.is_virtual, CustomError::LaunchAlreadyFinalized);
+
+    let current_time = Clock::get()?.unix_timestamp;
+    require!(token.extended_at > 0 && current_time <= token.opt_out_deadline, CustomError::OptOutWindowClosed);
+
+    // Later contributors joined knowing the new deadline, and the creator
+    // chose it.
+    let user_contribution = &mut ctx.accounts.user_contribution;
+    require!(user_contribution.timestamp <= token.extended_at, CustomError::NotEligibleForOptOut);
+    require!(user_contribution.user != token.creator, CustomError::NotEligibleForOptOut);
+
+    let amount = user_contribution.amount;
+    require!(amount > 0, CustomError::NoContributionToRefund);
+
+    system_program::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.program_account.to_account_info(),
+                to: user.to_account_info(),
+            },
+            &[&[
+                "global".as_bytes(),
+                &[ctx.bumps.program_account],
+            ]],
+        ),
+        amount,
+    )?;
+
+    // Only standard launches can be extended, so the position goes back to
+    // the curve like a refund.
+    let token = &mut ctx.accounts.token;
+    token.tokens_sold = token.tokens_sold.saturating_sub(user_contribution.total_tokens);
+    token.total_contributed = token.total_contributed.saturating_sub(amount);
+    token.refundable = token.refundable.saturating_sub(amount);
+    token.opted_out = token.opted_out.saturating_add(amount);
+    token.open_positions -= 1;
+
+    // The position stays open as a record of the opt-out.
+    user_contribution.amount = 0;
+    user_contribution.total_tokens = 0;
+    user_contribution.opted_out = true;
+
+    emit_cpi!(Refunded {
+        token: token.key(),
+        user: user.key(),
+        user_contribution: user_contribution.key(),
+        amount,
+        total_contributed: token.total_contributed,
+        timestamp: current_time,
+    });
+
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct OptOut<'info> {
+    #[account(mut)]
+    pub token: Box<Account<'info, TokenDetails>>,
+    #[account(
+        mut,
+        has_one = user,
+        has_one = token,
+        constraint = user_contribution.key() == user_contribution.address(&token)? @ CustomError::InvalidContribution,
+    )]
+    pub user_contribution: Box<Account<'info, UserContribution>>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"global"],
+        bump,
+    )]
+    /// CHECK: PDA holding launch SOL and supply
+    pub program_account: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+use crate::errors::CustomError;
+use crate::events::AdminProposed;
+use crate::state::Config;
+
+/// Nominates the next admin, who takes over by signing `accept_admin`.
+/// Proposing `Pubkey::default()` withdraws a pending proposal.
+pub fn propose_admin(ctx: Context<ProposeAdmin>, pending_admin: Pubkey) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    config.pending_admin = pending_admin;
+
+    emit_cpi!(AdminProposed {
+        admin: config.admin,
+        pending_admin,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ProposeAdmin<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ CustomError::Unauthorized,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    pub admin: Signer<'info>,
+}
@@ -0,0 +1,35 @@
+use anchor_lang::prelude::*;
+use crate::errors::CustomError;
+use crate::events::ConfigChangeQueued;
+use crate::state::{Config, PendingConfigChange, ProtocolParams};
+
+/// Queues `params` to replace the protocol parameters once the current
+/// `config_delay` has passed. A new queue replaces whatever was pending and
+/// restarts the clock.
+pub fn queue_config_change(ctx: Context<QueueConfigChange>, params: ProtocolParams) -> Result<()> {
+    params.validate()?;
+
+    let config = &mut ctx.accounts.config;
+    let timestamp = Clock::get()?.unix_timestamp;
+    let eta = timestamp.checked_add(config.params.config_delay)
+        .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+    config.pending_change = Some(PendingConfigChange { params, eta });
+
+    emit_cpi!(ConfigChangeQueued { params, eta, timestamp });
+
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct QueueConfigChange<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ CustomError::Unauthorized,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    pub admin: Signer<'info>,
+}
@@ -1,46 +1,165 @@
 use anchor_lang::prelude::*;
 use anchor_lang::system_program;
-use crate::state::{TokenDetails, UserContribution};
+use crate::state::{CreatorProfile, LaunchMode, LaunchStatus, TickerRecord, TokenDetails, UserContribution};
 use crate::errors::CustomError; 
+use crate::events::{BondSettled, LaunchFailed, Refunded};
+use crate::utils::math::{mul_div_u64, Rounding};
 
 pub fn refund(ctx: Context<Refund>) -> Result<()> {
     let token = &ctx.accounts.token;
     let user = &ctx.accounts.user;
 
+    // The creator's seed in a bootstrapping pool belongs to the pool.
+    require!(!matches!(token.mode, LaunchMode::Lbp { .. }), CustomError::InvalidLaunchMode);
+    // Tickets stay in the draw whatever the deadline, since the draw prices
+    // the raise off the tickets sold. Once it has settled the launch they
+    // are refunded through `settle_tickets`; only a cancelled lottery can be
+    // refunded before that.
+    require!(
+        !matches!(token.mode, LaunchMode::Lottery { .. }) || token.status != LaunchStatus::Active,
+        CustomError::InvalidLaunchMode
+    );
+
+    // The position is closed on the way out, so a refund that would pay
+    // nothing has to fail rather than discard it. A cancelled or failed
+    // launch refunds straight away.
     let current_time = Clock::get()?.unix_timestamp;
-    if current_time > token.creation_time + 7 * 24 * 60 * 60 && token.total_contributed < token.target {
-        let user_contribution = &mut ctx.accounts.user_contribution; 
-        let refund_amount = user_contribution.amount;
-
-        require!(refund_amount > 0, CustomError::NoContributionToRefund);
-
-        system_program::transfer(
-            CpiContext::new(
-                ctx.accounts.system_program.to_account_info(),
-                system_program::Transfer {
-                    from: ctx.accounts.program_account.to_account_info(),
-                    to: user.to_account_info(),
-                },
-            ),
-            refund_amount,
-        )?;
-
-        user_contribution.amount = 0;
+    let active = token.status == LaunchStatus::Active;
+    if active {
+        require!(current_time > token.refund_deadline, CustomError::TooEarlyForRefund);
+        require!(token.total_contributed < token.target, CustomError::TargetReached);
     }
 
+    let user_contribution = &mut ctx.accounts.user_contribution;
+    let refund_amount = user_contribution.amount;
+
+    require!(refund_amount > 0, CustomError::NoContributionToRefund);
+
+    // Penalties left behind by early exits are shared pro rata among the
+    // positions still open; the last one out takes whatever remains.
+    let penalty_share = if refund_amount >= token.refundable {
+        token.withdrawal_penalties
+    } else {
+        mul_div_u64(token.withdrawal_penalties, refund_amount, token.refundable, Rounding::Down)?
+    };
+
+    system_program::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.program_account.to_account_info(),
+                to: user.to_account_info(),
+            },
+            &[&[
+                "global".as_bytes(),
+                &[ctx.bumps.program_account],
+            ]],
+        ),
+        refund_amount + penalty_share,
+    )?;
+
+    // The first refund past the deadline settles that the launch failed.
+    // Sealed-bid and lottery totals are only known once `finalize_settle` runs, so
+    // those launches are left to it.
+    let token = &mut ctx.accounts.token;
+    if active && token.mode == LaunchMode::Standard {
+        token.status = LaunchStatus::Failed;
+        ctx.accounts.creator_profile.record_failure()?;
+        TickerRecord::release(&ctx.accounts.ticker_record, &token.key(), &ctx.accounts.creator)?;
+        emit_cpi!(LaunchFailed {
+            token: token.key(),
+            creator: token.creator,
+            total_contributed: token.total_contributed,
+            timestamp: current_time,
+        });
+
+        // Falling short of the target is not abandoning the launch, so the
+        // bond goes back in full.
+        if token.bond > 0 {
+            let (returned, slashed) = token.settle_bond(false)?;
+            system_program::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.program_account.to_account_info(),
+                        to: ctx.accounts.creator.to_account_info(),
+                    },
+                    &[&[
+                        "global".as_bytes(),
+                        &[ctx.bumps.program_account],
+                    ]],
+                ),
+                returned,
+            )?;
+            emit_cpi!(BondSettled {
+                token: token.key(),
+                creator: token.creator,
+                returned,
+                slashed,
+                timestamp: current_time,
+            });
+        }
+    }
+
+    // Hand the position back to the curve so later prices stay backed.
+    if token.mode == LaunchMode::Standard {
+        token.tokens_sold = token.tokens_sold.saturating_sub(user_contribution.total_tokens);
+    }
+    token.total_contributed = token.total_contributed.saturating_sub(refund_amount);
+    token.refundable = token.refundable.saturating_sub(refund_amount);
+    token.withdrawal_penalties -= penalty_share;
+    token.open_positions -= 1;
+
+    user_contribution.amount = 0;
+    user_contribution.total_tokens = 0;
+
+    emit_cpi!(Refunded {
+        token: token.key(),
+        user: user.key(),
+        user_contribution: user_contribution.key(),
+        amount: refund_amount + penalty_share,
+        total_contributed: token.total_contributed,
+        timestamp: current_time,
+    });
+
     Ok(())
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct Refund<'info> {
     #[account(mut)]
     pub token: Box<Account<'info, TokenDetails>>,
+    /// The creator's, charged with the failure if this refund settles it.
+    #[account(
+        mut,
+        seeds = [b"creator-profile", token.creator.as_ref()],
+        bump = creator_profile.bump,
+    )]
+    pub creator_profile: Box<Account<'info, CreatorProfile>>,
+    #[account(mut, address = token.creator)]
+    /// CHECK: Receives the bond back if this refund settles the launch as failed
+    pub creator: AccountInfo<'info>,
     #[account(mut)]
+    /// CHECK: The launch's ticker record, released by the refund that settles the launch as failed
+    pub ticker_record: AccountInfo<'info>,
+    /// Closed back to its owner once refunded.
+    #[account(
+        mut,
+        has_one = user,
+        has_one = token,
+        close = user,
+        constraint = user_contribution.key() == user_contribution.address(&token)? @ CustomError::InvalidContribution,
+    )]
     pub user_contribution: Box<Account<'info, UserContribution>>,
     #[account(mut)]
     pub user: Signer<'info>,
-    #[account(mut)]
-    /// CHECK: This is not dangerous because we don't read or write from this account
+    #[account(
+        mut,
+        seeds = [b"global"],
+        bump,
+    )]
+    /// CHECK: PDA holding launch SOL and supply
     pub program_account: AccountInfo<'info>,
     pub system_program: Program<'info, System>,
 }
\ No newline at end of file
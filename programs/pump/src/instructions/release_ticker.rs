@@ -0,0 +1,40 @@
+use anchor_lang::prelude::*;
+use crate::errors::CustomError;
+use crate::events::TickerReservationUpdated;
+use crate::state::{Config, TickerRecord};
+
+/// Lifts a reservation, leaving the ticker to whoever launches it first.
+pub fn release_ticker(ctx: Context<ReleaseTicker>, ticker: String) -> Result<()> {
+    emit_cpi!(TickerReservationUpdated {
+        ticker,
+        reserved: false,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(ticker: String)]
+pub struct ReleaseTicker<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ CustomError::Unauthorized,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    /// Tickers claimed by a launch stay with it.
+    #[account(
+        mut,
+        seeds = [b"ticker", ticker.as_bytes()],
+        bump = ticker_record.bump,
+        constraint = ticker_record.reserved @ CustomError::TickerNotReserved,
+        close = admin,
+    )]
+    pub ticker_record: Box<Account<'info, TickerRecord>>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+}
@@ -0,0 +1,51 @@
+use anchor_lang::prelude::*;
+use crate::errors::CustomError;
+use crate::events::TickerReservationUpdated;
+use crate::state::{Config, TickerRecord};
+
+/// Reserves `ticker` so no launch can claim it. The ticker seeds the record,
+/// so it has to be passed in its normalized form.
+pub fn reserve_ticker(ctx: Context<ReserveTicker>, ticker: String) -> Result<()> {
+    require!(TickerRecord::normalize(&ticker)? == ticker, CustomError::InvalidTicker);
+
+    let record = &mut ctx.accounts.ticker_record;
+    record.ticker = ticker.clone();
+    record.token = Pubkey::default();
+    record.reserved = true;
+    record.bump = ctx.bumps.ticker_record;
+
+    emit_cpi!(TickerReservationUpdated {
+        ticker,
+        reserved: true,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(ticker: String)]
+pub struct ReserveTicker<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ CustomError::Unauthorized,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    /// Fails to initialize if a launch has already claimed the ticker.
+    #[account(
+        init,
+        payer = admin,
+        space = TickerRecord::ACCOUNT_SIZE,
+        seeds = [b"ticker", ticker.as_bytes()],
+        bump
+    )]
+    pub ticker_record: Box<Account<'info, TickerRecord>>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
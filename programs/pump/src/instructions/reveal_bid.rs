@@ -0,0 +1,94 @@
+use anchor_lang::prelude::*;
+use crate::errors::CustomError;
+use crate::events::BidRevealed;
+use crate::state::{pause, BidBook, BidCommitment, Config, LaunchMode, TokenDetails};
+
+pub fn reveal_bid(ctx: Context<RevealBid>, amount: u64, price_limit: u64, salt: [u8; 32]) -> Result<()> {
+    let token = &ctx.accounts.token;
+    let (commit_end, reveal_end) = token.sealed_bid_phases().ok_or(CustomError::InvalidLaunchMode)?;
+    let LaunchMode::SealedBid { min_price, price_step, .. } = token.mode else {
+        return err!(CustomError::InvalidLaunchMode);
+    };
+
+    // The window runs on for as long as reveals were paused, up to clearing.
+    let current_time = Clock::get()?.unix_timestamp;
+    let bid_book = &ctx.accounts.bid_book;
+    let reveal_end = bid_book.reveal_end(reveal_end, &ctx.accounts.config, current_time);
+    require!(
+        current_time >= commit_end && current_time < reveal_end && !bid_book.cleared,
+        CustomError::RevealPhaseNotActive
+    );
+
+    let bid = &mut ctx.accounts.bid;
+    require!(!bid.revealed, CustomError::BidAlreadyRevealed);
+    require!(
+        BidCommitment::hash(&bid.user, amount, price_limit, &salt) == bid.commitment,
+        CustomError::CommitmentMismatch
+    );
+    require!(amount > 0 && amount <= bid.deposit, CustomError::BidExceedsDeposit);
+
+    // Bids under the minimum price stay unfilled and are refunded at settlement.
+    let tier = if price_limit >= min_price {
+        let tier = (price_limit - min_price) / price_step;
+        Some(tier.min(BidBook::PRICE_TIERS as u64 - 1) as u8)
+    } else {
+        None
+    };
+
+    let bid_book = &mut ctx.accounts.bid_book;
+    if let Some(tier) = tier {
+        let demand = &mut bid_book.demand[tier as usize];
+        *demand = demand.checked_add(amount)
+            .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+    }
+    bid_book.revealed_deposits = bid_book.revealed_deposits.checked_add(bid.deposit)
+        .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+
+    bid.amount = amount;
+    bid.price_limit = price_limit;
+    bid.tier = tier;
+    bid.revealed = true;
+
+    emit_cpi!(BidRevealed {
+        token: ctx.accounts.token.key(),
+        user: bid.user,
+        bid: bid.key(),
+        amount,
+        price_limit,
+        tier,
+        revealed_deposits: bid_book.revealed_deposits,
+        timestamp: current_time,
+    });
+
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RevealBid<'info> {
+    pub token: Box<Account<'info, TokenDetails>>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.is_paused(pause::REVEAL_BID) @ CustomError::ProgramPaused,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        mut,
+        seeds = [b"bid-book", token.key().as_ref()],
+        bump = bid_book.bump
+    )]
+    pub bid_book: Box<Account<'info, BidBook>>,
+
+    #[account(
+        mut,
+        has_one = user,
+        seeds = [b"bid", token.key().as_ref(), user.key().as_ref()],
+        bump = bid.bump
+    )]
+    pub bid: Box<Account<'info, BidCommitment>>,
+
+    pub user: Signer<'info>,
+}
@@ -0,0 +1,140 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::token::{self, Token, Mint, TokenAccount, Transfer};
+use crate::errors::CustomError;
+use crate::events::Trade;
+use crate::state::{pause, Config, LaunchMode, LiquidityPool, TokenDetails};
+use crate::utils::calculate_weighted_out;
+
+pub fn sell(ctx: Context<Sell>, amount_in: u64, minimum_amount_out: u64) -> Result<()> {
+    require!(amount_in > 0, CustomError::InvalidAmount);
+    let token = &mut ctx.accounts.token;
+    require!(token.is_virtual, CustomError::PoolNotActive);
+    require!(!token.finalize_in_progress(), CustomError::FinalizeInProgress);
+
+    let (amount_out, tokens_sold) = match token.mode {
+        // Selling walks back down the same curve, so the curve always covers what it owes.
+        LaunchMode::Standard => {
+            let amount_out = token.curve.sell_amount_out(token.tokens_sold, amount_in, token.decimals)?;
+            token.tokens_sold = token.tokens_sold.checked_sub(amount_in)
+                .ok_or(CustomError::InsufficientFunds)?;
+            // Contributions bought in at cost and can be refunded at cost, so a
+            // seller riding the price they pushed up is paid from trades only.
+            token.total_contributed = token.total_contributed.checked_sub(amount_out)
+                .filter(|total| *total >= token.refundable)
+                .ok_or(CustomError::InsufficientFunds)?;
+            (amount_out, token.tokens_sold)
+        }
+        LaunchMode::Lbp { .. } => {
+            let pool = ctx.accounts.liquidity_pool.as_mut().ok_or(CustomError::InvalidPool)?;
+            let current_time = Clock::get()?.unix_timestamp;
+            require!(current_time < pool.end_time, CustomError::PoolNotActive);
+
+            let (token_weight, sol_weight) = pool.weights(current_time);
+            let amount_out = calculate_weighted_out(pool.reserve_one, token_weight, pool.reserve_two, sol_weight, amount_in)?;
+
+            pool.reserve_one = pool.reserve_one.checked_add(amount_in)
+                .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+            pool.reserve_two -= amount_out;
+            token.total_contributed = pool.reserve_two;
+            (amount_out, token.total_supply - pool.reserve_one)
+        }
+        LaunchMode::SealedBid { .. } | LaunchMode::Lottery { .. } => return err!(CustomError::InvalidLaunchMode),
+    };
+    require!(amount_out > 0, CustomError::InsufficientFunds);
+    require!(amount_out >= minimum_amount_out, CustomError::SlippageExceeded);
+
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        "global".as_bytes(),
+        &[ctx.bumps.program_account],
+    ]];
+
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.user_token_account.to_account_info(),
+                to: ctx.accounts.pool_token_account.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            },
+        ),
+        amount_in,
+    )?;
+    system_program::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.program_account.to_account_info(),
+                to: ctx.accounts.user.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        amount_out,
+    )?;
+
+    let token = &ctx.accounts.token;
+    emit_cpi!(Trade {
+        token: token.key(),
+        user: ctx.accounts.user.key(),
+        is_buy: false,
+        amount_in,
+        amount_out,
+        tokens_sold,
+        total_contributed: token.total_contributed,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct Sell<'info> {
+    #[account(mut, has_one = mint)]
+    pub token: Box<Account<'info, TokenDetails>>,
+
+    #[account(
+        mut,
+        seeds = [LiquidityPool::POOL_SEED_PREFIX.as_bytes(), mint.key().as_ref()],
+        bump = liquidity_pool.bump
+    )]
+    pub liquidity_pool: Option<Account<'info, LiquidityPool>>, // Only for bootstrapping pools
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"global"],
+        bump,
+    )]
+    /// CHECK: PDA holding launch SOL and supply
+    pub program_account: AccountInfo<'info>,
+
+    pub mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = program_account,
+    )]
+    pub pool_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = user,
+    )]
+    pub user_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+
+    /// Last, so indexers keep reading trades at the original positions.
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.is_paused(pause::SELL) @ CustomError::ProgramPaused,
+    )]
+    pub config: Box<Account<'info, Config>>,
+}
@@ -0,0 +1,43 @@
+use anchor_lang::prelude::*;
+use crate::errors::CustomError;
+use crate::events::ConfigChangeQueued;
+use crate::state::{Config, PendingConfigChange};
+
+/// Queues `fee_recipient` behind the timelock like any other parameter
+/// change. It goes on top of whatever change is already pending and keeps
+/// that change's eta, so the fee admin cannot hold back the admin's changes;
+/// the admin can still cancel it.
+pub fn set_fee_recipient(ctx: Context<SetFeeRecipient>, fee_recipient: Pubkey) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    let timestamp = Clock::get()?.unix_timestamp;
+    let (mut params, eta) = match config.pending_change {
+        Some(change) => (change.params, change.eta),
+        None => (
+            config.params,
+            timestamp.checked_add(config.params.config_delay)
+                .ok_or(CustomError::OverflowOrUnderflowOccurred)?,
+        ),
+    };
+    params.fee_recipient = fee_recipient;
+    params.validate()?;
+
+    config.pending_change = Some(PendingConfigChange { params, eta });
+
+    emit_cpi!(ConfigChangeQueued { params, eta, timestamp });
+
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetFeeRecipient<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = fee_admin @ CustomError::Unauthorized,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    pub fee_admin: Signer<'info>,
+}
@@ -0,0 +1,56 @@
+use anchor_lang::prelude::*;
+use crate::errors::CustomError;
+use crate::events::PauseUpdated;
+use crate::state::{pause, Config};
+
+/// Replaces the pause state wholesale: `paused` stops every pausable
+/// instruction, `paused_instructions` stops those whose `pause` bit is set.
+/// The pause guardian can only add to what is paused; lifting a pause takes
+/// the admin.
+pub fn set_pause(ctx: Context<SetPause>, paused: bool, paused_instructions: u32) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    if ctx.accounts.authority.key() != config.admin {
+        let keeps_paused = paused || !config.paused;
+        let keeps_bits = paused_instructions & config.paused_instructions == config.paused_instructions;
+        require!(keeps_paused && keeps_bits, CustomError::Unauthorized);
+    }
+
+    // Sealed-bid reveal windows are extended by the time reveals spend paused.
+    let timestamp = Clock::get()?.unix_timestamp;
+    let reveals_were_paused = config.is_paused(pause::REVEAL_BID);
+    config.paused = paused;
+    config.paused_instructions = paused_instructions;
+    match (reveals_were_paused, config.is_paused(pause::REVEAL_BID)) {
+        (false, true) => config.reveal_paused_at = timestamp,
+        (true, false) => {
+            config.reveal_paused_for += timestamp - config.reveal_paused_at;
+            config.reveal_paused_at = 0;
+        }
+        _ => {}
+    }
+
+    emit_cpi!(PauseUpdated {
+        authority: ctx.accounts.authority.key(),
+        paused,
+        paused_instructions,
+        timestamp,
+    });
+
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetPause<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = authority.key() == config.admin || authority.key() == config.pause_guardian
+            @ CustomError::Unauthorized,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    /// The admin or the pause guardian.
+    pub authority: Signer<'info>,
+}
@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+use crate::errors::CustomError;
+use crate::events::RolesUpdated;
+use crate::state::Config;
+
+/// Hands the day-to-day roles to keys other than the admin's.
+pub fn set_roles(ctx: Context<SetRoles>, fee_admin: Pubkey, pause_guardian: Pubkey) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    config.fee_admin = fee_admin;
+    config.pause_guardian = pause_guardian;
+
+    emit_cpi!(RolesUpdated {
+        fee_admin,
+        pause_guardian,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetRoles<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ CustomError::Unauthorized,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    pub admin: Signer<'info>,
+}
@@ -0,0 +1,158 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::{
+    token::{self, Token, Mint, TokenAccount, Transfer},
+    associated_token::AssociatedToken,
+};
+use crate::errors::CustomError;
+use crate::events::TokensClaimed;
+use crate::state::{pause, BidBook, BidCommitment, Config, LaunchMode, LaunchStatus, TokenDetails};
+
+pub fn settle_bid(ctx: Context<SettleBid>) -> Result<()> {
+    let token = &ctx.accounts.token;
+    let bid_book = &mut ctx.accounts.bid_book;
+    let LaunchMode::SealedBid { penalty_bps, .. } = token.mode else {
+        return err!(CustomError::InvalidLaunchMode);
+    };
+
+    // A cancelled or failed launch never clears, so its bids are settled as
+    // refunds.
+    require!(bid_book.cleared || token.status != LaunchStatus::Active, CustomError::BidsNotCleared);
+    require!(!ctx.accounts.bid.settled, CustomError::BidAlreadySettled);
+    // The launch stays virtual until finalization completes, which would
+    // otherwise read as a launch that missed its target.
+    require!(!token.finalize_in_progress(), CustomError::FinalizeInProgress);
+
+    let bid = &ctx.accounts.bid;
+    // A launch that cleared below its target refunds every deposit in full.
+    let (tokens, spent, penalty) = if token.is_virtual {
+        (0, 0, 0)
+    } else if !bid.revealed {
+        (0, 0, bid_book.penalty(bid.deposit, penalty_bps)?)
+    } else {
+        let (tokens, spent) = bid_book.allocation(bid)?;
+        (tokens, spent, 0)
+    };
+    let refund_amount = bid.deposit
+        .checked_sub(spent)
+        .and_then(|amount| amount.checked_sub(penalty))
+        .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+
+    // Like `refund`, paying a deposit back is never paused; only handing
+    // out tokens is.
+    require!(tokens == 0 || !ctx.accounts.config.is_paused(pause::SETTLE_BID), CustomError::ProgramPaused);
+
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        "global".as_bytes(),
+        &[ctx.bumps.program_account],
+    ]];
+
+    if tokens > 0 {
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.pool_token_account.to_account_info(),
+                    to: ctx.accounts.user_token_account.to_account_info(),
+                    authority: ctx.accounts.program_account.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            tokens,
+        )?;
+    }
+
+    if refund_amount > 0 {
+        system_program::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.program_account.to_account_info(),
+                    to: ctx.accounts.user.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            refund_amount,
+        )?;
+    }
+
+    let bid = &mut ctx.accounts.bid;
+    bid.total_tokens = tokens;
+    bid.settled = true;
+
+    emit_cpi!(TokensClaimed {
+        token: ctx.accounts.token.key(),
+        user: ctx.accounts.user.key(),
+        amount: tokens,
+        refund: refund_amount,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SettleBid<'info> {
+    #[account(has_one = mint)]
+    pub token: Box<Account<'info, TokenDetails>>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        mut,
+        seeds = [b"bid-book", token.key().as_ref()],
+        bump = bid_book.bump
+    )]
+    pub bid_book: Box<Account<'info, BidBook>>,
+
+    #[account(
+        mut,
+        has_one = user,
+        seeds = [b"bid", token.key().as_ref(), user.key().as_ref()],
+        bump = bid.bump
+    )]
+    pub bid: Box<Account<'info, BidCommitment>>,
+
+    #[account(mut)]
+    /// CHECK: Owner of the position, pinned by `has_one`; receives the tokens and any refund
+    pub user: AccountInfo<'info>,
+
+    /// Whoever sends the transaction; settling is permissionless, so this
+    /// only pays for the owner's token account the first time it is needed.
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"global"],
+        bump,
+    )]
+    /// CHECK: PDA holding launch SOL and supply
+    pub program_account: AccountInfo<'info>,
+
+    pub mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = program_account,
+    )]
+    pub pool_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = cranker,
+        associated_token::mint = mint,
+        associated_token::authority = user,
+    )]
+    pub user_token_account: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,162 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::{
+    token::{self, Token, Mint, TokenAccount, Transfer},
+    associated_token::AssociatedToken,
+};
+use crate::errors::CustomError;
+use crate::events::TokensClaimed;
+use crate::state::{pause, Config, LaunchMode, LotteryDraw, TokenDetails, UserContribution};
+
+pub fn settle_tickets(ctx: Context<SettleTickets>) -> Result<()> {
+    let token = &ctx.accounts.token;
+    let lottery = &ctx.accounts.lottery;
+    let position = &ctx.accounts.user_contribution;
+    let LaunchMode::Lottery { ticket_price, .. } = token.mode else {
+        return err!(CustomError::InvalidLaunchMode);
+    };
+
+    require!(lottery.drawn, CustomError::LotteryNotDrawn);
+    require!(position.amount > 0 && position.ticket_count > 0, CustomError::NothingToSettle);
+
+    // A draw that leaves the raise under target refunds every ticket.
+    let (tokens, refund_amount) = if token.total_contributed >= token.target {
+        require!(!token.is_virtual, CustomError::LaunchNotFinalized);
+
+        let won = (position.first_ticket..position.first_ticket + position.ticket_count)
+            .filter(|ticket| lottery.is_winner(*ticket))
+            .count() as u64;
+        let sale_supply = token.total_supply - token.total_supply / 2;
+        let tokens_per_ticket = lottery.tokens_per_ticket(sale_supply);
+
+        (
+            won * tokens_per_ticket,
+            (position.ticket_count as u64 - won) * ticket_price,
+        )
+    } else {
+        (0, position.amount)
+    };
+
+    // Like `refund`, paying a deposit back is never paused; only handing
+    // out tokens is.
+    require!(tokens == 0 || !ctx.accounts.config.is_paused(pause::SETTLE_TICKETS), CustomError::ProgramPaused);
+
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        "global".as_bytes(),
+        &[ctx.bumps.program_account],
+    ]];
+
+    if tokens > 0 {
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.pool_token_account.to_account_info(),
+                    to: ctx.accounts.user_token_account.to_account_info(),
+                    authority: ctx.accounts.program_account.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            tokens,
+        )?;
+    }
+
+    if refund_amount > 0 {
+        system_program::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.program_account.to_account_info(),
+                    to: ctx.accounts.user.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            refund_amount,
+        )?;
+    }
+
+    let position = &mut ctx.accounts.user_contribution;
+    position.total_tokens = tokens;
+    position.amount = 0;
+
+    emit_cpi!(TokensClaimed {
+        token: ctx.accounts.token.key(),
+        user: ctx.accounts.user.key(),
+        amount: tokens,
+        refund: refund_amount,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SettleTickets<'info> {
+    #[account(has_one = mint)]
+    pub token: Box<Account<'info, TokenDetails>>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        seeds = [b"lottery", token.key().as_ref()],
+        bump = lottery.bump
+    )]
+    pub lottery: Box<Account<'info, LotteryDraw>>,
+
+    #[account(
+        mut,
+        has_one = user,
+        has_one = token,
+        seeds = [
+            b"user-contribution",
+            user.key().as_ref(),
+            token.key().as_ref(),
+            &user_contribution.contribution_number.to_le_bytes()
+        ],
+        bump = user_contribution.bump
+    )]
+    pub user_contribution: Box<Account<'info, UserContribution>>,
+
+    #[account(mut)]
+    /// CHECK: Owner of the position, pinned by `has_one`; receives the tokens and any refund
+    pub user: AccountInfo<'info>,
+
+    /// Whoever sends the transaction; settling is permissionless, so this
+    /// only pays for the owner's token account the first time it is needed.
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"global"],
+        bump,
+    )]
+    /// CHECK: PDA holding launch SOL and supply
+    pub program_account: AccountInfo<'info>,
+
+    pub mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = program_account,
+    )]
+    pub pool_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = cranker,
+        associated_token::mint = mint,
+        associated_token::authority = user,
+    )]
+    pub user_token_account: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,98 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use crate::errors::CustomError;
+use crate::events::{BondSettled, LaunchFailed};
+use crate::state::{CreatorProfile, LaunchMode, LaunchStatus, TickerRecord, TokenDetails};
+
+/// Fails a bonded launch that reached its target but was left unfinalized
+/// past the grace period. Anyone can call it; the slashed part of the bond
+/// is shared by the positions as they refund.
+pub fn slash_bond(ctx: Context<SlashBond>) -> Result<()> {
+    let token = &ctx.accounts.token;
+    let current_time = Clock::get()?.unix_timestamp;
+
+    require!(token.is_virtual, CustomError::LaunchAlreadyFinalized);
+    token.require_active()?;
+    require!(token.mode == LaunchMode::Standard, CustomError::InvalidLaunchMode);
+    require!(token.bond > 0, CustomError::NothingToSettle);
+    require!(
+        token.target_reached_at > 0 && token.total_contributed >= token.target,
+        CustomError::TargetNotReached
+    );
+    require!(
+        current_time > token.target_reached_at + token.terms.finalize_grace_period,
+        CustomError::GracePeriodNotOver
+    );
+
+    let token = &mut ctx.accounts.token;
+    token.status = LaunchStatus::Failed;
+    ctx.accounts.creator_profile.record_failure()?;
+    TickerRecord::release(&ctx.accounts.ticker_record, &token.key(), &ctx.accounts.creator)?;
+    let (returned, slashed) = token.settle_bond(true)?;
+
+    if returned > 0 {
+        system_program::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.program_account.to_account_info(),
+                    to: ctx.accounts.creator.to_account_info(),
+                },
+                &[&[
+                    "global".as_bytes(),
+                    &[ctx.bumps.program_account],
+                ]],
+            ),
+            returned,
+        )?;
+    }
+
+    let token = &ctx.accounts.token;
+    emit_cpi!(LaunchFailed {
+        token: token.key(),
+        creator: token.creator,
+        total_contributed: token.total_contributed,
+        timestamp: current_time,
+    });
+    emit_cpi!(BondSettled {
+        token: token.key(),
+        creator: token.creator,
+        returned,
+        slashed,
+        timestamp: current_time,
+    });
+
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SlashBond<'info> {
+    #[account(mut)]
+    pub token: Box<Account<'info, TokenDetails>>,
+
+    #[account(
+        mut,
+        seeds = [b"creator-profile", token.creator.as_ref()],
+        bump = creator_profile.bump,
+    )]
+    pub creator_profile: Box<Account<'info, CreatorProfile>>,
+
+    #[account(mut, address = token.creator)]
+    /// CHECK: Receives what is left of the bond
+    pub creator: AccountInfo<'info>,
+
+    #[account(mut)]
+    /// CHECK: The launch's ticker record, checked and closed by `TickerRecord::release`
+    pub ticker_record: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"global"],
+        bump,
+    )]
+    /// CHECK: PDA holding launch SOL and supply
+    pub program_account: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,71 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{spl_token::native_mint, Mint};
+use crate::errors::CustomError;
+use crate::events::LbpStarted;
+use crate::state::{pause, Config, LaunchMode, LiquidityPool, TokenDetails};
+
+pub fn start_lbp(ctx: Context<StartLbp>) -> Result<()> {
+    let token = &ctx.accounts.token;
+    token.require_active()?;
+    let LaunchMode::Lbp { start_weight_bps, end_weight_bps, duration } = token.mode else {
+        return err!(CustomError::InvalidLaunchMode);
+    };
+
+    // The whole supply is seeded against the creator's initial SOL, so the
+    // pool's closing price carries over when it migrates.
+    let current_time = Clock::get()?.unix_timestamp;
+    let pool = &mut ctx.accounts.liquidity_pool;
+    pool.set_inner(LiquidityPool::new(token.mint, ctx.bumps.liquidity_pool));
+    pool.token_two = native_mint::ID;
+    pool.reserve_one = token.total_supply;
+    pool.reserve_two = token.total_contributed;
+    pool.total_supply = LiquidityPool::initial_shares(pool.reserve_one, pool.reserve_two);
+    pool.start_weight = start_weight_bps;
+    pool.end_weight = end_weight_bps;
+    pool.start_time = current_time;
+    pool.end_time = current_time.checked_add(duration)
+        .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+
+    emit_cpi!(LbpStarted {
+        token: token.key(),
+        liquidity_pool: pool.key(),
+        token_reserve: pool.reserve_one,
+        sol_reserve: pool.reserve_two,
+        start_weight_bps,
+        end_weight_bps,
+        end_time: pool.end_time,
+        timestamp: current_time,
+    });
+
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct StartLbp<'info> {
+    #[account(has_one = creator, has_one = mint)]
+    pub token: Box<Account<'info, TokenDetails>>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.is_paused(pause::START_LBP) @ CustomError::ProgramPaused,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        init,
+        payer = creator,
+        space = LiquidityPool::ACCOUNT_SIZE,
+        seeds = [LiquidityPool::POOL_SEED_PREFIX.as_bytes(), mint.key().as_ref()],
+        bump
+    )]
+    pub liquidity_pool: Box<Account<'info, LiquidityPool>>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    pub mint: Box<Account<'info, Mint>>,
+
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,122 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use crate::errors::CustomError;
+use crate::events::ContributionWithdrawn;
+use crate::state::{pause, Config, LaunchMode, PenaltyRecipient, TokenDetails, UserContribution};
+use crate::utils::math::{mul_div_u64, Rounding};
+
+/// Takes `amount` back out of an open position while the sale is running,
+/// less the protocol's early-exit penalty. A position withdrawn in full is
+/// closed.
+pub fn withdraw_contribution(ctx: Context<WithdrawContribution>, amount: u64) -> Result<()> {
+    let token = &ctx.accounts.token;
+
+    token.require_active()?;
+    require!(token.is_virtual, CustomError::LaunchAlreadyFinalized);
+    // Tickets and bids are not priced per lamport, so only standard
+    // positions can be split.
+    require!(token.mode == LaunchMode::Standard, CustomError::InvalidLaunchMode);
+    // After the deadline the sale is over either way: `refund` or the finalize steps
+    // take it from there.
+    let current_time = Clock::get()?.unix_timestamp;
+    require!(current_time <= token.refund_deadline, CustomError::WithdrawalWindowClosed);
+    require!(token.total_contributed < token.target, CustomError::TargetReached);
+
+    let position = &ctx.accounts.user_contribution;
+    require!(amount > 0 && amount <= position.amount, CustomError::InvalidInputValue);
+
+    // The withdrawn share of the position goes back to the curve, rounded in
+    // the curve's favour.
+    let tokens = mul_div_u64(position.total_tokens, amount, position.amount, Rounding::Up)?;
+    let penalty = mul_div_u64(amount, token.terms.withdraw_penalty_bps as u64, 10_000, Rounding::Up)?;
+
+    let bump = ctx.bumps.program_account;
+    pay(ctx.accounts, bump, ctx.accounts.user.to_account_info(), amount - penalty)?;
+    let kept_for_contributors = match token.terms.withdraw_penalty_recipient {
+        PenaltyRecipient::Treasury => {
+            if penalty > 0 {
+                pay(ctx.accounts, bump, ctx.accounts.fee_recipient.to_account_info(), penalty)?;
+            }
+            0
+        }
+        PenaltyRecipient::Contributors => penalty,
+    };
+
+    let token = &mut ctx.accounts.token;
+    token.tokens_sold = token.tokens_sold.saturating_sub(tokens);
+    token.total_contributed -= amount;
+    token.refundable = token.refundable.saturating_sub(amount);
+    token.withdrawal_penalties = token.withdrawal_penalties.checked_add(kept_for_contributors)
+        .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+
+    let user_contribution = &mut ctx.accounts.user_contribution;
+    user_contribution.amount -= amount;
+    user_contribution.total_tokens -= tokens;
+    if user_contribution.amount == 0 {
+        token.open_positions -= 1;
+        user_contribution.close(ctx.accounts.user.to_account_info())?;
+    }
+
+    emit_cpi!(ContributionWithdrawn {
+        token: token.key(),
+        user: ctx.accounts.user.key(),
+        user_contribution: user_contribution.key(),
+        amount,
+        penalty,
+        tokens,
+        total_contributed: token.total_contributed,
+        timestamp: current_time,
+    });
+
+    Ok(())
+}
+
+fn pay<'info>(accounts: &WithdrawContribution<'info>, bump: u8, to: AccountInfo<'info>, lamports: u64) -> Result<()> {
+    system_program::transfer(
+        CpiContext::new_with_signer(
+            accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: accounts.program_account.to_account_info(),
+                to,
+            },
+            &[&[
+                "global".as_bytes(),
+                &[bump],
+            ]],
+        ),
+        lamports,
+    )
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct WithdrawContribution<'info> {
+    #[account(mut)]
+    pub token: Box<Account<'info, TokenDetails>>,
+    #[account(
+        mut,
+        has_one = user,
+        has_one = token,
+        constraint = user_contribution.key() == user_contribution.address(&token)? @ CustomError::InvalidContribution,
+    )]
+    pub user_contribution: Box<Account<'info, UserContribution>>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.is_paused(pause::WITHDRAW_CONTRIBUTION) @ CustomError::ProgramPaused,
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(mut, address = config.params.fee_recipient @ CustomError::InvalidFeeRecipient)]
+    /// CHECK: Receives penalties routed to the treasury
+    pub fee_recipient: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [b"global"],
+        bump,
+    )]
+    /// CHECK: PDA holding launch SOL and supply
+    pub program_account: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+}
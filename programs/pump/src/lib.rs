@@ -1,11 +1,14 @@
 use anchor_lang::prelude::*;
 
 pub mod errors;
+pub mod events;
 pub mod instructions;
 pub mod state;
 pub mod utils;
 
 use instructions::*;
+use state::{LaunchMode, ProtocolParams};
+use utils::Curve;
 
 declare_id!("Etv32C8ZjmHycJEpY6jUUWzMH1bbm7ztC3xUYsKi1366");
 
@@ -13,15 +16,63 @@ declare_id!("Etv32C8ZjmHycJEpY6jUUWzMH1bbm7ztC3xUYsKi1366");
 pub mod pump {
     use super::*;
 
+    #[allow(clippy::too_many_arguments)]
     pub fn initialize(
         ctx: Context<CreateToken>, 
         name: String, 
         ticker: String, 
         total_supply: u64, 
         initial_target: u64,
-        decimals: u8
+        decimals: u8,
+        mode: LaunchMode,
+        curve: Curve,
+        bond: u64,
     ) -> Result<()> {
-        instructions::initialize(ctx, name, ticker, total_supply, initial_target, decimals)
+        instructions::initialize(ctx, name, ticker, total_supply, initial_target, decimals, mode, curve, bond)
+    }
+
+    pub fn initialize_config(ctx: Context<InitializeConfig>, params: ProtocolParams) -> Result<()> {
+        instructions::initialize_config(ctx, params)
+    }
+
+    pub fn queue_config_change(ctx: Context<QueueConfigChange>, params: ProtocolParams) -> Result<()> {
+        instructions::queue_config_change(ctx, params)
+    }
+
+    pub fn execute_config_change(ctx: Context<ExecuteConfigChange>) -> Result<()> {
+        instructions::execute_config_change(ctx)
+    }
+
+    pub fn cancel_config_change(ctx: Context<CancelConfigChange>) -> Result<()> {
+        instructions::cancel_config_change(ctx)
+    }
+
+    pub fn set_pause(ctx: Context<SetPause>, paused: bool, paused_instructions: u32) -> Result<()> {
+        instructions::set_pause(ctx, paused, paused_instructions)
+    }
+
+    pub fn propose_admin(ctx: Context<ProposeAdmin>, pending_admin: Pubkey) -> Result<()> {
+        instructions::propose_admin(ctx, pending_admin)
+    }
+
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        instructions::accept_admin(ctx)
+    }
+
+    pub fn set_roles(ctx: Context<SetRoles>, fee_admin: Pubkey, pause_guardian: Pubkey) -> Result<()> {
+        instructions::set_roles(ctx, fee_admin, pause_guardian)
+    }
+
+    pub fn set_fee_recipient(ctx: Context<SetFeeRecipient>, fee_recipient: Pubkey) -> Result<()> {
+        instructions::set_fee_recipient(ctx, fee_recipient)
+    }
+
+    pub fn reserve_ticker(ctx: Context<ReserveTicker>, ticker: String) -> Result<()> {
+        instructions::reserve_ticker(ctx, ticker)
+    }
+
+    pub fn release_ticker(ctx: Context<ReleaseTicker>, ticker: String) -> Result<()> {
+        instructions::release_ticker(ctx, ticker)
     }
 
     pub fn contribute(ctx: Context<Contribute>, amount: u64) -> Result<()> {
@@ -32,7 +83,75 @@ pub mod pump {
         instructions::refund(ctx)
     }
 
-    pub fn finalize(ctx: Context<Finalize>) -> Result<()> {
-        instructions::finalize(ctx)
+    pub fn cancel_launch(ctx: Context<CancelLaunch>, burn_supply: bool) -> Result<()> {
+        instructions::cancel_launch(ctx, burn_supply)
+    }
+
+    pub fn slash_bond(ctx: Context<SlashBond>) -> Result<()> {
+        instructions::slash_bond(ctx)
+    }
+
+    pub fn extend_deadline(ctx: Context<ExtendDeadline>, extension: i64) -> Result<()> {
+        instructions::extend_deadline(ctx, extension)
+    }
+
+    pub fn opt_out(ctx: Context<OptOut>) -> Result<()> {
+        instructions::opt_out(ctx)
+    }
+
+    pub fn withdraw_contribution(ctx: Context<WithdrawContribution>, amount: u64) -> Result<()> {
+        instructions::withdraw_contribution(ctx, amount)
+    }
+
+    pub fn finalize_settle<'info>(ctx: Context<'_, '_, '_, 'info, FinalizeSettle<'info>>) -> Result<()> {
+        instructions::finalize_settle(ctx)
+    }
+
+    pub fn finalize_create_pool(ctx: Context<FinalizeCreatePool>) -> Result<()> {
+        instructions::finalize_create_pool(ctx)
+    }
+
+    pub fn finalize_lock_lp(ctx: Context<FinalizeLockLp>) -> Result<()> {
+        instructions::finalize_lock_lp(ctx)
+    }
+
+    pub fn finalize_complete(ctx: Context<FinalizeComplete>) -> Result<()> {
+        instructions::finalize_complete(ctx)
+    }
+
+    pub fn commit_bid(ctx: Context<CommitBid>, commitment: [u8; 32], deposit: u64) -> Result<()> {
+        instructions::commit_bid(ctx, commitment, deposit)
+    }
+
+    pub fn reveal_bid(ctx: Context<RevealBid>, amount: u64, price_limit: u64, salt: [u8; 32]) -> Result<()> {
+        instructions::reveal_bid(ctx, amount, price_limit, salt)
+    }
+
+    pub fn settle_bid(ctx: Context<SettleBid>) -> Result<()> {
+        instructions::settle_bid(ctx)
+    }
+
+    pub fn close_lottery(ctx: Context<CloseLottery>) -> Result<()> {
+        instructions::close_lottery(ctx)
+    }
+
+    pub fn draw_lottery(ctx: Context<DrawLottery>) -> Result<()> {
+        instructions::draw_lottery(ctx)
+    }
+
+    pub fn settle_tickets(ctx: Context<SettleTickets>) -> Result<()> {
+        instructions::settle_tickets(ctx)
+    }
+
+    pub fn start_lbp(ctx: Context<StartLbp>) -> Result<()> {
+        instructions::start_lbp(ctx)
+    }
+
+    pub fn buy(ctx: Context<Buy>, amount_in: u64, minimum_amount_out: u64) -> Result<()> {
+        instructions::buy(ctx, amount_in, minimum_amount_out)
+    }
+
+    pub fn sell(ctx: Context<Sell>, amount_in: u64, minimum_amount_out: u64) -> Result<()> {
+        instructions::sell(ctx, amount_in, minimum_amount_out)
     }
 }
\ No newline at end of file
@@ -1,6 +1,10 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
 use anchor_lang::system_program;
 use anchor_spl::token::{self, Token, TokenAccount};
+use crate::errors::CustomError;
+use crate::utils::Curve;
+use crate::utils::math::{mul_div, mul_div_u64, sqrt, to_u64, unit, Rounding};
 
 #[account]
 pub struct TokenDetails {
@@ -14,11 +18,59 @@ pub struct TokenDetails {
     pub decimals: u8,
     pub contribution_count: u32,
     pub bump: u8,
+    pub creator: Pubkey,
+    pub mint: Pubkey,
+    pub mode: LaunchMode,
+    pub tickets_sold: u32,
+    pub curve: Curve,
+    pub tokens_sold: u64,
+    /// Lamports of open contributions that `refund` may still pay back.
+    /// Sales along the curve cannot dip into them.
+    pub refundable: u64,
+    pub status: LaunchStatus,
+    /// When positions become refundable if the target has not been reached.
+    pub refund_deadline: i64,
+    /// When the creator pushed `refund_deadline` back, or 0. A deadline can
+    /// only be extended once.
+    pub extended_at: i64,
+    /// Until when contributors from before the extension can opt out.
+    pub opt_out_deadline: i64,
+    /// Lamports withdrawn by contributors who opted out of the extension.
+    pub opted_out: u64,
+    /// Lamports kept for the contributors who stay: early-exit penalties
+    /// and any slashed creator bond. Refunds pay them out pro rata, and
+    /// `finalize_settle` adds them to the liquidity.
+    pub withdrawal_penalties: u64,
+    pub terms: LaunchTerms,
+    /// Lamports the creator put up at `initialize`, held in custody apart
+    /// from contributions until the launch graduates, fails or is cancelled.
+    pub bond: u64,
+    /// When contributions first reached the target, or 0. The creator has
+    /// `terms.finalize_grace_period` from then to finalize before the bond
+    /// can be slashed.
+    pub target_reached_at: i64,
+    /// How far finalization has got. Each `finalize_*` step moves it on.
+    pub finalize_step: FinalizeStep,
+    /// Positions `finalize_settle` has paid out.
+    pub contributions_settled: u32,
+    /// Positions that still hold a stake in the launch. A standard launch
+    /// is done settling once `finalize_settle` has paid this many.
+    pub open_positions: u32,
+    /// Lamports and tokens `finalize_settle` sets aside for the pool.
+    pub liquidity_sol: u64,
+    pub liquidity_tokens: u64,
+    /// OpenBook market `finalize_create_pool` opened the pool against.
+    pub market: Pubkey,
 }
 
 impl TokenDetails {
     pub const MAX_NAME_LENGTH: usize = 32;
     pub const MAX_TICKER_LENGTH: usize = 10;
+    /// SPL mints allow up to 255 decimals, but prices are quoted per whole
+    /// token and stop fitting well beyond SOL's own 9.
+    pub const MAX_DECIMALS: u8 = 9;
+    /// Largest supply in whole tokens, one trillion.
+    pub const MAX_SUPPLY_TOKENS: u64 = 1_000_000_000_000;
     
     pub const ACCOUNT_SIZE: usize = 8 +  
         4 + Self::MAX_NAME_LENGTH +      
@@ -30,20 +82,480 @@ impl TokenDetails {
         1 +                              
         1 +                              
         4 +                              
-        1;                               
+        1 +                              
+        32 +                             
+        32 +                             
+        LaunchMode::MAX_SIZE +
+        4 +
+        Curve::MAX_SIZE +
+        8 +
+        8 +
+        1 +
+        8 +
+        8 +
+        8 +
+        8 +
+        8 +
+        LaunchTerms::SIZE +
+        8 +
+        8 +
+        1 +
+        4 +
+        4 +
+        8 +
+        8 +
+        32;
+}
+
+/// A creator's track record, at `[b"creator-profile", creator]`. Created by
+/// their first `initialize` and kept up to date as their launches graduate,
+/// fail or are cancelled.
+#[account]
+pub struct CreatorProfile {
+    pub creator: Pubkey,
+    pub launches: u32,
+    /// Launches that reached their target and were finalized.
+    pub graduations: u32,
+    pub cancellations: u32,
+    /// Launches that missed their target, or were abandoned past it, and
+    /// went to refunds.
+    pub failures: u32,
+    /// Lamports raised across graduated launches.
+    pub total_raised: u64,
+    pub bump: u8,
+}
+
+impl CreatorProfile {
+    pub const ACCOUNT_SIZE: usize = 8 + 32 + 4 + 4 + 4 + 4 + 8 + 1;
+
+    /// Counts a launch that just ended in failure. Every path that fails a
+    /// launch calls it exactly once.
+    pub fn record_failure(&mut self) -> Result<()> {
+        self.failures = self.failures.checked_add(1)
+            .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+        Ok(())
+    }
+}
+
+/// Registry entry for a ticker, at `[b"ticker", normalized ticker]`, so a
+/// ticker is held by at most one live launch across all creators.
+/// `initialize` claims it for the launch, and it is released again when the
+/// launch is cancelled or fails; the admin reserves well-known tickers ahead
+/// of time so nobody can launch under them.
+#[account]
+pub struct TickerRecord {
+    /// The normalized ticker.
+    pub ticker: String,
+    /// The launch that claimed the ticker, or `Pubkey::default()` while it is
+    /// reserved.
+    pub token: Pubkey,
+    pub reserved: bool,
+    pub bump: u8,
+}
+
+impl TickerRecord {
+    pub const ACCOUNT_SIZE: usize = 8 +
+        4 + TokenDetails::MAX_TICKER_LENGTH +
+        32 +
+        1 +
+        1;
+
+    /// The form tickers are registered under: ASCII letters and digits,
+    /// upper-cased, so `usdc` and `USDC` share one entry.
+    pub fn normalize(ticker: &str) -> Result<String> {
+        require!(
+            !ticker.is_empty() &&
+                ticker.len() <= TokenDetails::MAX_TICKER_LENGTH &&
+                ticker.bytes().all(|byte| byte.is_ascii_alphanumeric()),
+            CustomError::InvalidTicker
+        );
+        Ok(ticker.to_ascii_uppercase())
+    }
+
+    /// Closes the record `info` holds for `token` into `destination`, so the
+    /// ticker can be launched again.
+    pub fn release<'info>(info: &AccountInfo<'info>, token: &Pubkey, destination: &AccountInfo<'info>) -> Result<()> {
+        let record = Account::<TickerRecord>::try_from(info)?;
+        require_keys_eq!(record.token, *token, CustomError::InvalidTickerRecord);
+        record.close(destination.clone())
+    }
+}
+
+/// Where a launch stands apart from finalization, which `is_virtual` tracks.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LaunchStatus {
+    #[default]
+    Active,
+    /// Closed by `cancel_launch`. Every position can be refunded right away
+    /// and nothing can be bought or finalized.
+    Cancelled,
+    /// Missed its target by the refund deadline. Set by the first refund.
+    Failed,
+}
+
+/// Steps of finalization, in order. A step that runs out of compute is
+/// simply sent again; one that already ran is skipped.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FinalizeStep {
+    #[default]
+    Pending,
+    /// `finalize_settle` is paying contributors out in batches.
+    Settling,
+    /// The fee is paid and the liquidity set aside.
+    Settled,
+    PoolCreated,
+    LpLocked,
+    /// Set by `finalize_complete` along with clearing `is_virtual`.
+    Complete,
+}
+
+/// How contributions are collected for a launch.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LaunchMode {
+    /// Open contributions through `contribute`.
+    Standard,
+    /// Contributors commit a hash of their bid with a deposit, then reveal it
+    /// once the commit phase is over. Bids are priced in lamports per whole
+    /// token and bucketed into `BidBook::PRICE_TIERS` tiers starting at
+    /// `min_price`, `price_step` apart.
+    SealedBid {
+        commit_duration: i64,
+        reveal_duration: i64,
+        penalty_bps: u16,
+        min_price: u64,
+        price_step: u64,
+    },
+    /// Contributions buy tickets until `sale_duration` has passed. Winning
+    /// tickets are drawn from `randomness_account`, which must be owned by
+    /// the protocol's `randomness_program`; losing tickets are refunded in
+    /// full.
+    Lottery {
+        ticket_price: u64,
+        winning_tickets: u32,
+        sale_duration: i64,
+        randomness_account: Pubkey,
+    },
+    /// The supply is sold from a weighted pool whose token weight moves from
+    /// `start_weight_bps` to `end_weight_bps` over `duration`, then migrates
+    /// through the finalize steps.
+    Lbp {
+        start_weight_bps: u16,
+        end_weight_bps: u16,
+        duration: i64,
+    },
+}
+
+impl LaunchMode {
+    // Sized for the largest variant, `Lottery`.
+    pub const MAX_SIZE: usize = 1 + 8 + 4 + 8 + 32;
+}
+
+impl TokenDetails {
+    /// Records when contributions first reach the target.
+    pub fn note_target_reached(&mut self, timestamp: i64) {
+        if self.target_reached_at == 0 && self.total_contributed >= self.target {
+            self.target_reached_at = timestamp;
+        }
+    }
+
+    /// Releases the bond, slashing `terms.bond_slash_bps` of it to the
+    /// contributors if `slashed`. Returns the lamports owed back to the
+    /// creator and the lamports slashed; the caller pays the creator.
+    pub fn settle_bond(&mut self, slashed: bool) -> Result<(u64, u64)> {
+        let slash = if slashed {
+            mul_div_u64(self.bond, self.terms.bond_slash_bps as u64, 10_000, Rounding::Up)?
+        } else {
+            0
+        };
+        let returned = self.bond - slash;
+        self.withdrawal_penalties = self.withdrawal_penalties.checked_add(slash)
+            .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+        self.bond = 0;
+        Ok((returned, slash))
+    }
+
+    /// Fails unless the launch is still open, naming how it ended. A launch
+    /// partway through finalization is no longer open either.
+    pub fn require_active(&self) -> Result<()> {
+        match self.status {
+            LaunchStatus::Active => {}
+            LaunchStatus::Cancelled => return err!(CustomError::LaunchCancelled),
+            LaunchStatus::Failed => return err!(CustomError::LaunchFailed),
+        }
+        require!(!self.finalize_in_progress(), CustomError::FinalizeInProgress);
+        Ok(())
+    }
+
+    /// Whether finalization has started but not yet completed.
+    pub fn finalize_in_progress(&self) -> bool {
+        self.finalize_step != FinalizeStep::Pending && self.finalize_step != FinalizeStep::Complete
+    }
+
+    /// Whether the finalize step that follows `from` still has to run. One
+    /// that already ran is skipped; one sent before `from` is reached fails.
+    pub fn finalize_step_due(&self, from: FinalizeStep) -> Result<bool> {
+        if self.finalize_step > from {
+            return Ok(false);
+        }
+        require!(self.finalize_step == from, CustomError::FinalizeStepOutOfOrder);
+        Ok(true)
+    }
+
+    /// The fee and the crank reward a successful raise pays, in lamports.
+    /// The reward never takes more than the fee leaves.
+    pub fn finalize_costs(&self) -> Result<(u64, u64)> {
+        let fee = mul_div_u64(self.total_contributed, self.terms.finalize_fee_bps as u64, 10_000, Rounding::Down)?;
+        let reward = self.terms.crank_reward.min(self.total_contributed - fee);
+        Ok((fee, reward))
+    }
+
+    /// How much of the crank reward finalization has earned so far, as a
+    /// running total over the steps. Settlement earns half of it, spread over
+    /// the positions it pays so every settle batch pays its keeper; the pool,
+    /// lock and complete steps earn a third of the rest each, complete
+    /// taking the rounding.
+    pub fn crank_reward_earned(&self) -> Result<u64> {
+        let (_, reward) = self.finalize_costs()?;
+        let settlement = reward / 2;
+        let per_step = (reward - settlement) / 3;
+        let earned = match self.finalize_step {
+            FinalizeStep::Pending => 0,
+            FinalizeStep::Settling => mul_div_u64(
+                settlement,
+                self.contributions_settled.min(self.open_positions) as u64,
+                self.open_positions.max(1) as u64,
+                Rounding::Down,
+            )?,
+            FinalizeStep::Settled => settlement,
+            FinalizeStep::PoolCreated => settlement + per_step,
+            FinalizeStep::LpLocked => settlement + 2 * per_step,
+            FinalizeStep::Complete => reward,
+        };
+        Ok(earned)
+    }
+
+    /// Pays `cranker` out of custody what the step that just ran added to
+    /// `crank_reward_earned`, which stood at `earned_before`, and returns it.
+    pub fn pay_crank_reward<'info>(
+        &self,
+        earned_before: u64,
+        program_account: &AccountInfo<'info>,
+        cranker: &AccountInfo<'info>,
+        system_program: &AccountInfo<'info>,
+        bump: u8,
+    ) -> Result<u64> {
+        let reward = self.crank_reward_earned()? - earned_before;
+        if reward > 0 {
+            system_program::transfer(
+                CpiContext::new_with_signer(
+                    system_program.clone(),
+                    system_program::Transfer {
+                        from: program_account.clone(),
+                        to: cranker.clone(),
+                    },
+                    &[&[
+                        "global".as_bytes(),
+                        &[bump],
+                    ]],
+                ),
+                reward,
+            )?;
+        }
+        Ok(reward)
+    }
+
+    /// End of the commit and reveal phases of a sealed-bid launch.
+    pub fn sealed_bid_phases(&self) -> Option<(i64, i64)> {
+        match self.mode {
+            LaunchMode::SealedBid { commit_duration, reveal_duration, .. } => {
+                let commit_end = self.creation_time.checked_add(commit_duration)?;
+                let reveal_end = commit_end.checked_add(reveal_duration)?;
+                Some((commit_end, reveal_end))
+            }
+            _ => None,
+        }
+    }
+
+    /// End of the ticket sale of a lottery launch.
+    pub fn lottery_sale_end(&self) -> Option<i64> {
+        match self.mode {
+            LaunchMode::Lottery { sale_duration, .. } => self.creation_time.checked_add(sale_duration),
+            _ => None,
+        }
+    }
+}
+
+#[account]
+pub struct BidBook {
+    pub token: Pubkey,
+    pub demand: [u64; BidBook::PRICE_TIERS],
+    pub total_deposits: u64,
+    pub revealed_deposits: u64,
+    pub cleared: bool,
+    pub clearing_price: u64,
+    pub clearing_tier: u8,
+    pub eligible_demand: u64,
+    pub filled_amount: u64,
+    pub tokens_sold: u64,
+    pub settled_demand: u64,
+    pub settled_tokens: u64,
+    pub settled_spent: u64,
+    pub settled_forfeits: u64,
+    pub settled_penalties: u64,
+    /// `Config::reveal_pause_time` when the last bid was committed.
+    pub reveal_pause_time: i64,
+    pub bump: u8,
+}
+
+impl BidBook {
+    pub const PRICE_TIERS: usize = 8;
+
+    /// The launch's `reveal_end`, pushed back by however long reveals have
+    /// been paused since the last bid was committed, so a pause never runs
+    /// down the time bidders have to reveal.
+    pub fn reveal_end(&self, reveal_end: i64, config: &Config, now: i64) -> i64 {
+        reveal_end.saturating_add(config.reveal_pause_time(now) - self.reveal_pause_time)
+    }
+
+    /// Runs a uniform-price clearing over the revealed demand. The clearing
+    /// price is the highest tier at which demand from that tier and above buys
+    /// the whole `sale_supply`; every bid at or above it is filled pro rata at
+    /// that price. If no tier sells out, all eligible bids fill at `min_price`.
+    pub fn clear(&mut self, min_price: u64, price_step: u64, sale_supply: u64, decimals: u8) -> Result<()> {
+        let unit = unit(decimals)?;
+        let supply = sale_supply as u128;
+
+        let mut eligible: u128 = 0;
+        for tier in (0..Self::PRICE_TIERS).rev() {
+            eligible += self.demand[tier] as u128;
+            let price = min_price as u128 + tier as u128 * price_step as u128;
+            let tokens = mul_div(eligible, unit, price, Rounding::Down)?;
+
+            if supply > 0 && tokens >= supply {
+                self.clearing_tier = tier as u8;
+                self.clearing_price = price as u64;
+                self.eligible_demand = eligible as u64;
+                self.filled_amount = to_u64(mul_div(supply, price, unit, Rounding::Up)?)?;
+                self.tokens_sold = sale_supply;
+                self.cleared = true;
+                return Ok(());
+            }
+        }
+
+        self.clearing_tier = 0;
+        self.clearing_price = min_price;
+        self.eligible_demand = eligible as u64;
+        self.filled_amount = eligible as u64;
+        self.tokens_sold = to_u64(mul_div(eligible, unit, min_price as u128, Rounding::Down)?)?;
+        self.cleared = true;
+        Ok(())
+    }
+
+    /// Tokens allocated to a revealed bid and the lamports it spends. Both are
+    /// taken from running totals over the bids settled so far, tokens rounding
+    /// down and spend rounding up, so once every bid settles the book hands
+    /// out exactly `tokens_sold` and collects exactly `filled_amount`.
+    pub fn allocation(&mut self, bid: &BidCommitment) -> Result<(u64, u64)> {
+        match bid.tier {
+            Some(tier) if bid.revealed && tier >= self.clearing_tier && self.eligible_demand > 0 => {
+                let settled = self.settled_demand.checked_add(bid.amount)
+                    .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+                let tokens = mul_div_u64(settled, self.tokens_sold, self.eligible_demand, Rounding::Down)?;
+                let spent = mul_div_u64(settled, self.filled_amount, self.eligible_demand, Rounding::Up)?;
+
+                let allocation = (tokens - self.settled_tokens, spent - self.settled_spent);
+                self.settled_demand = settled;
+                self.settled_tokens = tokens;
+                self.settled_spent = spent;
+                Ok(allocation)
+            }
+            _ => Ok((0, 0)),
+        }
+    }
+
+    /// Total penalty forfeited by deposits that were never revealed, rounded up.
+    pub fn forfeited_deposits(&self, penalty_bps: u16) -> Result<u64> {
+        let unrevealed = self.total_deposits - self.revealed_deposits;
+        mul_div_u64(unrevealed, penalty_bps as u64, 10_000, Rounding::Up)
+    }
+
+    /// Penalty charged to a single unrevealed deposit, taken from a running
+    /// total so the penalties add up to exactly `forfeited_deposits`.
+    pub fn penalty(&mut self, deposit: u64, penalty_bps: u16) -> Result<u64> {
+        let forfeits = self.settled_forfeits.checked_add(deposit)
+            .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+        let penalties = mul_div_u64(forfeits, penalty_bps as u64, 10_000, Rounding::Up)?;
+
+        let penalty = penalties - self.settled_penalties;
+        self.settled_forfeits = forfeits;
+        self.settled_penalties = penalties;
+        Ok(penalty)
+    }
+
+    pub const ACCOUNT_SIZE: usize = 8 +
+        32 +
+        8 * Self::PRICE_TIERS +
+        8 +
+        8 +
+        1 +
+        8 +
+        1 +
+        8 +
+        8 +
+        8 +
+        8 +
+        8 +
+        8 +
+        8 +
+        8 +
+        8 +
+        1;
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
-pub struct ContributionRecord {
+#[account]
+pub struct BidCommitment {
     pub user: Pubkey,
     pub token: Pubkey,
+    pub commitment: [u8; 32],
+    pub deposit: u64,
     pub amount: u64,
+    pub price_limit: u64,
+    pub tier: Option<u8>,
+    pub revealed: bool,
+    pub settled: bool,
     pub total_tokens: u64,
-    pub contribution_number: u32,
     pub timestamp: i64,
     pub bump: u8,
 }
 
+impl BidCommitment {
+    pub const ACCOUNT_SIZE: usize = 8 +
+        32 +
+        32 +
+        32 +
+        8 +
+        8 +
+        8 +
+        2 +
+        1 +
+        1 +
+        8 +
+        8 +
+        1;
+
+    /// Hash a bidder commits to; binding the bidder's key stops a commitment
+    /// from being copied by another wallet.
+    pub fn hash(user: &Pubkey, amount: u64, price_limit: u64, salt: &[u8; 32]) -> [u8; 32] {
+        hashv(&[
+            user.as_ref(),
+            &amount.to_le_bytes(),
+            &price_limit.to_le_bytes(),
+            salt,
+        ])
+        .to_bytes()
+    }
+}
+
 #[account]
 #[derive(Default)]
 pub struct UserContribution {
@@ -54,6 +566,12 @@ pub struct UserContribution {
     pub contribution_number: u32,
     pub timestamp: i64,
     pub bump: u8,
+    pub first_ticket: u32,
+    pub ticket_count: u32,
+    /// Set once the position was withdrawn in protest of a deadline extension.
+    pub opted_out: bool,
+    /// Set once `finalize_settle` has paid the position its tokens.
+    pub settled: bool,
 }
 
 impl UserContribution {
@@ -64,16 +582,352 @@ impl UserContribution {
         8 +                              
         4 +                              
         8 +                              
-        1;                               
+        1 +                              
+        4 +
+        4 +
+        1 +
+        1;
+
+    /// Address the position was created at. The creator's opening position
+    /// is keyed by ticker, since `initialize` creates it alongside the launch.
+    pub fn address(&self, token: &TokenDetails) -> Result<Pubkey> {
+        let number = self.contribution_number.to_le_bytes();
+        let bump = [self.bump];
+        let seeds: [&[u8]; 5] = if self.contribution_number == 0 {
+            [b"user-contribution", self.user.as_ref(), token.ticker.as_bytes(), &[0], &bump]
+        } else {
+            [b"user-contribution", self.user.as_ref(), self.token.as_ref(), &number, &bump]
+        };
+        Pubkey::create_program_address(&seeds, &crate::ID)
+            .map_err(|_| error!(CustomError::InvalidContribution))
+    }
 }
 
 #[account]
-pub struct UserContributions {
-    pub contributions: Vec<ContributionRecord>, 
+pub struct LotteryDraw {
+    pub token: Pubkey,
+    pub close_slot: u64,
+    pub total_tickets: u32,
+    pub winning_tickets: u32,
+    pub randomness: [u8; 32],
+    pub offset: u32,
+    pub stride: u32,
+    pub stride_inverse: u32,
+    pub drawn: bool,
+    pub bump: u8,
+}
+
+impl LotteryDraw {
+    pub const MAX_TICKETS_PER_ENTRY: u64 = 100;
+
+    pub const ACCOUNT_SIZE: usize = 8 +
+        32 +
+        8 +
+        4 +
+        4 +
+        32 +
+        4 +
+        4 +
+        4 +
+        1 +
+        1;
+
+    /// Picks the winning tickets from `randomness`. Winners are the first
+    /// `winning_tickets` entries of the sequence `offset + k * stride (mod N)`,
+    /// with `stride` coprime to `N`, so every ticket can be checked on its own.
+    pub fn draw(&mut self, randomness: [u8; 32], winning_tickets: u32) {
+        let total = self.total_tickets as u64;
+        self.randomness = randomness;
+        self.winning_tickets = winning_tickets.min(self.total_tickets);
+        self.drawn = true;
+
+        if total == 0 {
+            return;
+        }
+
+        let offset = u64::from_le_bytes(randomness[0..8].try_into().unwrap()) % total;
+        let mut stride = u64::from_le_bytes(randomness[8..16].try_into().unwrap()) % total;
+        if stride == 0 {
+            stride = 1;
+        }
+        while gcd(stride, total) != 1 {
+            stride = stride % total + 1;
+        }
+
+        self.offset = offset as u32;
+        self.stride = (stride % total) as u32;
+        self.stride_inverse = mod_inverse(stride % total, total) as u32;
+    }
+
+    /// Tokens each winning ticket receives out of `sale_supply`, rounded down.
+    /// The remainder is dust that finalize adds to the liquidity.
+    pub fn tokens_per_ticket(&self, sale_supply: u64) -> u64 {
+        sale_supply.checked_div(self.winning_tickets as u64).unwrap_or(0)
+    }
+
+    /// Whether `ticket` is among the drawn winners.
+    pub fn is_winner(&self, ticket: u32) -> bool {
+        let total = self.total_tickets as u64;
+        if !self.drawn || ticket as u64 >= total {
+            return false;
+        }
+
+        let distance = (ticket as u64 + total - self.offset as u64) % total;
+        let position = distance * self.stride_inverse as u64 % total;
+        position < self.winning_tickets as u64
+    }
 }
 
-impl UserContributions {
-    pub const ACCOUNT_SIZE: usize = 8 + 4 + (10 * UserContribution::ACCOUNT_SIZE);
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+fn mod_inverse(value: u64, modulus: u64) -> u64 {
+    if modulus == 1 {
+        return 0;
+    }
+
+    let (mut old_r, mut r) = (value as i128, modulus as i128);
+    let (mut old_s, mut s) = (1_i128, 0_i128);
+    while r != 0 {
+        let quotient = old_r / r;
+        (old_r, r) = (r, old_r - quotient * r);
+        (old_s, s) = (s, old_s - quotient * s);
+    }
+    old_s.rem_euclid(modulus as i128) as u64
+}
+
+/// Protocol-wide settings, held in the `config` PDA.
+#[account]
+pub struct Config {
+    /// Signs for protocol-level changes. Every role is checked as a plain
+    /// signer, so it can be a PDA a multisig program signs for through CPI.
+    pub admin: Pubkey,
+    /// Proposed by `propose_admin`, takes over once it signs `accept_admin`.
+    /// `Pubkey::default()` when nothing is pending.
+    pub pending_admin: Pubkey,
+    /// Can queue a new fee recipient.
+    pub fee_admin: Pubkey,
+    /// Can pause instructions, but only the admin can resume them.
+    pub pause_guardian: Pubkey,
+    /// Terms new launches are opened under.
+    pub params: ProtocolParams,
+    /// Replacement for `params`, queued behind the timelock.
+    pub pending_change: Option<PendingConfigChange>,
+    /// Stops every pausable instruction at once.
+    pub paused: bool,
+    /// Instructions stopped on their own, as `pause` bits.
+    pub paused_instructions: u32,
+    /// When `reveal_bid` was last paused, by `paused` or its own bit; 0 while
+    /// it is not.
+    pub reveal_paused_at: i64,
+    /// Seconds `reveal_bid` spent paused before `reveal_paused_at`.
+    pub reveal_paused_for: i64,
+    pub bump: u8,
+}
+
+impl Config {
+    pub const ACCOUNT_SIZE: usize = 8 +
+        32 +
+        32 +
+        32 +
+        32 +
+        ProtocolParams::SIZE +
+        1 + PendingConfigChange::SIZE +
+        1 +
+        4 +
+        8 +
+        8 +
+        1;
+
+    pub fn is_paused(&self, instruction: u32) -> bool {
+        self.paused || self.paused_instructions & instruction != 0
+    }
+
+    /// Seconds `reveal_bid` has spent paused, up to `now`.
+    pub fn reveal_pause_time(&self, now: i64) -> i64 {
+        let current = if self.reveal_paused_at > 0 { now - self.reveal_paused_at } else { 0 };
+        self.reveal_paused_for + current
+    }
+}
+
+/// Bits of `Config::paused_instructions`. `refund` and `opt_out` have none so
+/// contributors can always get their money out, and `settle_bid` and
+/// `settle_tickets` still pay refunds while paused; only token deliveries
+/// stop. Time `reveal_bid` spends paused extends sealed-bid reveal windows.
+pub mod pause {
+    pub const INITIALIZE: u32 = 1 << 0;
+    pub const CONTRIBUTE: u32 = 1 << 1;
+    pub const WITHDRAW_CONTRIBUTION: u32 = 1 << 2;
+    pub const EXTEND_DEADLINE: u32 = 1 << 3;
+    pub const FINALIZE: u32 = 1 << 4;
+    pub const COMMIT_BID: u32 = 1 << 5;
+    pub const REVEAL_BID: u32 = 1 << 6;
+    pub const SETTLE_BID: u32 = 1 << 7;
+    pub const CLOSE_LOTTERY: u32 = 1 << 8;
+    pub const DRAW_LOTTERY: u32 = 1 << 9;
+    pub const SETTLE_TICKETS: u32 = 1 << 10;
+    pub const START_LBP: u32 = 1 << 11;
+    pub const BUY: u32 = 1 << 12;
+    pub const SELL: u32 = 1 << 13;
+}
+
+/// Protocol parameters. They only change through `queue_config_change` and
+/// `execute_config_change`, and a launch keeps the terms it opened under.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProtocolParams {
+    /// Lamports a creator pays to open a launch.
+    pub launch_fee: u64,
+    /// Share of a successful raise, in bps, `finalize_settle` sends to the fee
+    /// recipient.
+    pub finalize_fee_bps: u16,
+    /// Receives the finalize fee and early-exit penalties.
+    pub fee_recipient: Pubkey,
+    /// Lamports out of a successful raise paid to whoever sends the finalize
+    /// steps, shared out between them, so keepers finish launches as soon as
+    /// they qualify.
+    pub crank_reward: u64,
+    /// Lamports a launch has to raise.
+    pub target: u64,
+    /// Most a single position or bid can hold, in lamports.
+    pub max_contribution: u64,
+    /// Highest valuation, in lamports at the opening price, a creator
+    /// without a graduated launch may open with. 0 lifts the limit.
+    pub max_unproven_valuation: u64,
+    /// How long a launch has to reach its target before positions become
+    /// refundable.
+    pub refund_delay: i64,
+    /// Longest a creator may push back their launch's refund deadline, in
+    /// seconds.
+    pub max_deadline_extension: i64,
+    /// Share of the launch fee, in bps, handed back to the creator when a
+    /// launch is cancelled.
+    pub cancel_rebate_bps: u16,
+    /// Penalty, in bps, on contributions withdrawn before the sale closes.
+    pub withdraw_penalty_bps: u16,
+    pub withdraw_penalty_recipient: PenaltyRecipient,
+    /// Share of a creator bond, in bps, paid to contributors when the launch
+    /// is cancelled after others joined or abandoned past its target.
+    pub bond_slash_bps: u16,
+    /// How long a creator has to finalize once the target is reached before
+    /// their bond can be slashed.
+    pub finalize_grace_period: i64,
+    /// How long a queued change waits before it can be executed.
+    pub config_delay: i64,
+    /// Program that owns the randomness accounts lotteries are drawn from.
+    /// Lottery launches cannot open while it is `Pubkey::default()`.
+    pub randomness_program: Pubkey,
+}
+
+impl ProtocolParams {
+    /// Shortest timelock a config can be given, so holders always get a
+    /// day's notice of a change.
+    pub const MIN_CONFIG_DELAY: i64 = 24 * 60 * 60;
+
+    pub const SIZE: usize = 8 + 2 + 32 + 8 + 8 + 8 + 8 + 8 + 8 + 2 + 2 + 1 + 2 + 8 + 8 + 32;
+
+    pub fn validate(&self) -> Result<()> {
+        // What a launch that only just meets its target pays out of the raise.
+        let fee = mul_div_u64(self.target, self.finalize_fee_bps as u64, 10_000, Rounding::Down)?;
+        let bps_valid = self.finalize_fee_bps <= 10_000 &&
+            self.cancel_rebate_bps <= 10_000 &&
+            self.withdraw_penalty_bps <= 10_000 &&
+            self.bond_slash_bps <= 10_000;
+        let amounts_valid = self.fee_recipient != Pubkey::default() &&
+            self.target > 0 &&
+            self.max_contribution > 0 &&
+            self.crank_reward < self.target &&
+            self.crank_reward.checked_add(fee).is_some_and(|costs| costs <= self.target);
+        let durations_valid = self.refund_delay > 0 &&
+            self.max_deadline_extension >= 0 &&
+            self.finalize_grace_period > 0 &&
+            self.config_delay >= Self::MIN_CONFIG_DELAY;
+        require!(bps_valid && amounts_valid && durations_valid, CustomError::InvalidConfig);
+        Ok(())
+    }
+
+    /// The part of the parameters a launch is held to for its whole life.
+    pub fn terms(&self) -> LaunchTerms {
+        LaunchTerms {
+            launch_fee: self.launch_fee,
+            finalize_fee_bps: self.finalize_fee_bps,
+            crank_reward: self.crank_reward,
+            max_contribution: self.max_contribution,
+            max_deadline_extension: self.max_deadline_extension,
+            cancel_rebate_bps: self.cancel_rebate_bps,
+            withdraw_penalty_bps: self.withdraw_penalty_bps,
+            withdraw_penalty_recipient: self.withdraw_penalty_recipient,
+            bond_slash_bps: self.bond_slash_bps,
+            finalize_grace_period: self.finalize_grace_period,
+        }
+    }
+}
+
+impl Default for ProtocolParams {
+    fn default() -> Self {
+        Self {
+            launch_fee: 100_000_000,
+            finalize_fee_bps: 500,
+            fee_recipient: Pubkey::default(),
+            crank_reward: 5_000_000,
+            target: 200 * 1_000_000_000,
+            max_contribution: 10 * 1_000_000_000,
+            max_unproven_valuation: 0,
+            refund_delay: 7 * 24 * 60 * 60,
+            max_deadline_extension: 0,
+            cancel_rebate_bps: 0,
+            withdraw_penalty_bps: 0,
+            withdraw_penalty_recipient: PenaltyRecipient::Treasury,
+            bond_slash_bps: 5_000,
+            finalize_grace_period: 3 * 24 * 60 * 60,
+            config_delay: 2 * 24 * 60 * 60,
+            randomness_program: Pubkey::default(),
+        }
+    }
+}
+
+/// `ProtocolParams` as they stood when a launch was initialized. The target
+/// and refund deadline are fixed on the launch itself.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LaunchTerms {
+    pub launch_fee: u64,
+    pub finalize_fee_bps: u16,
+    pub crank_reward: u64,
+    pub max_contribution: u64,
+    pub max_deadline_extension: i64,
+    pub cancel_rebate_bps: u16,
+    pub withdraw_penalty_bps: u16,
+    pub withdraw_penalty_recipient: PenaltyRecipient,
+    pub bond_slash_bps: u16,
+    pub finalize_grace_period: i64,
+}
+
+impl LaunchTerms {
+    pub const SIZE: usize = 8 + 2 + 8 + 8 + 8 + 2 + 2 + 1 + 2 + 8;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PendingConfigChange {
+    pub params: ProtocolParams,
+    /// Earliest time `execute_config_change` applies it.
+    pub eta: i64,
+}
+
+impl PendingConfigChange {
+    pub const SIZE: usize = ProtocolParams::SIZE + 8;
+}
+
+/// Where early-exit penalties go.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PenaltyRecipient {
+    /// The protocol fee recipient.
+    #[default]
+    Treasury,
+    /// The launch's remaining contributors.
+    Contributors,
 }
 
 #[account]
@@ -94,11 +948,16 @@ pub struct LiquidityPool {
     pub reserve_one: u64,  
     pub reserve_two: u64,  
     pub bump: u8,          
+    pub start_weight: u16, 
+    pub end_weight: u16,   
+    pub start_time: i64,   
+    pub end_time: i64,     
 }
 
 impl LiquidityPool {
     pub const POOL_SEED_PREFIX: &'static str = "liquidity_pool";
-    pub const ACCOUNT_SIZE: usize = 8 + 32 + 32 + 8 + 8 + 8 + 1;
+    pub const ACCOUNT_SIZE: usize = 8 + 32 + 32 + 8 + 8 + 8 + 1 + 2 + 2 + 8 + 8;
+    pub const WEIGHT_DENOMINATOR: u64 = 10_000;
 
     pub fn new(token_one: Pubkey, bump: u8) -> Self {
         Self {
@@ -108,8 +967,30 @@ impl LiquidityPool {
             reserve_one: 0_u64,
             reserve_two: 0_u64,
             bump,
+            start_weight: 0_u16,
+            end_weight: 0_u16,
+            start_time: 0_i64,
+            end_time: 0_i64,
         }
     }
+
+    /// LP shares minted for the opening deposit, the geometric mean of the
+    /// two reserves rounded down.
+    pub fn initial_shares(reserve_one: u64, reserve_two: u64) -> u64 {
+        sqrt(reserve_one as u128 * reserve_two as u128) as u64
+    }
+
+    /// Current `(token_one, token_two)` weights in bps, moving linearly from
+    /// `start_weight` to `end_weight` between `start_time` and `end_time`.
+    pub fn weights(&self, now: i64) -> (u64, u64) {
+        let start = self.start_weight as i128;
+        let end = self.end_weight as i128;
+        let elapsed = (now.clamp(self.start_time, self.end_time) - self.start_time) as i128;
+        let duration = (self.end_time - self.start_time).max(1) as i128;
+
+        let weight_one = (start + (end - start) * elapsed / duration) as u64;
+        (weight_one, Self::WEIGHT_DENOMINATOR - weight_one)
+    }
 }
 
 pub trait LiquidityPoolAccount<'info> {
@@ -1,9 +1,16 @@
-use std::ops::{Div, Mul};
+use anchor_lang::prelude::*;
+use crate::utils::math::{mul_div, pow_wad, to_u64, Rounding, WAD};
 
-pub fn convert_to_float(value: u64, decimals: u8) -> f64 {
-    (value as f64).div(f64::powf(10.0, decimals as f64))
-}
+/// Output of a weighted-product swap:
+/// `balance_out * (1 - (balance_in / (balance_in + amount_in)) ^ (weight_in / weight_out))`.
+/// Every step rounds in the pool's favour.
+pub fn calculate_weighted_out(balance_in: u64, weight_in: u64, balance_out: u64, weight_out: u64, amount_in: u64) -> Result<u64> {
+    let balance_after = balance_in as u128 + amount_in as u128;
+    let ratio = mul_div(balance_in as u128, WAD, balance_after, Rounding::Up)?;
+    let exponent = mul_div(weight_in as u128, WAD, weight_out as u128, Rounding::Down)?;
+    let remaining = pow_wad(ratio, exponent, Rounding::Up)?.min(WAD);
+    let amount_out = to_u64(mul_div(balance_out as u128, WAD - remaining, WAD, Rounding::Down)?)?;
 
-pub fn convert_from_float(value: f64, decimals: u8) -> u64 {
-    value.mul(f64::powf(10.0, decimals as f64)) as u64
+    // Never drain the pool side completely.
+    Ok(amount_out.min(balance_out.saturating_sub(1)))
 }
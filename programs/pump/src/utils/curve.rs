@@ -0,0 +1,353 @@
+use anchor_lang::prelude::*;
+use crate::errors::CustomError;
+use crate::utils::math::{exp_wad, ln_wad, mul_div, sqrt, to_u64, unit, with_margin, Rounding, WAD};
+
+/// Price curve a launch sells its supply along. Prices are in lamports per
+/// whole token (`10^decimals` base units); supplies are in base units.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Curve {
+    /// `price = base_price + slope * sold`, with `sold` in whole tokens.
+    Linear { base_price: u64, slope: u64 },
+    /// `price = base_price * e^(sold / growth)`.
+    Exponential { base_price: u64, growth: u64 },
+    /// `price = max_price / (1 + e^(-(sold - midpoint) / width))`.
+    Sigmoid { max_price: u64, midpoint: u64, width: u64 },
+    /// `x * y = k` over virtual SOL and token reserves.
+    ConstantProduct { virtual_sol: u64, virtual_tokens: u64 },
+}
+
+impl Curve {
+    pub const MAX_SIZE: usize = 1 + 8 + 8 + 8;
+
+    /// Checks the parameters and that the curve can price `supply`.
+    pub fn validate(&self, supply: u64, decimals: u8) -> Result<()> {
+        let valid = match *self {
+            Curve::Linear { base_price, slope } => base_price > 0 || slope > 0,
+            Curve::Exponential { base_price, growth } => base_price > 0 && growth > 0,
+            Curve::Sigmoid { max_price, width, .. } => max_price > 0 && width > 0,
+            Curve::ConstantProduct { virtual_sol, virtual_tokens } => {
+                virtual_sol > 0 && virtual_tokens > supply
+            }
+        };
+        require!(valid, CustomError::InvalidCurve);
+
+        self.amount_in(0, supply, decimals)?;
+        Ok(())
+    }
+
+    /// Spot price at `supply` sold, rounded up.
+    pub fn price(&self, supply: u64, decimals: u8) -> Result<u64> {
+        let unit = unit(decimals)?;
+        let supply = supply as u128;
+        let price = match *self {
+            Curve::Linear { base_price, slope } => {
+                base_price as u128 + mul_div(slope as u128, supply, unit, Rounding::Up)?
+            }
+            Curve::Exponential { base_price, growth } => {
+                let growth = exp_wad(mul_div(supply, WAD, growth as u128, Rounding::Up)? as i128)?;
+                mul_div(base_price as u128, growth, WAD, Rounding::Up)?
+            }
+            Curve::Sigmoid { max_price, midpoint, width } => {
+                let z = signed_ratio(supply, midpoint, width)?;
+                let tail = exp_wad(-z.abs())?;
+                let numerator = if z >= 0 { WAD } else { tail };
+                mul_div(max_price as u128, numerator, WAD + tail, Rounding::Up)?
+            }
+            Curve::ConstantProduct { virtual_sol, virtual_tokens } => {
+                let remaining = (virtual_tokens as u128).checked_sub(supply)
+                    .filter(|remaining| *remaining > 0)
+                    .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+                let k = virtual_sol as u128 * virtual_tokens as u128;
+                mul_div(mul_div(k, unit, remaining, Rounding::Up)?, 1, remaining, Rounding::Up)?
+            }
+        };
+        to_u64(price)
+    }
+
+    /// Lamports needed to buy `tokens` when `supply` is already sold, rounded up.
+    pub fn amount_in(&self, supply: u64, tokens: u64, decimals: u8) -> Result<u64> {
+        let end = supply.checked_add(tokens).ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+        let cost = self.integral(end, decimals, Rounding::Up)?
+            .saturating_sub(self.integral(supply, decimals, Rounding::Down)?);
+        to_u64(cost)
+    }
+
+    /// Tokens bought with `lamports` when `supply` is already sold, rounded down.
+    pub fn amount_out(&self, supply: u64, lamports: u64, decimals: u8) -> Result<u64> {
+        let paid = self.integral(supply, decimals, Rounding::Down)?;
+        let target = paid + lamports as u128;
+        let estimate = to_u64(self.supply_at(target, decimals)?)?.saturating_sub(supply);
+        let fits = |tokens: u64| {
+            supply.checked_add(tokens)
+                .and_then(|end| self.integral(end, decimals, Rounding::Up).ok())
+                .is_some_and(|cost| cost.saturating_sub(paid) <= lamports as u128)
+        };
+
+        // The closed-form estimate is close; walk it to the exact largest fit.
+        let (mut low, mut high) = if fits(estimate) {
+            let mut step = 1_u64;
+            loop {
+                match estimate.checked_add(step) {
+                    Some(next) if fits(next) => step = step.saturating_mul(2),
+                    Some(next) => break (estimate + step / 2, next),
+                    None => break (estimate, u64::MAX),
+                }
+            }
+        } else {
+            let mut step = 1_u64;
+            loop {
+                match estimate.checked_sub(step) {
+                    Some(previous) if fits(previous) => break (previous, estimate - step / 2),
+                    Some(_) => step = step.saturating_mul(2),
+                    None => break (0, estimate),
+                }
+            }
+        };
+        while high - low > 1 {
+            let middle = low + (high - low) / 2;
+            if fits(middle) {
+                low = middle;
+            } else {
+                high = middle;
+            }
+        }
+        Ok(low)
+    }
+
+    /// Lamports returned for selling `tokens` back when `supply` is sold, rounded down.
+    pub fn sell_amount_out(&self, supply: u64, tokens: u64, decimals: u8) -> Result<u64> {
+        let start = supply.checked_sub(tokens).ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+        let proceeds = self.integral(supply, decimals, Rounding::Down)?
+            .saturating_sub(self.integral(start, decimals, Rounding::Up)?);
+        to_u64(proceeds)
+    }
+
+    /// Lamports paid for the first `supply` base units.
+    fn integral(&self, supply: u64, decimals: u8, rounding: Rounding) -> Result<u128> {
+        let unit = unit(decimals)?;
+        let supply = supply as u128;
+        match *self {
+            Curve::Linear { base_price, slope } => {
+                let flat = mul_div(base_price as u128, supply, unit, rounding)?;
+                let sloped = mul_div(mul_div(slope as u128, supply, unit, rounding)?, supply, 2 * unit, rounding)?;
+                flat.checked_add(sloped).ok_or(error!(CustomError::OverflowOrUnderflowOccurred))
+            }
+            Curve::Exponential { base_price, growth } => {
+                let growth_factor = exp_wad(mul_div(supply, WAD, growth as u128, rounding)? as i128)?;
+                let scale = base_price as u128 * growth as u128;
+                let cost = mul_div(scale, growth_factor.saturating_sub(WAD), unit_wad(unit)?, rounding)?;
+                Ok(with_margin(cost, rounding))
+            }
+            Curve::Sigmoid { max_price, midpoint, width } => {
+                let area = softplus_wad(signed_ratio(supply, midpoint, width)?)?
+                    .saturating_sub(softplus_wad(signed_ratio(0, midpoint, width)?)?);
+                let scale = max_price as u128 * width as u128;
+                let cost = mul_div(scale, area, unit_wad(unit)?, rounding)?;
+                Ok(with_margin(cost, rounding))
+            }
+            Curve::ConstantProduct { virtual_sol, virtual_tokens } => {
+                let remaining = (virtual_tokens as u128).checked_sub(supply)
+                    .filter(|remaining| *remaining > 0)
+                    .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+                mul_div(virtual_sol as u128, supply, remaining, rounding)
+            }
+        }
+    }
+
+    /// Approximate inverse of `integral`: supply sold once `cost` lamports are paid.
+    fn supply_at(&self, cost: u128, decimals: u8) -> Result<u128> {
+        if cost == 0 {
+            return Ok(0);
+        }
+
+        let unit = unit(decimals)?;
+        match *self {
+            Curve::Linear { base_price, slope } => {
+                // Positive root of `slope / 2 * y^2 + base_price * y = cost`, in whole tokens.
+                let discriminant = (2 * slope as u128).checked_mul(cost)
+                    .and_then(|sloped| sloped.checked_add(base_price as u128 * base_price as u128))
+                    .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+                mul_div(2 * cost, unit, base_price as u128 + sqrt(discriminant), Rounding::Down)
+            }
+            Curve::Exponential { base_price, growth } => {
+                let scale = base_price as u128 * growth as u128;
+                let growth_factor = WAD + mul_div(cost, unit_wad(unit)?, scale, Rounding::Down)?;
+                let exponent = ln_wad(growth_factor)?.max(0) as u128;
+                mul_div(exponent, growth as u128, WAD, Rounding::Down)
+            }
+            Curve::Sigmoid { max_price, midpoint, width } => {
+                let scale = max_price as u128 * width as u128;
+                let area = softplus_wad(signed_ratio(0, midpoint, width)?)?
+                    + mul_div(cost, unit_wad(unit)?, scale, Rounding::Down)?;
+                // softplus^-1(a) = a + ln(1 - e^-a)
+                let z = area as i128 + ln_wad(WAD.saturating_sub(exp_wad(-(area as i128))?).max(1))?;
+                let offset = z.checked_mul(width as i128)
+                    .ok_or(CustomError::OverflowOrUnderflowOccurred)? / WAD as i128;
+                Ok((midpoint as i128 + offset).max(0) as u128)
+            }
+            Curve::ConstantProduct { virtual_sol, virtual_tokens } => {
+                mul_div(virtual_tokens as u128, cost, virtual_sol as u128 + cost, Rounding::Down)
+            }
+        }
+    }
+}
+
+fn unit_wad(unit: u128) -> Result<u128> {
+    unit.checked_mul(WAD).ok_or(error!(CustomError::OverflowOrUnderflowOccurred))
+}
+
+/// `(value - offset) / scale` as a signed WAD.
+fn signed_ratio(value: u128, offset: u64, scale: u64) -> Result<i128> {
+    let difference = value as i128 - offset as i128;
+    difference.checked_mul(WAD as i128)
+        .map(|scaled| scaled / scale as i128)
+        .ok_or(error!(CustomError::OverflowOrUnderflowOccurred))
+}
+
+/// `ln(1 + e^z)` for a signed WAD `z`, as a WAD.
+fn softplus_wad(z: i128) -> Result<u128> {
+    let tail = ln_wad(WAD + exp_wad(-z.abs())?)?;
+    Ok((z.max(0) + tail) as u128)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DECIMALS: u8 = 6;
+    const UNIT: u64 = 1_000_000;
+    const SOL: u64 = 1_000_000_000;
+
+    const LINEAR: Curve = Curve::Linear { base_price: 1_000, slope: 1 };
+    const EXPONENTIAL: Curve = Curve::Exponential { base_price: 1_000, growth: 100_000_000 * UNIT };
+    const SIGMOID: Curve = Curve::Sigmoid { max_price: 10_000, midpoint: 10_000_000 * UNIT, width: 5_000_000 * UNIT };
+    const CONSTANT_PRODUCT: Curve = Curve::ConstantProduct { virtual_sol: 30 * SOL, virtual_tokens: 1_073_000_000 * UNIT };
+    const CURVES: [Curve; 4] = [LINEAR, EXPONENTIAL, SIGMOID, CONSTANT_PRODUCT];
+
+    const SUPPLIES: [u64; 7] = [
+        0,
+        1,
+        12_345 * UNIT,
+        3_000_000 * UNIT,
+        10_000_000 * UNIT,
+        123_456_789 * UNIT + 7,
+        400_000_000 * UNIT,
+    ];
+    const PAYMENTS: [u64; 5] = [1, 999, SOL / 100, SOL, 20 * SOL];
+
+    #[test]
+    fn every_curve_prices_half_a_billion_tokens() {
+        for curve in CURVES {
+            curve.validate(500_000_000 * UNIT, DECIMALS).unwrap();
+        }
+    }
+
+    #[test]
+    fn spot_prices_match_the_closed_forms() {
+        let supplies = [0, 1_000, 5_000_000, 10_000_000, 100_000_000, 500_000_000];
+        let expected = [
+            (LINEAR, [1_000, 2_000, 5_001_000, 10_001_000, 100_001_000, 500_001_000]),
+            // base * e^(sold / growth): 1000 * e is 2718.28...
+            (EXPONENTIAL, [1_000, 1_001, 1_052, 1_106, 2_719, 148_414]),
+            // Half of max_price at the midpoint.
+            (SIGMOID, [1_193, 1_193, 2_690, 5_000, 10_000, 10_000]),
+            (CONSTANT_PRODUCT, [28, 28, 29, 29, 35, 99]),
+        ];
+        for (curve, prices) in expected {
+            for (tokens, price) in supplies.into_iter().zip(prices) {
+                assert_eq!(curve.price(tokens * UNIT, DECIMALS).unwrap(), price, "{curve:?} at {tokens}");
+            }
+        }
+    }
+
+    /// Exact costs worked out to full precision, rounded down.
+    #[test]
+    fn costs_bracket_the_exact_integral() {
+        let cases = [
+            (LINEAR, 0, 1_000_000, 501_000_000_000),
+            (LINEAR, 100_000_000, 50_000_000, 6_250_050_000_000_000),
+            (EXPONENTIAL, 0, 1_000_000, 1_005_016_708),
+            (EXPONENTIAL, 400_000_000, 100_000_000, 9_381_500_906_943),
+            (SIGMOID, 10_000_000, 1_000_000, 5_249_584_441),
+            (SIGMOID, 100_000_000, 50_000_000, 499_999_999_238),
+            (CONSTANT_PRODUCT, 0, 1_000_000, 27_985_074),
+            (CONSTANT_PRODUCT, 400_000_000, 100_000_000, 8_347_401_258),
+        ];
+        for (curve, supply, tokens, exact) in cases {
+            let (supply, tokens) = (supply * UNIT, tokens * UNIT);
+            let cost = curve.amount_in(supply, tokens, DECIMALS).unwrap();
+            let proceeds = curve.sell_amount_out(supply + tokens, tokens, DECIMALS).unwrap();
+            assert!(proceeds <= exact && exact <= cost, "{curve:?}: {proceeds} / {exact} / {cost}");
+            assert!(cost - proceeds <= 50, "{curve:?}: spread of {}", cost - proceeds);
+        }
+    }
+
+    #[test]
+    fn buys_round_down_for_the_buyer() {
+        for curve in CURVES {
+            for supply in SUPPLIES {
+                for lamports in PAYMENTS {
+                    let tokens = curve.amount_out(supply, lamports, DECIMALS).unwrap();
+                    if tokens > 0 {
+                        assert!(curve.amount_in(supply, tokens, DECIMALS).unwrap() <= lamports);
+                    }
+                    // One more base unit would cost more than was paid.
+                    assert!(
+                        curve.amount_in(supply, tokens + 1, DECIMALS).unwrap() > lamports,
+                        "{curve:?} at {supply} undercharged for {lamports}",
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn sells_round_down_for_the_seller() {
+        for curve in CURVES {
+            for supply in SUPPLIES {
+                for tokens in [1, UNIT, 1_000 * UNIT, supply / 3] {
+                    let Some(start) = supply.checked_sub(tokens) else { continue };
+                    let proceeds = curve.sell_amount_out(supply, tokens, DECIMALS).unwrap();
+                    assert!(proceeds <= curve.amount_in(start, tokens, DECIMALS).unwrap(), "{curve:?} at {supply}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn buying_and_selling_back_never_profits() {
+        for curve in CURVES {
+            for supply in SUPPLIES {
+                for lamports in PAYMENTS {
+                    let tokens = curve.amount_out(supply, lamports, DECIMALS).unwrap();
+                    let proceeds = curve.sell_amount_out(supply + tokens, tokens, DECIMALS).unwrap();
+                    assert!(proceeds <= lamports, "{curve:?} at {supply} paid {proceeds} for {lamports}");
+                }
+            }
+        }
+    }
+
+    /// `integral` for an exponential curve as the series gives it, before
+    /// `with_margin`.
+    fn series_cost(base_price: u64, growth: u64, supply: u64, rounding: Rounding) -> u128 {
+        let exponent = mul_div(supply as u128, WAD, growth as u128, rounding).unwrap();
+        let growth_factor = exp_wad(exponent as i128).unwrap();
+        mul_div(base_price as u128 * growth as u128, growth_factor - WAD, UNIT as u128 * WAD, rounding).unwrap()
+    }
+
+    #[test]
+    fn the_margin_covers_the_series_error() {
+        let (base_price, growth) = (SOL, 1_000_000_000 * UNIT);
+        let curve = Curve::Exponential { base_price, growth };
+
+        // The series comes up short of the exact 35_304_623_193_541.72.
+        let supply = 35_304 * UNIT;
+        assert_eq!(series_cost(base_price, growth, supply, Rounding::Up), 35_304_623_193_541);
+        assert!(curve.integral(supply, DECIMALS, Rounding::Up).unwrap() >= 35_304_623_193_542);
+
+        // And here overshoots the exact 416_104_936_183_602_001.06.
+        let supply = 347_910_100 * UNIT;
+        assert_eq!(series_cost(base_price, growth, supply, Rounding::Down), 416_104_936_183_602_004);
+        assert!(curve.integral(supply, DECIMALS, Rounding::Down).unwrap() <= 416_104_936_183_602_001);
+    }
+}
@@ -0,0 +1,324 @@
+//! Checked integer math shared by the curves, pools and settlement paths.
+//! Fractional values are WADs (`1e18` fixed point); every operation that can
+//! lose precision takes an explicit [`Rounding`] so callers decide which side
+//! keeps the remainder.
+
+use anchor_lang::prelude::*;
+use crate::errors::CustomError;
+
+/// Direction a lossy operation rounds in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rounding {
+    Down,
+    Up,
+}
+
+pub const WAD: u128 = 1_000_000_000_000_000_000;
+const LN_2_WAD: i128 = 693_147_180_559_945_309;
+
+/// `10^decimals`, the number of base units in one whole token.
+pub fn unit(decimals: u8) -> Result<u128> {
+    10_u128.checked_pow(decimals as u32).ok_or(error!(CustomError::OverflowOrUnderflowOccurred))
+}
+
+pub fn to_u64(value: u128) -> Result<u64> {
+    u64::try_from(value).map_err(|_| error!(CustomError::OverflowOrUnderflowOccurred))
+}
+
+/// Widens a cost computed from series approximations away from the caller.
+pub fn with_margin(cost: u128, rounding: Rounding) -> u128 {
+    let margin = cost / 1_000_000_000_000 + 1;
+    match rounding {
+        Rounding::Down => cost.saturating_sub(margin),
+        Rounding::Up => cost + margin,
+    }
+}
+
+/// `a * b / denominator` with a 256-bit intermediate product.
+pub fn mul_div(a: u128, b: u128, denominator: u128, rounding: Rounding) -> Result<u128> {
+    require!(denominator > 0, CustomError::OverflowOrUnderflowOccurred);
+
+    if let Some(product) = a.checked_mul(b) {
+        let quotient = product / denominator;
+        let round_up = rounding == Rounding::Up && product % denominator != 0;
+        return Ok(quotient + round_up as u128);
+    }
+
+    let (high, low) = wide_mul(a, b);
+    require!(high < denominator, CustomError::OverflowOrUnderflowOccurred);
+
+    // Shift-subtract long division of the 256-bit product.
+    let (mut remainder, mut quotient) = (high, 0_u128);
+    for bit in (0..128).rev() {
+        let carry = remainder >> 127;
+        remainder = (remainder << 1) | ((low >> bit) & 1);
+        quotient <<= 1;
+        if carry == 1 || remainder >= denominator {
+            remainder = remainder.wrapping_sub(denominator);
+            quotient |= 1;
+        }
+    }
+
+    let round_up = rounding == Rounding::Up && remainder != 0;
+    quotient.checked_add(round_up as u128).ok_or(error!(CustomError::OverflowOrUnderflowOccurred))
+}
+
+/// `a * b / denominator` for `u64` operands, checked back into a `u64`.
+pub fn mul_div_u64(a: u64, b: u64, denominator: u64, rounding: Rounding) -> Result<u64> {
+    to_u64(mul_div(a as u128, b as u128, denominator as u128, rounding)?)
+}
+
+fn wide_mul(a: u128, b: u128) -> (u128, u128) {
+    let mask = u64::MAX as u128;
+    let (a_high, a_low) = (a >> 64, a & mask);
+    let (b_high, b_low) = (b >> 64, b & mask);
+
+    let low_low = a_low * b_low;
+    let high_low = a_high * b_low;
+    let low_high = a_low * b_high;
+    let high_high = a_high * b_high;
+
+    let middle = (low_low >> 64) + (high_low & mask) + (low_high & mask);
+    let low = (low_low & mask) | (middle << 64);
+    let high = high_high + (high_low >> 64) + (low_high >> 64) + (middle >> 64);
+    (high, low)
+}
+
+/// Integer square root, rounded down.
+pub fn sqrt(value: u128) -> u128 {
+    if value < 2 {
+        return value;
+    }
+
+    let mut root = 1_u128 << ((128 - value.leading_zeros()) / 2 + 1);
+    loop {
+        let next = (root + value / root) / 2;
+        if next >= root {
+            return root;
+        }
+        root = next;
+    }
+}
+
+/// `e^x` for a signed WAD `x`, as a WAD.
+pub fn exp_wad(x: i128) -> Result<u128> {
+    // e^x = 2^k * e^r with |r| <= ln(2) / 2
+    let k = (x + x.signum() * LN_2_WAD / 2) / LN_2_WAD;
+    let r = x - k * LN_2_WAD;
+
+    let mut term = WAD as i128;
+    let mut sum = WAD as i128;
+    for n in 1..=24 {
+        term = term * r / WAD as i128 / n;
+        if term == 0 {
+            break;
+        }
+        sum += term;
+    }
+
+    let sum = sum as u128;
+    if k >= 0 {
+        require!(k < 128 && sum.leading_zeros() as i128 > k, CustomError::OverflowOrUnderflowOccurred);
+        Ok(sum << k)
+    } else if k > -128 {
+        Ok(sum >> -k)
+    } else {
+        Ok(0)
+    }
+}
+
+/// `ln(x)` for a positive WAD `x`, as a signed WAD.
+pub fn ln_wad(x: u128) -> Result<i128> {
+    require!(x > 0, CustomError::OverflowOrUnderflowOccurred);
+
+    // ln(x) = k * ln(2) + ln(m) with m in [1, 2)
+    let k = (127 - x.leading_zeros() as i128) - (127 - WAD.leading_zeros() as i128);
+    let mut m = if k >= 0 { x >> k } else { x << -k };
+    let mut k = k;
+    while m >= 2 * WAD {
+        m >>= 1;
+        k += 1;
+    }
+    while m < WAD {
+        m <<= 1;
+        k -= 1;
+    }
+
+    // ln(m) = 2 * atanh((m - 1) / (m + 1))
+    let y = ((m - WAD) * WAD / (m + WAD)) as i128;
+    let y_squared = y * y / WAD as i128;
+    let mut term = y;
+    let mut sum = 0_i128;
+    for n in (1..=41).step_by(2) {
+        sum += term / n;
+        term = term * y_squared / WAD as i128;
+        if term == 0 {
+            break;
+        }
+    }
+
+    Ok(k * LN_2_WAD + 2 * sum)
+}
+
+/// `base^exponent` for WAD operands, widened by [`with_margin`] so the
+/// series error never lands on the caller's side.
+pub fn pow_wad(base: u128, exponent: u128, rounding: Rounding) -> Result<u128> {
+    if exponent == 0 {
+        return Ok(WAD);
+    }
+    if base == 0 {
+        return Ok(0);
+    }
+
+    let exponent = i128::try_from(exponent).map_err(|_| error!(CustomError::OverflowOrUnderflowOccurred))?;
+    let power = ln_wad(base)?.checked_mul(exponent)
+        .ok_or(CustomError::OverflowOrUnderflowOccurred)? / WAD as i128;
+    Ok(with_margin(exp_wad(power)?, rounding))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HALF: u128 = 1 << 127;
+
+    #[test]
+    fn mul_div_rounds_in_the_direction_asked() {
+        assert_eq!(mul_div(7, 3, 2, Rounding::Down).unwrap(), 10);
+        assert_eq!(mul_div(7, 3, 2, Rounding::Up).unwrap(), 11);
+        assert_eq!(mul_div(6, 3, 2, Rounding::Up).unwrap(), 9);
+    }
+
+    #[test]
+    fn mul_div_carries_a_256_bit_product() {
+        assert_eq!(mul_div(u128::MAX, u128::MAX, u128::MAX, Rounding::Up).unwrap(), u128::MAX);
+        assert_eq!(mul_div(u128::MAX, u128::MAX / 2, u128::MAX, Rounding::Down).unwrap(), u128::MAX / 2);
+        assert_eq!(
+            mul_div(u128::MAX, 3, 4, Rounding::Down).unwrap(),
+            255_211_775_190_703_847_597_530_955_573_826_158_591,
+        );
+        assert_eq!(
+            mul_div(u128::MAX, 3, 4, Rounding::Up).unwrap(),
+            255_211_775_190_703_847_597_530_955_573_826_158_592,
+        );
+        // A divisor above 2^127 shifts a bit out of the remainder.
+        // (2^127 + 5)(2^127 + 3) = (2^127 + 7)(2^127 + 1) + 8
+        assert_eq!(mul_div(HALF + 5, HALF + 3, HALF + 7, Rounding::Down).unwrap(), HALF + 1);
+        assert_eq!(mul_div(HALF + 5, HALF + 3, HALF + 7, Rounding::Up).unwrap(), HALF + 2);
+    }
+
+    #[test]
+    fn mul_div_rejects_what_does_not_fit() {
+        assert!(mul_div(1, 1, 0, Rounding::Down).is_err());
+        assert!(mul_div(u128::MAX, u128::MAX, 0, Rounding::Down).is_err());
+        assert!(mul_div(u128::MAX, 2, 1, Rounding::Down).is_err());
+        assert!(mul_div(u128::MAX, u128::MAX, u128::MAX - 1, Rounding::Down).is_err());
+        // 7 * 97_223_533_405_982_418_132_392_744_980_505_203_273 = 2 * u128::MAX + 1,
+        // which fits rounded down but not rounded up.
+        let b = 97_223_533_405_982_418_132_392_744_980_505_203_273;
+        assert_eq!(mul_div(7, b, 2, Rounding::Down).unwrap(), u128::MAX);
+        assert!(mul_div(7, b, 2, Rounding::Up).is_err());
+    }
+
+    #[test]
+    fn mul_div_u64_checks_the_result_back_into_a_u64() {
+        assert_eq!(mul_div_u64(u64::MAX, u64::MAX, u64::MAX, Rounding::Up).unwrap(), u64::MAX);
+        assert_eq!(mul_div_u64(u64::MAX, u64::MAX - 1, u64::MAX, Rounding::Down).unwrap(), u64::MAX - 1);
+        assert!(mul_div_u64(u64::MAX, 2, 1, Rounding::Down).is_err());
+        assert!(mul_div_u64(u64::MAX, u64::MAX, 0, Rounding::Down).is_err());
+    }
+
+    #[test]
+    fn sqrt_rounds_down() {
+        let roots = [(0, 0), (1, 1), (2, 1), (3, 1), (4, 2), (99, 9), (100, 10)];
+        for (value, root) in roots {
+            assert_eq!(sqrt(value), root);
+        }
+        let max = u64::MAX as u128;
+        assert_eq!(sqrt(max * max), max);
+        assert_eq!(sqrt(max * max - 1), max - 1);
+        assert_eq!(sqrt(u128::MAX), max);
+    }
+
+    /// Exact values, worked out to full precision and truncated.
+    #[test]
+    fn exp_wad_matches_known_values() {
+        let cases: [(i128, u128); 8] = [
+            (-42, 0),
+            (-20, 2_061_153_622),
+            (-1, 367_879_441_171_442_321),
+            (0, WAD),
+            (1, 2_718_281_828_459_045_235),
+            (10, 22_026_465_794_806_716_516_957),
+            (40, 235_385_266_837_019_985_407_899_910_749_034_804),
+            (46, 94_961_194_206_024_488_745_133_649_117_118_323_101),
+        ];
+        for (x, exact) in cases {
+            let value = exp_wad(x * WAD as i128).unwrap();
+            assert!(value.abs_diff(exact) <= exact / 10_u128.pow(16) + 1, "e^{x} = {value}, not {exact}");
+        }
+        // e^48 does not fit a u128 WAD.
+        assert!(exp_wad(48 * WAD as i128).is_err());
+    }
+
+    #[test]
+    fn ln_wad_matches_known_values() {
+        let cases: [(u128, i128); 7] = [
+            (1, -41_446_531_673_892_822_312),
+            (WAD / 2, -693_147_180_559_945_309),
+            (WAD, 0),
+            (2 * WAD, 693_147_180_559_945_309),
+            (3 * WAD, 1_098_612_288_668_109_691),
+            (10_u128.pow(30), 27_631_021_115_928_548_208),
+            (u128::MAX, 47_276_307_437_780_177_293),
+        ];
+        for (x, exact) in cases {
+            let value = ln_wad(x).unwrap();
+            assert!(value.abs_diff(exact) <= 50, "ln({x}) = {value}, not {exact}");
+        }
+        assert!(ln_wad(0).is_err());
+    }
+
+    /// Neither series rounds one way, which is why anything that needs a
+    /// direction goes through `with_margin`.
+    #[test]
+    fn the_series_err_on_either_side() {
+        assert!(exp_wad(WAD as i128).unwrap() < 2_718_281_828_459_045_235);
+        assert!(exp_wad(10 * WAD as i128).unwrap() > 22_026_465_794_806_716_516_957);
+        assert!(ln_wad(3 * WAD).unwrap() < 1_098_612_288_668_109_691);
+        assert!(ln_wad(1).unwrap() > -41_446_531_673_892_822_312);
+    }
+
+    #[test]
+    fn pow_wad_rounds_in_the_direction_asked() {
+        // (base, exponent, exact power truncated, with a remainder)
+        let cases = [
+            (2 * WAD, 10 * WAD, 1_024 * WAD, false),
+            (WAD / 2, 3 * WAD, WAD / 8, false),
+            (3 * WAD, WAD / 2, 1_732_050_807_568_877_293, true),
+            (WAD + 1, WAD, WAD + 1, false),
+            (1_050_000_000_000_000_000, 100 * WAD, 131_501_257_846_303_455_025, true),
+            (10 * WAD, 20 * WAD, 10_u128.pow(38), false),
+        ];
+        for (base, exponent, exact, inexact) in cases {
+            let up = pow_wad(base, exponent, Rounding::Up).unwrap();
+            let down = pow_wad(base, exponent, Rounding::Down).unwrap();
+            assert!(up >= exact + inexact as u128, "{base}^{exponent} rounded up to {up}");
+            assert!(down <= exact, "{base}^{exponent} rounded down to {down}");
+            assert!(up - down <= exact / 10_u128.pow(11) + 2, "{base}^{exponent}: {down}..{up}");
+        }
+
+        assert_eq!(pow_wad(0, 0, Rounding::Down).unwrap(), WAD);
+        assert_eq!(pow_wad(0, WAD, Rounding::Up).unwrap(), 0);
+        // 10^21 does not fit a u128 WAD.
+        assert!(pow_wad(10 * WAD, 21 * WAD, Rounding::Up).is_err());
+    }
+
+    #[test]
+    fn with_margin_widens_by_a_part_in_a_trillion() {
+        assert_eq!(with_margin(0, Rounding::Up), 1);
+        assert_eq!(with_margin(0, Rounding::Down), 0);
+        assert_eq!(with_margin(10_u128.pow(15), Rounding::Up), 10_u128.pow(15) + 1_001);
+        assert_eq!(with_margin(10_u128.pow(15), Rounding::Down), 10_u128.pow(15) - 1_001);
+    }
+}
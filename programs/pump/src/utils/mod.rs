@@ -1,2 +1,8 @@
 pub mod calc;
+pub mod curve;
+pub mod math;
+pub mod randomness;
+pub mod raydium;
 pub use calc::*;
+pub use curve::*;
+pub use randomness::*;
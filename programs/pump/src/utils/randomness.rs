@@ -0,0 +1,43 @@
+use anchor_lang::prelude::*;
+use crate::errors::CustomError;
+
+/// A source of verifiable randomness for lottery draws.
+pub trait RandomnessSource {
+    /// Returns the 32-byte randomness value once it was fulfilled after
+    /// `min_slot`, so it could not be known while the sale was open.
+    fn fulfilled_randomness(&self, min_slot: u64) -> Result<[u8; 32]>;
+}
+
+/// VRF-style account laid out as an 8-byte header, the 32-byte randomness
+/// value and the little-endian slot it was fulfilled at (0 while pending).
+pub struct VrfAccount<'a, 'info> {
+    account: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> VrfAccount<'a, 'info> {
+    pub const RANDOMNESS_OFFSET: usize = 8;
+    pub const FULFILLED_SLOT_OFFSET: usize = Self::RANDOMNESS_OFFSET + 32;
+    pub const LEN: usize = Self::FULFILLED_SLOT_OFFSET + 8;
+
+    pub fn load(account: &'a AccountInfo<'info>, expected: &Pubkey, owner: &Pubkey) -> Result<Self> {
+        require_keys_eq!(account.key(), *expected, CustomError::InvalidRandomnessAccount);
+        require_keys_eq!(*account.owner, *owner, CustomError::InvalidRandomnessAccount);
+        require!(account.data_len() >= Self::LEN, CustomError::InvalidRandomnessAccount);
+
+        Ok(Self { account })
+    }
+}
+
+impl RandomnessSource for VrfAccount<'_, '_> {
+    fn fulfilled_randomness(&self, min_slot: u64) -> Result<[u8; 32]> {
+        let data = self.account.try_borrow_data()?;
+        let fulfilled_slot = u64::from_le_bytes(
+            data[Self::FULFILLED_SLOT_OFFSET..Self::LEN].try_into().unwrap()
+        );
+        require!(fulfilled_slot > min_slot, CustomError::RandomnessNotFulfilled);
+
+        let mut randomness = [0_u8; 32];
+        randomness.copy_from_slice(&data[Self::RANDOMNESS_OFFSET..Self::FULFILLED_SLOT_OFFSET]);
+        Ok(randomness)
+    }
+}
@@ -0,0 +1,22 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::pubkey;
+
+/// Raydium AMM v4, the program `finalize_create_pool` opens the launch's pool on.
+pub const AMM_PROGRAM_ID: Pubkey = pubkey!("675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8");
+
+/// OpenBook, the market program Raydium pools are listed against.
+pub const MARKET_PROGRAM_ID: Pubkey = pubkey!("srmqPvymJeFKQ4zGQed1GFppgkRHL9kaELCbyksJtPX");
+
+/// Collects Raydium's pool creation fee.
+pub const CREATE_POOL_FEE_ID: Pubkey = pubkey!("7YttLkHDoNj9wyDur5pM1ejNaAvT9X4eqaYcHQqtj2G5");
+
+// Seeds the AMM derives its accounts from. The pool accounts are keyed by
+// `[AMM_PROGRAM_ID, market, seed]`, the authority and config by the seed alone.
+pub const AUTHORITY_SEED: &[u8] = b"amm authority";
+pub const AMM_CONFIG_SEED: &[u8] = b"amm_config_account_seed";
+pub const AMM_SEED: &[u8] = b"amm_associated_seed";
+pub const OPEN_ORDERS_SEED: &[u8] = b"open_order_associated_seed";
+pub const LP_MINT_SEED: &[u8] = b"lp_mint_associated_seed";
+pub const COIN_VAULT_SEED: &[u8] = b"coin_vault_associated_seed";
+pub const PC_VAULT_SEED: &[u8] = b"pc_vault_associated_seed";
+pub const TARGET_ORDERS_SEED: &[u8] = b"target_associated_seed";
@@ -0,0 +1,1784 @@
+//! End-to-end runs of a standard launch against the mock AMM in
+//! `programs/mock-amm`, asserting on the custody and pool balances.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_lang::solana_program::system_instruction;
+use anchor_lang::AccountSerialize;
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::token::spl_token;
+use mock_amm::PoolRecord;
+use pump::errors::CustomError;
+use pump::state::{
+    pause, BidCommitment, Config, CreatorProfile, FinalizeStep, LaunchMode, LaunchStatus, LiquidityPool, LotteryDraw, PenaltyRecipient,
+    ProtocolParams, TickerRecord, TokenDetails, UserContribution,
+};
+use pump::utils::{calculate_weighted_out, raydium, Curve, VrfAccount};
+use pump_client::instructions::{self, FinalizeAccounts, LaunchParams};
+use pump_client::pda;
+use solana_program_test::{processor, ProgramTest, ProgramTestContext};
+use solana_sdk::account::Account as SolanaAccount;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::Transaction;
+
+const SOL: u64 = 1_000_000_000;
+const SUPPLY: u64 = 1_000_000_000_000_000;
+const TARGET: u64 = 200 * SOL;
+const MAX_CONTRIBUTION: u64 = 10 * SOL;
+const LAUNCH_FEE: u64 = SOL / 10;
+const REFUND_DELAY: i64 = 7 * 24 * 60 * 60;
+const CANCEL_REBATE_BPS: u16 = 5_000;
+const MAX_EXTENSION: i64 = 7 * 24 * 60 * 60;
+const WITHDRAW_PENALTY_BPS: u16 = 1_000;
+const CRANK_REWARD: u64 = 5_000_000;
+const LP_TOKENS: u64 = 1_000_000_000;
+/// Owns the randomness accounts lottery launches draw from.
+const RANDOMNESS_PROGRAM: Pubkey = Pubkey::new_from_array([9; 32]);
+const TICKER: &str = "LNCH";
+/// Length of the bootstrapping pools' schedule: ten days, over which the
+/// token weight steps down one bps every 216 seconds.
+const LBP_DURATION: i64 = 10 * 24 * 60 * 60;
+/// Contribution numbers that fill the raise after the creator's opening
+/// position, contributions being capped per account.
+const FILLED: std::ops::Range<u32> = 1..(TARGET / MAX_CONTRIBUTION) as u32;
+
+fn pump_entry(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> entrypoint::ProgramResult {
+    // Anchor's entry wants the account slice and its infos to share one
+    // lifetime, which the test processor does not promise.
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    pump::entry(program_id, accounts, data)
+}
+
+struct Launch {
+    ctx: ProgramTestContext,
+    creator: Keypair,
+    contributor: Keypair,
+    admin: Keypair,
+    token: Pubkey,
+    mint: Pubkey,
+    fee_recipient: Pubkey,
+    mode: LaunchMode,
+}
+
+fn program_account<T: AccountSerialize>(account: &T) -> SolanaAccount {
+    let mut data = Vec::new();
+    account.try_serialize(&mut data).unwrap();
+    SolanaAccount {
+        lamports: SOL,
+        data,
+        owner: pump::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn wallet(lamports: u64) -> SolanaAccount {
+    SolanaAccount::new(lamports, 0, &anchor_lang::system_program::ID)
+}
+
+/// Starts a validator and opens a standard launch with `initial` lamports
+/// from the creator.
+async fn launch(initial: u64) -> Launch {
+    bonded_launch(initial, 0).await
+}
+
+/// Like `launch`, with the creator posting `bond` lamports.
+async fn bonded_launch(initial: u64, bond: u64) -> Launch {
+    open_launch(initial, bond, LaunchMode::Standard).await
+}
+
+/// Like `launch`, in the given mode.
+async fn launch_in(mode: LaunchMode, initial: u64) -> Launch {
+    open_launch(initial, 0, mode).await
+}
+
+async fn open_launch(initial: u64, bond: u64, mode: LaunchMode) -> Launch {
+    let mut test = ProgramTest::new("pump", pump::ID, processor!(pump_entry));
+    // The program only talks to the AMM at Raydium's address.
+    test.add_program("mock_amm", raydium::AMM_PROGRAM_ID, processor!(mock_amm::process_instruction));
+
+    let creator = Keypair::new();
+    let contributor = Keypair::new();
+    test.add_account(creator.pubkey(), wallet(1_000 * SOL));
+    test.add_account(contributor.pubkey(), wallet(1_000 * SOL));
+
+    // The protocol config, which the tests do not create through
+    // `initialize_config`.
+    let ticker = TICKER;
+    let token = pda::token(&creator.pubkey(), ticker).0;
+    let fee_recipient = Pubkey::new_unique();
+    let admin = Keypair::new();
+    test.add_account(pda::config().0, program_account(&Config {
+        admin: admin.pubkey(),
+        pending_admin: Pubkey::default(),
+        fee_admin: admin.pubkey(),
+        pause_guardian: admin.pubkey(),
+        params: ProtocolParams {
+            fee_recipient,
+            max_deadline_extension: MAX_EXTENSION,
+            cancel_rebate_bps: CANCEL_REBATE_BPS,
+            withdraw_penalty_bps: WITHDRAW_PENALTY_BPS,
+            randomness_program: RANDOMNESS_PROGRAM,
+            ..ProtocolParams::default()
+        },
+        pending_change: None,
+        paused: false,
+        paused_instructions: 0,
+        reveal_paused_at: 0,
+        reveal_paused_for: 0,
+        bump: pda::config().1,
+    }));
+
+    let mut ctx = test.start_with_context().await;
+    let ix = instructions::initialize(&creator.pubkey(), LaunchParams {
+        name: "Launch".to_string(),
+        ticker: ticker.to_string(),
+        total_supply: SUPPLY,
+        initial_target: initial,
+        decimals: 6,
+        mode,
+        curve: Curve::Linear { base_price: 1_000, slope: 0 },
+        bond,
+    });
+    send(&mut ctx, &[ix], &[&creator]).await.unwrap();
+
+    Launch {
+        token,
+        mint: pda::mint(&creator.pubkey(), ticker).0,
+        fee_recipient,
+        mode,
+        ctx,
+        creator,
+        contributor,
+        admin,
+    }
+}
+
+async fn send(ctx: &mut ProgramTestContext, ixs: &[Instruction], signers: &[&Keypair]) -> std::result::Result<(), String> {
+    let blockhash = ctx.get_new_latest_blockhash().await.unwrap();
+    let mut all_signers = vec![&ctx.payer];
+    all_signers.extend_from_slice(signers);
+    let tx = Transaction::new_signed_with_payer(ixs, Some(&ctx.payer.pubkey()), &all_signers, blockhash);
+    ctx.banks_client.process_transaction(tx).await.map_err(|error| error.to_string())
+}
+
+async fn lamports(ctx: &mut ProgramTestContext, address: &Pubkey) -> u64 {
+    ctx.banks_client.get_balance(*address).await.unwrap()
+}
+
+async fn account<T: AccountDeserialize>(ctx: &mut ProgramTestContext, address: &Pubkey) -> T {
+    let account = ctx.banks_client.get_account(*address).await.unwrap().unwrap();
+    T::try_deserialize(&mut &account.data[..]).unwrap()
+}
+
+async fn token_balance(ctx: &mut ProgramTestContext, address: &Pubkey) -> u64 {
+    let account = ctx.banks_client.get_account(*address).await.unwrap().unwrap();
+    spl_token::state::Account::unpack(&account.data).unwrap().amount
+}
+
+/// Creates a token account for `mint` owned by `owner`.
+async fn create_token_account(ctx: &mut ProgramTestContext, mint: &Pubkey, owner: &Pubkey) -> Pubkey {
+    let account = Keypair::new();
+    let rent = ctx.banks_client.get_rent().await.unwrap();
+    let ixs = [
+        system_instruction::create_account(
+            &ctx.payer.pubkey(),
+            &account.pubkey(),
+            rent.minimum_balance(spl_token::state::Account::LEN),
+            spl_token::state::Account::LEN as u64,
+            &spl_token::ID,
+        ),
+        spl_token::instruction::initialize_account3(&spl_token::ID, &account.pubkey(), mint, owner).unwrap(),
+    ];
+    send(ctx, &ixs, &[&account]).await.unwrap();
+    account.pubkey()
+}
+
+/// Writes an initialized token account for `mint` owned by `owner` at
+/// `address`, for accounts the AMM would create at its own PDAs.
+fn set_token_account(ctx: &mut ProgramTestContext, address: &Pubkey, mint: &Pubkey, owner: &Pubkey) {
+    let mut data = vec![0; spl_token::state::Account::LEN];
+    spl_token::state::Account {
+        mint: *mint,
+        owner: *owner,
+        state: spl_token::state::AccountState::Initialized,
+        ..Default::default()
+    }
+    .pack_into_slice(&mut data);
+    ctx.set_account(address, &SolanaAccount { lamports: SOL, data, owner: spl_token::ID, executable: false, rent_epoch: 0 }.into());
+}
+
+/// Lists a market for the mock AMM and sets up the pool accounts Raydium
+/// would have created for it: the pool record, the token and wSOL vaults and
+/// the LP mint, with the LP tokens already minted to the custody account.
+/// The opening liquidity comes out of the custody account's token and wSOL
+/// accounts.
+fn open_market(ctx: &mut ProgramTestContext, mint: &Pubkey) -> Pubkey {
+    let market = Pubkey::new_unique();
+    ctx.set_account(&market, &SolanaAccount::new(SOL, 0, &raydium::MARKET_PROGRAM_ID).into());
+    let amm = pda::raydium_pool(&market, raydium::AMM_SEED);
+    ctx.set_account(&amm, &SolanaAccount::new(SOL, PoolRecord::LEN, &raydium::AMM_PROGRAM_ID).into());
+    let authority = pda::raydium(raydium::AUTHORITY_SEED).0;
+    let coin_vault = pda::raydium_pool(&market, raydium::COIN_VAULT_SEED);
+    set_token_account(ctx, &coin_vault, mint, &authority);
+    let pc_vault = pda::raydium_pool(&market, raydium::PC_VAULT_SEED);
+    set_token_account(ctx, &pc_vault, &spl_token::native_mint::ID, &authority);
+
+    let lp_mint = pda::raydium_pool(&market, raydium::LP_MINT_SEED);
+    let mut data = vec![0; spl_token::state::Mint::LEN];
+    spl_token::state::Mint {
+        mint_authority: COption::Some(authority),
+        supply: LP_TOKENS,
+        decimals: 9,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    }
+    .pack_into_slice(&mut data);
+    ctx.set_account(&lp_mint, &SolanaAccount { lamports: SOL, data, owner: spl_token::ID, executable: false, rent_epoch: 0 }.into());
+    let global = pda::global().0;
+    let mut data = vec![0; spl_token::state::Account::LEN];
+    spl_token::state::Account {
+        mint: lp_mint,
+        owner: global,
+        amount: LP_TOKENS,
+        state: spl_token::state::AccountState::Initialized,
+        ..Default::default()
+    }
+    .pack_into_slice(&mut data);
+    let lp_tokens = get_associated_token_address(&global, &lp_mint);
+    ctx.set_account(&lp_tokens, &SolanaAccount { lamports: SOL, data, owner: spl_token::ID, executable: false, rent_epoch: 0 }.into());
+    market
+}
+
+/// Positions paid per `finalize_settle`, few enough to fit a transaction.
+const SETTLE_BATCH: usize = 8;
+
+/// The finalize steps in order, paying `positions` over as many settle
+/// batches as it takes. The last three are always the pool, lock and
+/// complete steps.
+fn finalize(launch: &Launch, market: Pubkey, positions: Vec<(Pubkey, Pubkey)>) -> Vec<Instruction> {
+    let mut accounts = FinalizeAccounts {
+        token: launch.token,
+        creator: launch.creator.pubkey(),
+        ticker: TICKER.to_string(),
+        cranker: launch.ctx.payer.pubkey(),
+        mint: launch.mint,
+        fee_recipient: launch.fee_recipient,
+        market,
+        positions: vec![],
+        mode: launch.mode,
+    };
+    let mut steps: Vec<Instruction> = positions.chunks(SETTLE_BATCH)
+        .map(|batch| {
+            accounts.positions = batch.to_vec();
+            instructions::finalize_settle(&accounts)
+        })
+        .collect();
+    if steps.is_empty() {
+        steps.push(instructions::finalize_settle(&accounts));
+    }
+    steps.extend(instructions::finalize(&accounts).into_iter().skip(1));
+    steps
+}
+
+/// The creator's opening position and the contributor's positions from
+/// `filled`, each with the token account its tokens go to.
+fn positions(launch: &Launch, filled: std::ops::Range<u32>, creator_tokens: Pubkey, user_tokens: Pubkey) -> Vec<(Pubkey, Pubkey)> {
+    let user = launch.contributor.pubkey();
+    let mut positions = vec![(pda::creator_contribution(&launch.creator.pubkey(), TICKER).0, creator_tokens)];
+    positions.extend(filled.map(|number| (pda::user_contribution(&user, &launch.token, number).0, user_tokens)));
+    positions
+}
+
+/// Sends each instruction in a transaction of its own, as a keeper would.
+async fn send_each(ctx: &mut ProgramTestContext, ixs: &[Instruction]) -> std::result::Result<(), String> {
+    for ix in ixs {
+        send(ctx, &[ix.clone()], &[]).await?;
+    }
+    Ok(())
+}
+
+/// A lottery selling one-SOL tickets, four of them winning.
+fn lottery_mode() -> LaunchMode {
+    LaunchMode::Lottery {
+        ticket_price: SOL,
+        winning_tickets: 4,
+        sale_duration: 60,
+        randomness_account: Pubkey::new_from_array([3; 32]),
+    }
+}
+
+/// Writes the lottery's randomness account, fulfilled at `slot` with
+/// `randomness`.
+fn fulfil_randomness(ctx: &mut ProgramTestContext, mode: &LaunchMode, randomness: [u8; 32], slot: u64) {
+    let LaunchMode::Lottery { randomness_account, .. } = mode else {
+        panic!("not a lottery");
+    };
+    let mut data = vec![0; VrfAccount::LEN];
+    data[VrfAccount::RANDOMNESS_OFFSET..VrfAccount::FULFILLED_SLOT_OFFSET].copy_from_slice(&randomness);
+    data[VrfAccount::FULFILLED_SLOT_OFFSET..].copy_from_slice(&slot.to_le_bytes());
+    ctx.set_account(randomness_account, &SolanaAccount { lamports: SOL, data, owner: RANDOMNESS_PROGRAM, executable: false, rent_epoch: 0 }.into());
+}
+
+/// A bootstrapping pool whose token weight decays from 90% to 50%.
+fn lbp_mode() -> LaunchMode {
+    LaunchMode::Lbp { start_weight_bps: 9_000, end_weight_bps: 5_000, duration: LBP_DURATION }
+}
+
+async fn warp_past_refund_delay(ctx: &mut ProgramTestContext) {
+    warp(ctx, REFUND_DELAY + 1).await;
+}
+
+async fn warp(ctx: &mut ProgramTestContext, seconds: i64) {
+    let mut clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp += seconds;
+    ctx.set_sysvar(&clock);
+}
+
+async fn warp_to(ctx: &mut ProgramTestContext, unix_timestamp: i64) {
+    let mut clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp = unix_timestamp;
+    ctx.set_sysvar(&clock);
+}
+
+#[tokio::test]
+async fn initialize_and_contribute_fund_the_custody_account() {
+    let mut launch = launch(10 * SOL).await;
+    let global = pda::global().0;
+    let ctx = &mut launch.ctx;
+
+    assert_eq!(lamports(ctx, &global).await, LAUNCH_FEE + 10 * SOL);
+    assert_eq!(token_balance(ctx, &pda::pool_token_account(&launch.mint)).await, SUPPLY);
+
+    let details: TokenDetails = account(ctx, &launch.token).await;
+    assert_eq!((details.total_contributed, details.contribution_count), (10 * SOL, 1));
+    assert!(details.is_virtual);
+
+    let user = launch.contributor.pubkey();
+    let before = lamports(ctx, &user).await;
+    let ix = instructions::contribute(&user, &launch.token, 1, 5 * SOL);
+    send(ctx, &[ix], &[&launch.contributor]).await.unwrap();
+
+    let contribution: UserContribution = account(ctx, &pda::user_contribution(&user, &launch.token, 1).0).await;
+    assert_eq!((contribution.user, contribution.amount), (user, 5 * SOL));
+    assert_eq!(lamports(ctx, &global).await, LAUNCH_FEE + 15 * SOL);
+    // The contributor also paid rent for the contribution account.
+    assert!(before - lamports(ctx, &user).await > 5 * SOL);
+
+    let details: TokenDetails = account(ctx, &launch.token).await;
+    assert_eq!((details.total_contributed, details.contribution_count), (15 * SOL, 2));
+}
+
+#[tokio::test]
+async fn refund_returns_contributions_once_the_deadline_passes() {
+    let mut launch = launch(10 * SOL).await;
+    let global = pda::global().0;
+    let user = launch.contributor.pubkey();
+    let ctx = &mut launch.ctx;
+
+    let ix = instructions::contribute(&user, &launch.token, 1, 5 * SOL);
+    send(ctx, &[ix], &[&launch.contributor]).await.unwrap();
+    let contribution = pda::user_contribution(&user, &launch.token, 1).0;
+    let funded = lamports(ctx, &user).await;
+
+    let rent = lamports(ctx, &contribution).await;
+
+    // Too early: the refund fails and the funds stay in custody.
+    let refund = instructions::refund(&user, &launch.token, &launch.creator.pubkey(), TICKER, &contribution);
+    assert!(send(ctx, std::slice::from_ref(&refund), &[&launch.contributor]).await.is_err());
+    assert_eq!(lamports(ctx, &user).await, funded);
+    assert_eq!(lamports(ctx, &global).await, LAUNCH_FEE + 15 * SOL);
+
+    warp_past_refund_delay(ctx).await;
+    send(ctx, std::slice::from_ref(&refund), &[&launch.contributor]).await.unwrap();
+
+    // The position is closed and its rent returned along with the refund.
+    assert_eq!(lamports(ctx, &user).await, funded + 5 * SOL + rent);
+    assert_eq!(lamports(ctx, &global).await, LAUNCH_FEE + 10 * SOL);
+    let details: TokenDetails = account(ctx, &launch.token).await;
+    assert_eq!(details.total_contributed, 10 * SOL);
+    assert!(ctx.banks_client.get_account(contribution).await.unwrap().is_none());
+
+    // That first refund marks the launch failed on the creator's record.
+    assert_eq!(details.status, LaunchStatus::Failed);
+    let profile: CreatorProfile = account(ctx, &pda::creator_profile(&launch.creator.pubkey()).0).await;
+    assert_eq!((profile.launches, profile.failures, profile.graduations), (1, 1, 0));
+    // It also frees the ticker for another launch.
+    assert!(ctx.banks_client.get_account(pda::ticker(TICKER).0).await.unwrap().is_none());
+
+    // A closed position cannot be refunded again.
+    assert!(send(ctx, &[refund], &[&launch.contributor]).await.is_err());
+    assert_eq!(lamports(ctx, &global).await, LAUNCH_FEE + 10 * SOL);
+}
+
+#[tokio::test]
+async fn refund_only_pays_the_owner_of_the_position() {
+    let mut launch = launch(10 * SOL).await;
+    let global = pda::global().0;
+    let user = launch.contributor.pubkey();
+    let creator = launch.creator.pubkey();
+    let ctx = &mut launch.ctx;
+
+    let ix = instructions::contribute(&user, &launch.token, 1, 5 * SOL);
+    send(ctx, &[ix], &[&launch.contributor]).await.unwrap();
+    let contribution = pda::user_contribution(&user, &launch.token, 1).0;
+    warp_past_refund_delay(ctx).await;
+
+    // The creator signs for the contributor's position.
+    let ix = instructions::refund(&creator, &launch.token, &creator, TICKER, &contribution);
+    assert!(send(ctx, &[ix], &[&launch.creator]).await.is_err());
+    assert_eq!(lamports(ctx, &global).await, LAUNCH_FEE + 15 * SOL);
+
+    // The creator's own opening position is still theirs to refund.
+    let opening = pda::creator_contribution(&creator, TICKER).0;
+    let ix = instructions::refund(&creator, &launch.token, &creator, TICKER, &opening);
+    send(ctx, &[ix], &[&launch.creator]).await.unwrap();
+    assert_eq!(lamports(ctx, &global).await, LAUNCH_FEE + 5 * SOL);
+    let position: UserContribution = account(ctx, &contribution).await;
+    assert_eq!(position.amount, 5 * SOL);
+}
+
+#[tokio::test]
+async fn finalize_pays_the_fee_and_seeds_the_pool() {
+    let mut launch = launch(MAX_CONTRIBUTION).await;
+    let global = pda::global().0;
+    let user = launch.contributor.pubkey();
+    let ctx = &mut launch.ctx;
+
+    // Contributions are capped per account, so fill the raise 10 SOL at a time.
+    for number in FILLED {
+        let ix = instructions::contribute(&user, &launch.token, number, MAX_CONTRIBUTION);
+        send(ctx, &[ix], &[&launch.contributor]).await.unwrap();
+    }
+
+    let market = open_market(ctx, &launch.mint);
+    let creator_tokens = create_token_account(ctx, &launch.mint, &launch.creator.pubkey()).await;
+    let user_tokens = create_token_account(ctx, &launch.mint, &user).await;
+    // Any keeper can send the steps and is paid for each of them. Opening the
+    // first pool has it front the rent of the custody account's wSOL account.
+    let cranker = Keypair::new();
+    let mut steps = finalize(&launch, market, positions(&launch, FILLED, creator_tokens, user_tokens));
+    for meta in steps.iter_mut().flat_map(|step| step.accounts.iter_mut()) {
+        if meta.pubkey == launch.ctx.payer.pubkey() {
+            meta.pubkey = cranker.pubkey();
+        }
+    }
+    let ctx = &mut launch.ctx;
+    let rent = ctx.banks_client.get_rent().await.unwrap().minimum_balance(spl_token::state::Account::LEN);
+    let fund = system_instruction::transfer(&ctx.payer.pubkey(), &cranker.pubkey(), rent);
+    send(ctx, &[fund], &[]).await.unwrap();
+    let create_pool = steps.len() - 3;
+    for (index, step) in steps.into_iter().enumerate() {
+        let before = lamports(ctx, &cranker.pubkey()).await;
+        send(ctx, &[step], &[&cranker]).await.unwrap();
+        let fronted = if index == create_pool { rent } else { 0 };
+        assert!(lamports(ctx, &cranker.pubkey()).await + fronted > before);
+    }
+
+    let fee = TARGET / 20;
+    let pc_vault = pda::raydium_pool(&market, raydium::PC_VAULT_SEED);
+    assert_eq!(lamports(ctx, &launch.fee_recipient).await, fee);
+    assert_eq!(lamports(ctx, &cranker.pubkey()).await, CRANK_REWARD);
+    assert_eq!(token_balance(ctx, &pc_vault).await, TARGET - fee - CRANK_REWARD);
+    // Only the launch fee is left in custody, and all the wrapped SOL went
+    // into the pool.
+    assert_eq!(lamports(ctx, &global).await, LAUNCH_FEE);
+    let wsol = get_associated_token_address(&global, &spl_token::native_mint::ID);
+    assert_eq!(token_balance(ctx, &wsol).await, 0);
+
+    // Each position is paid what it bought along the curve and the rest of
+    // the supply is pooled.
+    let details: TokenDetails = account(ctx, &launch.token).await;
+    let opening: UserContribution = account(ctx, &pda::creator_contribution(&launch.creator.pubkey(), TICKER).0).await;
+    let pooled = SUPPLY - details.tokens_sold;
+    assert!(opening.settled);
+    assert_eq!(token_balance(ctx, &creator_tokens).await, opening.total_tokens);
+    assert_eq!(token_balance(ctx, &user_tokens).await, details.tokens_sold - opening.total_tokens);
+    assert_eq!(token_balance(ctx, &pda::raydium_pool(&market, raydium::COIN_VAULT_SEED)).await, pooled);
+    assert_eq!(token_balance(ctx, &pda::pool_token_account(&launch.mint)).await, 0);
+
+    let pool = ctx.banks_client.get_account(pda::raydium_pool(&market, raydium::AMM_SEED)).await.unwrap().unwrap();
+    let record = PoolRecord::unpack(&pool.data).unwrap();
+    let lp_mint = pda::raydium_pool(&market, raydium::LP_MINT_SEED);
+    assert_eq!(record.init_pc_amount, TARGET - fee - CRANK_REWARD);
+    assert_eq!(record.init_coin_amount, pooled);
+    assert_eq!(record.nonce, pda::global().1);
+    assert_eq!((record.coin_mint, record.pc_mint, record.lp_mint), (launch.mint, spl_token::native_mint::ID, lp_mint));
+    assert_eq!(record.user_wallet, global);
+    // The LP tokens are burned, so the liquidity can never be pulled.
+    assert_eq!(token_balance(ctx, &get_associated_token_address(&global, &lp_mint)).await, 0);
+
+    let details: TokenDetails = account(ctx, &launch.token).await;
+    assert!(!details.is_virtual);
+    assert_eq!((details.finalize_step, details.market), (FinalizeStep::Complete, market));
+    let profile: CreatorProfile = account(ctx, &pda::creator_profile(&launch.creator.pubkey()).0).await;
+    assert_eq!((profile.graduations, profile.total_raised), (1, TARGET));
+    assert_eq!(details.creator, launch.creator.pubkey());
+}
+
+#[tokio::test]
+async fn finalize_rejects_substituted_accounts() {
+    let mut launch = launch(MAX_CONTRIBUTION).await;
+    let global = pda::global().0;
+    let user = launch.contributor.pubkey();
+    let ctx = &mut launch.ctx;
+
+    for number in FILLED {
+        let ix = instructions::contribute(&user, &launch.token, number, MAX_CONTRIBUTION);
+        send(ctx, &[ix], &[&launch.contributor]).await.unwrap();
+    }
+    let market = open_market(ctx, &launch.mint);
+    let creator_tokens = create_token_account(ctx, &launch.mint, &launch.creator.pubkey()).await;
+    let user_tokens = create_token_account(ctx, &launch.mint, &user).await;
+    let thief = Keypair::new();
+    let thief_tokens = create_token_account(ctx, &launch.mint, &thief.pubkey()).await;
+
+    let opening = pda::creator_contribution(&launch.creator.pubkey(), TICKER).0;
+    let mut attempts = Vec::new();
+    // The fee goes to the configured recipient only.
+    let mut ix = finalize(&launch, market, positions(&launch, FILLED, creator_tokens, user_tokens)).remove(0);
+    let fee_index = ix.accounts.iter().position(|meta| meta.pubkey == launch.fee_recipient).unwrap();
+    ix.accounts[fee_index].pubkey = thief.pubkey();
+    attempts.push(ix);
+    // Shares go to each contributor's own account.
+    attempts.push(finalize(&launch, market, positions(&launch, FILLED, creator_tokens, thief_tokens)).remove(0));
+    attempts.push(finalize(&launch, market, positions(&launch, FILLED, user_tokens, creator_tokens)).remove(0));
+    // A position is paid once, even when passed twice.
+    attempts.push(finalize(&launch, market, vec![(opening, creator_tokens), (opening, creator_tokens)]).remove(0));
+    // And only positions of this launch count.
+    attempts.push(finalize(&launch, market, vec![(launch.token, creator_tokens)]).remove(0));
+
+    let ctx = &mut launch.ctx;
+    for ix in attempts {
+        assert!(send(ctx, &[ix], &[]).await.is_err());
+    }
+    assert_eq!(lamports(ctx, &global).await, LAUNCH_FEE + TARGET);
+    assert_eq!(token_balance(ctx, &pda::pool_token_account(&launch.mint)).await, SUPPLY);
+    assert_eq!(token_balance(ctx, &thief_tokens).await, 0);
+
+    let steps = finalize(&launch, market, positions(&launch, FILLED, creator_tokens, user_tokens));
+    let (settle, steps) = steps.split_at(steps.len() - 3);
+    let ctx = &mut launch.ctx;
+    send_each(ctx, settle).await.unwrap();
+
+    let mut attempts = Vec::new();
+    // The LP tokens stay with the custody account.
+    let lp_mint = pda::raydium_pool(&market, raydium::LP_MINT_SEED);
+    let mut ix = steps[0].clone();
+    let lp_index = ix.accounts.iter()
+        .position(|meta| meta.pubkey == get_associated_token_address(&global, &lp_mint))
+        .unwrap();
+    ix.accounts[lp_index].pubkey = get_associated_token_address(&thief.pubkey(), &lp_mint);
+    attempts.push(ix);
+    // And the pool is opened on Raydium, not a look-alike program.
+    let mut ix = steps[0].clone();
+    let amm_index = ix.accounts.iter().position(|meta| meta.pubkey == raydium::AMM_PROGRAM_ID).unwrap();
+    ix.accounts[amm_index].pubkey = mock_amm::ID;
+    attempts.push(ix);
+
+    for ix in attempts {
+        assert!(send(ctx, &[ix], &[]).await.is_err());
+    }
+    let details: TokenDetails = account(ctx, &launch.token).await;
+    assert_eq!(details.finalize_step, FinalizeStep::Settled);
+    assert_eq!(token_balance(ctx, &pda::raydium_pool(&market, raydium::PC_VAULT_SEED)).await, 0);
+}
+
+#[tokio::test]
+async fn finalize_below_target_leaves_the_launch_virtual() {
+    let mut launch = launch(10 * SOL).await;
+    let global = pda::global().0;
+    let ctx = &mut launch.ctx;
+
+    let market = open_market(ctx, &launch.mint);
+    let creator_tokens = create_token_account(ctx, &launch.mint, &launch.creator.pubkey()).await;
+    let opening = pda::creator_contribution(&launch.creator.pubkey(), TICKER).0;
+    let steps = finalize(&launch, market, vec![(opening, creator_tokens)]);
+    let ctx = &mut launch.ctx;
+    send(ctx, &[steps[0].clone()], &[]).await.unwrap();
+    // Settling did nothing, so there is no pool to open.
+    assert!(send(ctx, &[steps[1].clone()], &[]).await.is_err());
+
+    assert_eq!(lamports(ctx, &global).await, LAUNCH_FEE + 10 * SOL);
+    assert_eq!(token_balance(ctx, &pda::pool_token_account(&launch.mint)).await, SUPPLY);
+    let details: TokenDetails = account(ctx, &launch.token).await;
+    assert!(details.is_virtual);
+}
+
+#[tokio::test]
+async fn finalize_steps_run_in_order_and_resume_where_they_stopped() {
+    let mut launch = launch(MAX_CONTRIBUTION).await;
+    let global = pda::global().0;
+    let creator = launch.creator.pubkey();
+    let user = launch.contributor.pubkey();
+    let admin = launch.admin.pubkey();
+    let ctx = &mut launch.ctx;
+
+    for number in FILLED {
+        let ix = instructions::contribute(&user, &launch.token, number, MAX_CONTRIBUTION);
+        send(ctx, &[ix], &[&launch.contributor]).await.unwrap();
+    }
+    let market = open_market(ctx, &launch.mint);
+    let creator_tokens = create_token_account(ctx, &launch.mint, &creator).await;
+    let user_tokens = create_token_account(ctx, &launch.mint, &user).await;
+
+    // No step can run ahead of settlement.
+    let mut all = positions(&launch, FILLED, creator_tokens, user_tokens);
+    let rest = all.split_off(1);
+    let first_batch = finalize(&launch, market, all);
+    let ctx = &mut launch.ctx;
+    assert!(send(ctx, &[first_batch[1].clone()], &[]).await.is_err());
+    assert!(send(ctx, &[first_batch[3].clone()], &[]).await.is_err());
+
+    // The first batch pays the creator and leaves the rest for later.
+    send(ctx, &[first_batch[0].clone()], &[]).await.unwrap();
+    let opening: UserContribution = account(ctx, &pda::creator_contribution(&creator, TICKER).0).await;
+    assert!(opening.settled);
+    assert_eq!(token_balance(ctx, &creator_tokens).await, opening.total_tokens);
+    let details: TokenDetails = account(ctx, &launch.token).await;
+    assert_eq!((details.finalize_step, details.contributions_settled), (FinalizeStep::Settling, 1));
+    assert_eq!(lamports(ctx, &launch.fee_recipient).await, 0);
+    assert!(send(ctx, &[first_batch[1].clone()], &[]).await.is_err());
+    // A settled position is not paid again.
+    assert!(send(ctx, &[first_batch[0].clone()], &[]).await.is_err());
+    assert_eq!(token_balance(ctx, &creator_tokens).await, opening.total_tokens);
+
+    // Nothing else can happen to a launch while it is being finalized.
+    let ix = instructions::cancel_launch(&admin, &launch.token, &creator, &launch.mint, TICKER, false);
+    assert!(send(ctx, &[ix], &[&launch.admin]).await.is_err());
+
+    let steps = finalize(&launch, market, rest);
+    let ctx = &mut launch.ctx;
+    send_each(ctx, &steps).await.unwrap();
+    let fee = TARGET / 20;
+    let user_share = details.tokens_sold - opening.total_tokens;
+    assert_eq!(token_balance(ctx, &user_tokens).await, user_share);
+    assert_eq!(lamports(ctx, &launch.fee_recipient).await, fee);
+    assert_eq!(lamports(ctx, &global).await, LAUNCH_FEE);
+
+    // Steps that already ran are skipped when sent again.
+    send_each(ctx, &steps).await.unwrap();
+    send_each(ctx, &first_batch).await.unwrap();
+    assert_eq!(lamports(ctx, &launch.fee_recipient).await, fee);
+    assert_eq!(token_balance(ctx, &user_tokens).await, user_share);
+    let details: TokenDetails = account(ctx, &launch.token).await;
+    assert_eq!((details.finalize_step, details.contributions_settled), (FinalizeStep::Complete, FILLED.end));
+    let profile: CreatorProfile = account(ctx, &pda::creator_profile(&creator).0).await;
+    assert_eq!(profile.graduations, 1);
+}
+
+#[tokio::test]
+async fn finalize_pools_what_curve_buyers_left_behind() {
+    let mut launch = launch(MAX_CONTRIBUTION).await;
+    let user = launch.contributor.pubkey();
+    let buyer = Keypair::new();
+    let ctx = &mut launch.ctx;
+    ctx.set_account(&buyer.pubkey(), &wallet(100 * SOL).into());
+
+    // The last 10 SOL of the raise is a curve buy rather than a position.
+    let filled = FILLED.start..FILLED.end - 1;
+    for number in filled.clone() {
+        let ix = instructions::contribute(&user, &launch.token, number, MAX_CONTRIBUTION);
+        send(ctx, &[ix], &[&launch.contributor]).await.unwrap();
+    }
+    let ix = instructions::buy(&buyer.pubkey(), &launch.token, &launch.mint, &LaunchMode::Standard, MAX_CONTRIBUTION, 0);
+    send(ctx, &[ix], &[&buyer]).await.unwrap();
+    let buyer_tokens = get_associated_token_address(&buyer.pubkey(), &launch.mint);
+    let bought = token_balance(ctx, &buyer_tokens).await;
+    assert!(bought > 0);
+
+    let market = open_market(ctx, &launch.mint);
+    let creator_tokens = create_token_account(ctx, &launch.mint, &launch.creator.pubkey()).await;
+    let user_tokens = create_token_account(ctx, &launch.mint, &user).await;
+    let steps = finalize(&launch, market, positions(&launch, filled, creator_tokens, user_tokens));
+    let ctx = &mut launch.ctx;
+    send_each(ctx, &steps).await.unwrap();
+
+    // The buyer keeps what they bought, so the positions and the pool
+    // split the rest of the supply between them.
+    let details: TokenDetails = account(ctx, &launch.token).await;
+    assert_eq!(details.finalize_step, FinalizeStep::Complete);
+    let paid = token_balance(ctx, &creator_tokens).await + token_balance(ctx, &user_tokens).await;
+    assert_eq!(paid + bought, details.tokens_sold);
+    let pooled = token_balance(ctx, &pda::raydium_pool(&market, raydium::COIN_VAULT_SEED)).await;
+    assert_eq!(pooled, SUPPLY - details.tokens_sold);
+    assert_eq!(token_balance(ctx, &buyer_tokens).await, bought);
+    assert_eq!(token_balance(ctx, &pda::pool_token_account(&launch.mint)).await, 0);
+}
+
+#[tokio::test]
+async fn creator_cancels_an_untouched_launch_and_refunds_at_once() {
+    let mut launch = launch(10 * SOL).await;
+    let global = pda::global().0;
+    let creator = launch.creator.pubkey();
+    let ctx = &mut launch.ctx;
+
+    let before = lamports(ctx, &creator).await;
+    let ix = instructions::cancel_launch(&creator, &launch.token, &creator, &launch.mint, TICKER, true);
+    send(ctx, &[ix], &[&launch.creator]).await.unwrap();
+
+    // Half the launch fee comes back and the unsold supply is burned.
+    let rebate = LAUNCH_FEE * CANCEL_REBATE_BPS as u64 / 10_000;
+    assert_eq!(lamports(ctx, &creator).await, before + rebate);
+    assert_eq!(token_balance(ctx, &pda::pool_token_account(&launch.mint)).await, 0);
+
+    // The opening position refunds without waiting out the deadline.
+    let opening = pda::creator_contribution(&creator, TICKER).0;
+    let ix = instructions::refund(&creator, &launch.token, &creator, TICKER, &opening);
+    send(ctx, &[ix], &[&launch.creator]).await.unwrap();
+    assert_eq!(lamports(ctx, &global).await, LAUNCH_FEE - rebate);
+    let profile: CreatorProfile = account(ctx, &pda::creator_profile(&creator).0).await;
+    assert_eq!((profile.cancellations, profile.failures), (1, 0));
+
+    // Nothing else can happen to a cancelled launch.
+    let user = launch.contributor.pubkey();
+    let ix = instructions::contribute(&user, &launch.token, 1, SOL);
+    assert!(send(ctx, &[ix], &[&launch.contributor]).await.is_err());
+    let ix = instructions::cancel_launch(&creator, &launch.token, &creator, &launch.mint, TICKER, false);
+    assert!(send(ctx, &[ix], &[&launch.creator]).await.is_err());
+}
+
+#[tokio::test]
+async fn only_the_admin_cancels_a_launch_others_have_joined() {
+    let mut launch = launch(10 * SOL).await;
+    let global = pda::global().0;
+    let creator = launch.creator.pubkey();
+    let user = launch.contributor.pubkey();
+    let admin = launch.admin.pubkey();
+    let ctx = &mut launch.ctx;
+
+    let ix = instructions::contribute(&user, &launch.token, 1, 5 * SOL);
+    send(ctx, &[ix], &[&launch.contributor]).await.unwrap();
+
+    let ix = instructions::cancel_launch(&creator, &launch.token, &creator, &launch.mint, TICKER, false);
+    assert!(send(ctx, &[ix], &[&launch.creator]).await.is_err());
+    let ix = instructions::cancel_launch(&user, &launch.token, &creator, &launch.mint, TICKER, false);
+    assert!(send(ctx, &[ix], &[&launch.contributor]).await.is_err());
+
+    // The admin cancels without a rebate and the supply stays in custody.
+    let before = lamports(ctx, &creator).await;
+    let ix = instructions::cancel_launch(&admin, &launch.token, &creator, &launch.mint, TICKER, false);
+    send(ctx, &[ix], &[&launch.admin]).await.unwrap();
+    assert_eq!(lamports(ctx, &creator).await, before);
+    assert_eq!(token_balance(ctx, &pda::pool_token_account(&launch.mint)).await, SUPPLY);
+
+    let contribution = pda::user_contribution(&user, &launch.token, 1).0;
+    let ix = instructions::refund(&user, &launch.token, &creator, TICKER, &contribution);
+    send(ctx, &[ix], &[&launch.contributor]).await.unwrap();
+    assert_eq!(lamports(ctx, &global).await, LAUNCH_FEE + 10 * SOL);
+}
+
+#[tokio::test]
+async fn bonds_come_back_when_the_launch_graduates() {
+    let bond = 4 * SOL;
+    let mut launch = bonded_launch(MAX_CONTRIBUTION, bond).await;
+    let global = pda::global().0;
+    let creator = launch.creator.pubkey();
+    let user = launch.contributor.pubkey();
+    let ctx = &mut launch.ctx;
+    assert_eq!(lamports(ctx, &global).await, LAUNCH_FEE + MAX_CONTRIBUTION + bond);
+
+    for number in FILLED {
+        let ix = instructions::contribute(&user, &launch.token, number, MAX_CONTRIBUTION);
+        send(ctx, &[ix], &[&launch.contributor]).await.unwrap();
+    }
+    let details: TokenDetails = account(ctx, &launch.token).await;
+    assert!(details.target_reached_at > 0);
+
+    let market = open_market(ctx, &launch.mint);
+    let creator_tokens = create_token_account(ctx, &launch.mint, &creator).await;
+    let user_tokens = create_token_account(ctx, &launch.mint, &user).await;
+    let steps = finalize(&launch, market, positions(&launch, FILLED, creator_tokens, user_tokens));
+    let ctx = &mut launch.ctx;
+    let before = lamports(ctx, &creator).await;
+    send_each(ctx, &steps).await.unwrap();
+
+    // The bond never touches the pool; it goes back to the creator whole.
+    let fee = TARGET / 20;
+    assert_eq!(lamports(ctx, &creator).await, before + bond);
+    assert_eq!(token_balance(ctx, &pda::raydium_pool(&market, raydium::PC_VAULT_SEED)).await, TARGET - fee - CRANK_REWARD);
+    assert_eq!(lamports(ctx, &global).await, LAUNCH_FEE);
+    let details: TokenDetails = account(ctx, &launch.token).await;
+    assert_eq!(details.bond, 0);
+}
+
+#[tokio::test]
+async fn cancelling_a_joined_launch_slashes_the_bond_to_its_contributors() {
+    let bond = 4 * SOL;
+    let mut launch = bonded_launch(10 * SOL, bond).await;
+    let global = pda::global().0;
+    let creator = launch.creator.pubkey();
+    let user = launch.contributor.pubkey();
+    let admin = launch.admin.pubkey();
+    let ctx = &mut launch.ctx;
+
+    let ix = instructions::contribute(&user, &launch.token, 1, 5 * SOL);
+    send(ctx, &[ix], &[&launch.contributor]).await.unwrap();
+
+    let before = lamports(ctx, &creator).await;
+    let ix = instructions::cancel_launch(&admin, &launch.token, &creator, &launch.mint, TICKER, false);
+    send(ctx, &[ix], &[&launch.admin]).await.unwrap();
+
+    // Half the bond goes back; the other half is shared by the positions.
+    let slashed = bond / 2;
+    assert_eq!(lamports(ctx, &creator).await, before + bond - slashed);
+    let details: TokenDetails = account(ctx, &launch.token).await;
+    assert_eq!((details.bond, details.withdrawal_penalties), (0, slashed));
+
+    let contribution = pda::user_contribution(&user, &launch.token, 1).0;
+    let rent = lamports(ctx, &contribution).await;
+    let funded = lamports(ctx, &user).await;
+    let ix = instructions::refund(&user, &launch.token, &creator, TICKER, &contribution);
+    send(ctx, &[ix], &[&launch.contributor]).await.unwrap();
+    assert_eq!(lamports(ctx, &user).await, funded + 5 * SOL + slashed / 3 + rent);
+
+    // The creator's opening position takes the rest of the slashed bond.
+    let opening = pda::creator_contribution(&creator, TICKER).0;
+    let ix = instructions::refund(&creator, &launch.token, &creator, TICKER, &opening);
+    send(ctx, &[ix], &[&launch.creator]).await.unwrap();
+    assert_eq!(lamports(ctx, &global).await, LAUNCH_FEE);
+}
+
+#[tokio::test]
+async fn launches_left_unfinalized_past_the_grace_period_lose_their_bond() {
+    let bond = 4 * SOL;
+    let mut launch = bonded_launch(MAX_CONTRIBUTION, bond).await;
+    let creator = launch.creator.pubkey();
+    let user = launch.contributor.pubkey();
+    let ctx = &mut launch.ctx;
+
+    // Nothing to slash before the target is reached.
+    let slash = instructions::slash_bond(&launch.token, &creator, TICKER);
+    assert!(send(ctx, std::slice::from_ref(&slash), &[]).await.is_err());
+
+    for number in FILLED {
+        let ix = instructions::contribute(&user, &launch.token, number, MAX_CONTRIBUTION);
+        send(ctx, &[ix], &[&launch.contributor]).await.unwrap();
+    }
+
+    // The creator still has the grace period to finalize.
+    assert!(send(ctx, std::slice::from_ref(&slash), &[]).await.is_err());
+    warp(ctx, ProtocolParams::default().finalize_grace_period + 1).await;
+
+    let before = lamports(ctx, &creator).await;
+    send(ctx, std::slice::from_ref(&slash), &[]).await.unwrap();
+    assert_eq!(lamports(ctx, &creator).await, before + bond / 2);
+    let details: TokenDetails = account(ctx, &launch.token).await;
+    assert_eq!(details.status, LaunchStatus::Failed);
+    assert_eq!(details.withdrawal_penalties, bond / 2);
+    let profile: CreatorProfile = account(ctx, &pda::creator_profile(&creator).0).await;
+    assert_eq!(profile.failures, 1);
+
+    // Positions refund straight away, with their share of the slashed bond.
+    let contribution = pda::user_contribution(&user, &launch.token, 1).0;
+    let rent = lamports(ctx, &contribution).await;
+    let funded = lamports(ctx, &user).await;
+    let ix = instructions::refund(&user, &launch.token, &creator, TICKER, &contribution);
+    send(ctx, &[ix], &[&launch.contributor]).await.unwrap();
+    let share = bond / 2 / (TARGET / MAX_CONTRIBUTION);
+    assert_eq!(lamports(ctx, &user).await, funded + MAX_CONTRIBUTION + share + rent);
+
+    // A settled bond cannot be slashed twice.
+    assert!(send(ctx, &[slash], &[]).await.is_err());
+}
+
+#[tokio::test]
+async fn sealed_bid_launches_that_clear_below_target_fail_at_settle() {
+    let mode = LaunchMode::SealedBid {
+        commit_duration: 60,
+        reveal_duration: 60,
+        penalty_bps: 1_000,
+        min_price: 1_000,
+        price_step: 1_000,
+    };
+    let mut launch = launch_in(mode, 0).await;
+    let creator = launch.creator.pubkey();
+    let user = launch.contributor.pubkey();
+    let ctx = &mut launch.ctx;
+
+    let ix = instructions::commit_bid(&user, &launch.token, [7; 32], 5 * SOL);
+    send(ctx, &[ix], &[&launch.contributor]).await.unwrap();
+    warp(ctx, 121).await;
+
+    // Clearing is final, so a book that cannot reach the target fails the
+    // launch there and then, and counts against the creator.
+    let settle = instructions::finalize_settle(&FinalizeAccounts {
+        token: launch.token,
+        creator,
+        ticker: TICKER.to_string(),
+        cranker: ctx.payer.pubkey(),
+        mint: launch.mint,
+        fee_recipient: launch.fee_recipient,
+        market: Pubkey::new_unique(),
+        positions: vec![],
+        mode,
+    });
+    send(ctx, std::slice::from_ref(&settle), &[]).await.unwrap();
+    let details: TokenDetails = account(ctx, &launch.token).await;
+    assert_eq!((details.status, details.finalize_step), (LaunchStatus::Failed, FinalizeStep::Pending));
+    let profile: CreatorProfile = account(ctx, &pda::creator_profile(&creator).0).await;
+    assert_eq!(profile.failures, 1);
+    assert!(ctx.banks_client.get_account(pda::ticker(TICKER).0).await.unwrap().is_none());
+
+    // Only once.
+    assert!(send(ctx, &[settle], &[]).await.is_err());
+    let profile: CreatorProfile = account(ctx, &pda::creator_profile(&creator).0).await;
+    assert_eq!(profile.failures, 1);
+
+    // Deposits come back even with everything paused, settled by anyone
+    // but paid only to the bidder.
+    let ix = instructions::set_pause(&launch.admin.pubkey(), true, 0);
+    send(ctx, &[ix], &[&launch.admin]).await.unwrap();
+    let cranker = ctx.payer.pubkey();
+    let mut redirected = instructions::settle_bid(&cranker, &user, &launch.token, &launch.mint);
+    redirected.accounts[4].pubkey = cranker;
+    assert!(send(ctx, &[redirected], &[]).await.is_err());
+    let before = lamports(ctx, &user).await;
+    let ix = instructions::settle_bid(&cranker, &user, &launch.token, &launch.mint);
+    send(ctx, &[ix], &[]).await.unwrap();
+    assert_eq!(lamports(ctx, &user).await, before + 5 * SOL);
+}
+
+#[tokio::test]
+async fn pausing_reveals_extends_the_reveal_window() {
+    let mode = LaunchMode::SealedBid {
+        commit_duration: 60,
+        reveal_duration: 60,
+        penalty_bps: 1_000,
+        min_price: 1_000,
+        price_step: 1_000,
+    };
+    let mut launch = launch_in(mode, 0).await;
+    let admin = launch.admin.pubkey();
+    let user = launch.contributor.pubkey();
+    let ctx = &mut launch.ctx;
+
+    let salt = [7; 32];
+    let commitment = BidCommitment::hash(&user, 5 * SOL, 2_000, &salt);
+    let ix = instructions::commit_bid(&user, &launch.token, commitment, 5 * SOL);
+    send(ctx, &[ix], &[&launch.contributor]).await.unwrap();
+    warp(ctx, 61).await;
+
+    // Reveals are paused for two minutes, past the end of the window.
+    let ix = instructions::set_pause(&admin, false, pause::REVEAL_BID);
+    send(ctx, &[ix], &[&launch.admin]).await.unwrap();
+    let reveal = instructions::reveal_bid(&user, &launch.token, 5 * SOL, 2_000, salt);
+    assert!(send(ctx, std::slice::from_ref(&reveal), &[&launch.contributor]).await.is_err());
+    warp(ctx, 120).await;
+    let ix = instructions::set_pause(&admin, false, 0);
+    send(ctx, &[ix], &[&launch.admin]).await.unwrap();
+    let config: Config = account(ctx, &pda::config().0).await;
+    assert_eq!((config.reveal_paused_at, config.reveal_paused_for), (0, 120));
+
+    // The window got those two minutes back, and the book only clears
+    // once they have run out.
+    send(ctx, &[reveal], &[&launch.contributor]).await.unwrap();
+    let bid: BidCommitment = account(ctx, &pda::bid(&launch.token, &user).0).await;
+    assert!(bid.revealed);
+    let settle = instructions::finalize_settle(&FinalizeAccounts {
+        token: launch.token,
+        creator: launch.creator.pubkey(),
+        ticker: TICKER.to_string(),
+        cranker: ctx.payer.pubkey(),
+        mint: launch.mint,
+        fee_recipient: launch.fee_recipient,
+        market: Pubkey::new_unique(),
+        positions: vec![],
+        mode,
+    });
+    let error = send(ctx, std::slice::from_ref(&settle), &[]).await.unwrap_err();
+    let code = u32::from(CustomError::RevealPhaseNotOver);
+    assert!(error.contains(&format!("custom program error: {code:#x}")), "{error}");
+    warp(ctx, 60).await;
+    send(ctx, &[settle], &[]).await.unwrap();
+}
+
+#[tokio::test]
+async fn sealed_bid_launches_open_without_a_creator_position() {
+    let mode = LaunchMode::SealedBid {
+        commit_duration: 60,
+        reveal_duration: 60,
+        penalty_bps: 1_000,
+        min_price: 1_000,
+        price_step: 1_000,
+    };
+    let mut launch = launch_in(mode, 0).await;
+    let ctx = &mut launch.ctx;
+    let details: TokenDetails = account(ctx, &launch.token).await;
+    assert_eq!((details.total_contributed, details.tokens_sold, details.open_positions), (0, 0, 0));
+    let opening: UserContribution = account(ctx, &pda::creator_contribution(&launch.creator.pubkey(), TICKER).0).await;
+    assert_eq!((opening.amount, opening.total_tokens), (0, 0));
+
+    // Nothing would ever deliver an opening position bought off the curve,
+    // so the creator cannot pay for one.
+    let rival = Keypair::new();
+    ctx.set_account(&rival.pubkey(), &wallet(100 * SOL).into());
+    let ix = instructions::initialize(&rival.pubkey(), LaunchParams {
+        name: "Rival".to_string(),
+        ticker: "RIVL".to_string(),
+        total_supply: SUPPLY,
+        initial_target: SOL,
+        decimals: 6,
+        mode,
+        curve: Curve::Linear { base_price: 1_000, slope: 0 },
+        bond: 0,
+    });
+    let error = send(ctx, &[ix], &[&rival]).await.unwrap_err();
+    let code = u32::from(CustomError::InvalidInputValue);
+    assert!(error.contains(&format!("custom program error: {code:#x}")), "{error}");
+}
+
+#[tokio::test]
+async fn lottery_launches_open_without_a_creator_position() {
+    let mode = lottery_mode();
+    let mut launch = launch_in(mode, 0).await;
+    let ctx = &mut launch.ctx;
+    let details: TokenDetails = account(ctx, &launch.token).await;
+    assert_eq!((details.total_contributed, details.tokens_sold, details.open_positions), (0, 0, 0));
+
+    // Only tickets are ever settled, so an opening position would be lost.
+    let rival = Keypair::new();
+    ctx.set_account(&rival.pubkey(), &wallet(100 * SOL).into());
+    let ix = instructions::initialize(&rival.pubkey(), LaunchParams {
+        name: "Rival".to_string(),
+        ticker: "RIVL".to_string(),
+        total_supply: SUPPLY,
+        initial_target: SOL,
+        decimals: 6,
+        mode,
+        curve: Curve::Linear { base_price: 1_000, slope: 0 },
+        bond: 0,
+    });
+    let error = send(ctx, &[ix], &[&rival]).await.unwrap_err();
+    let code = u32::from(CustomError::InvalidInputValue);
+    assert!(error.contains(&format!("custom program error: {code:#x}")), "{error}");
+}
+
+#[tokio::test]
+async fn lottery_winners_get_tokens_and_losing_tickets_their_price_back() {
+    let mut launch = launch_in(lottery_mode(), 0).await;
+    let creator = launch.creator.pubkey();
+    let user = launch.contributor.pubkey();
+    let global = pda::global().0;
+    let ctx = &mut launch.ctx;
+
+    // Four winning tickets are enough to graduate this one.
+    let mut details: TokenDetails = account(ctx, &launch.token).await;
+    details.target = 4 * SOL;
+    ctx.set_account(&launch.token, &program_account(&details).into());
+
+    let ix = instructions::contribute(&user, &launch.token, 1, 3 * SOL);
+    send(ctx, &[ix], &[&launch.contributor]).await.unwrap();
+    let ix = instructions::contribute(&creator, &launch.token, 2, 3 * SOL);
+    send(ctx, &[ix], &[&launch.creator]).await.unwrap();
+    let details: TokenDetails = account(ctx, &launch.token).await;
+    assert_eq!((details.tickets_sold, details.total_contributed), (6, 6 * SOL));
+
+    // The sale runs its course before the draw is closed.
+    let payer = ctx.payer.pubkey();
+    let close = instructions::close_lottery(&payer, &launch.token);
+    assert!(send(ctx, std::slice::from_ref(&close), &[]).await.is_err());
+    warp(ctx, 61).await;
+    send(ctx, &[close], &[]).await.unwrap();
+    let lottery: LotteryDraw = account(ctx, &pda::lottery(&launch.token).0).await;
+    assert_eq!((lottery.total_tickets, lottery.drawn), (6, false));
+
+    // Randomness known while the sale was open is refused.
+    let LaunchMode::Lottery { randomness_account, .. } = launch.mode else { unreachable!() };
+    let draw = instructions::draw_lottery(&launch.token, &randomness_account);
+    fulfil_randomness(ctx, &launch.mode, [5; 32], lottery.close_slot);
+    assert!(send(ctx, std::slice::from_ref(&draw), &[]).await.is_err());
+    fulfil_randomness(ctx, &launch.mode, [5; 32], lottery.close_slot + 1);
+    send(ctx, std::slice::from_ref(&draw), &[]).await.unwrap();
+    assert!(send(ctx, &[draw], &[]).await.is_err());
+
+    // Only the winning tickets stay in the raise.
+    let lottery: LotteryDraw = account(ctx, &pda::lottery(&launch.token).0).await;
+    assert_eq!((lottery.winning_tickets, lottery.drawn), (4, true));
+    let details: TokenDetails = account(ctx, &launch.token).await;
+    assert_eq!(details.total_contributed, 4 * SOL);
+
+    // Tickets are settled once the launch has graduated.
+    let cranker = ctx.payer.pubkey();
+    let ix = instructions::settle_tickets(&cranker, &user, &launch.token, &launch.mint, 1);
+    assert!(send(ctx, &[ix], &[]).await.is_err());
+    let market = open_market(ctx, &launch.mint);
+    let steps = finalize(&launch, market, vec![]);
+    let ctx = &mut launch.ctx;
+    send_each(ctx, &steps).await.unwrap();
+
+    // Anyone may settle a position; the owner is paid without signing.
+    let tokens_per_ticket = lottery.tokens_per_ticket(SUPPLY - SUPPLY / 2);
+    let mut delivered = 0;
+    let mut refunded = 0;
+    for (owner, number, first_ticket) in [(&launch.contributor, 1, 0), (&launch.creator, 2, 3)] {
+        let won = (first_ticket..first_ticket + 3).filter(|ticket| lottery.is_winner(*ticket)).count() as u64;
+        let before = lamports(ctx, &owner.pubkey()).await;
+        let ix = instructions::settle_tickets(&cranker, &owner.pubkey(), &launch.token, &launch.mint, number);
+        send(ctx, std::slice::from_ref(&ix), &[]).await.unwrap();
+
+        let tokens = token_balance(ctx, &get_associated_token_address(&owner.pubkey(), &launch.mint)).await;
+        assert_eq!(tokens, won * tokens_per_ticket);
+        assert_eq!(lamports(ctx, &owner.pubkey()).await, before + (3 - won) * SOL);
+        delivered += tokens;
+        refunded += (3 - won) * SOL;
+
+        // Only once.
+        assert!(send(ctx, &[ix], &[]).await.is_err());
+    }
+    assert_eq!((delivered, refunded), (4 * tokens_per_ticket, 2 * SOL));
+    // The sale half, less what the winners took, went to the pool.
+    assert_eq!(token_balance(ctx, &pda::pool_token_account(&launch.mint)).await, 0);
+    assert_eq!(lamports(ctx, &global).await, LAUNCH_FEE);
+}
+
+#[tokio::test]
+async fn lottery_tickets_are_refunded_through_the_draw_that_fails_the_launch() {
+    let mut launch = launch_in(lottery_mode(), 0).await;
+    let creator = launch.creator.pubkey();
+    let user = launch.contributor.pubkey();
+    let ctx = &mut launch.ctx;
+
+    let ix = instructions::contribute(&user, &launch.token, 1, 3 * SOL);
+    send(ctx, &[ix], &[&launch.contributor]).await.unwrap();
+    let ix = instructions::contribute(&creator, &launch.token, 2, 3 * SOL);
+    send(ctx, &[ix], &[&launch.creator]).await.unwrap();
+
+    // Past the deadline the raise is short, but the tickets are still in
+    // the draw, so they cannot be refunded on their own.
+    warp_past_refund_delay(ctx).await;
+    let contribution = pda::user_contribution(&user, &launch.token, 1).0;
+    let ix = instructions::refund(&user, &launch.token, &creator, TICKER, &contribution);
+    let error = send(ctx, &[ix], &[&launch.contributor]).await.unwrap_err();
+    let code = u32::from(CustomError::InvalidLaunchMode);
+    assert!(error.contains(&format!("custom program error: {code:#x}")), "{error}");
+
+    let payer = ctx.payer.pubkey();
+    send(ctx, &[instructions::close_lottery(&payer, &launch.token)], &[]).await.unwrap();
+    let lottery: LotteryDraw = account(ctx, &pda::lottery(&launch.token).0).await;
+    fulfil_randomness(ctx, &launch.mode, [5; 32], lottery.close_slot + 1);
+    let LaunchMode::Lottery { randomness_account, .. } = launch.mode else { unreachable!() };
+    send(ctx, &[instructions::draw_lottery(&launch.token, &randomness_account)], &[]).await.unwrap();
+    let details: TokenDetails = account(ctx, &launch.token).await;
+    assert_eq!((details.tickets_sold, details.total_contributed), (6, 4 * SOL));
+
+    // Four winning tickets fall short of the target, which fails the launch.
+    let settle = finalize(&launch, Pubkey::new_unique(), vec![]).remove(0);
+    let ctx = &mut launch.ctx;
+    send(ctx, &[settle], &[]).await.unwrap();
+    let details: TokenDetails = account(ctx, &launch.token).await;
+    assert_eq!(details.status, LaunchStatus::Failed);
+
+    // Winning or not, every ticket comes back at its price, even with
+    // everything paused.
+    let ix = instructions::set_pause(&launch.admin.pubkey(), true, 0);
+    send(ctx, &[ix], &[&launch.admin]).await.unwrap();
+    let cranker = ctx.payer.pubkey();
+    for (owner, number) in [(&launch.contributor, 1), (&launch.creator, 2)] {
+        let before = lamports(ctx, &owner.pubkey()).await;
+        let ix = instructions::settle_tickets(&cranker, &owner.pubkey(), &launch.token, &launch.mint, number);
+        send(ctx, std::slice::from_ref(&ix), &[]).await.unwrap();
+        assert_eq!(lamports(ctx, &owner.pubkey()).await, before + 3 * SOL);
+        assert_eq!(token_balance(ctx, &get_associated_token_address(&owner.pubkey(), &launch.mint)).await, 0);
+        assert!(send(ctx, &[ix], &[]).await.is_err());
+    }
+    assert_eq!(lamports(ctx, &pda::global().0).await, LAUNCH_FEE);
+}
+
+#[tokio::test]
+async fn lbp_weights_decay_over_the_schedule() {
+    let mut launch = launch_in(lbp_mode(), MAX_CONTRIBUTION).await;
+    let creator = launch.creator.pubkey();
+    let ctx = &mut launch.ctx;
+
+    // Only an open launch starts its pool.
+    let start = instructions::start_lbp(&creator, &launch.token, &launch.mint);
+    let mut details: TokenDetails = account(ctx, &launch.token).await;
+    details.status = LaunchStatus::Cancelled;
+    ctx.set_account(&launch.token, &program_account(&details).into());
+    let error = send(ctx, std::slice::from_ref(&start), &[&launch.creator]).await.unwrap_err();
+    let code = u32::from(CustomError::LaunchCancelled);
+    assert!(error.contains(&format!("custom program error: {code:#x}")), "{error}");
+    details.status = LaunchStatus::Active;
+    ctx.set_account(&launch.token, &program_account(&details).into());
+
+    // The whole supply is seeded against the creator's opening SOL, once.
+    send(ctx, std::slice::from_ref(&start), &[&launch.creator]).await.unwrap();
+    assert!(send(ctx, &[start], &[&launch.creator]).await.is_err());
+    let pool: LiquidityPool = account(ctx, &pda::liquidity_pool(&launch.mint).0).await;
+    assert_eq!((pool.reserve_one, pool.reserve_two), (SUPPLY, MAX_CONTRIBUTION));
+    assert_eq!(pool.end_time - pool.start_time, LBP_DURATION);
+
+    let begin = pool.start_time;
+    assert_eq!(pool.weights(begin - 1), (9_000, 1_000));
+    assert_eq!(pool.weights(begin), (9_000, 1_000));
+    assert_eq!(pool.weights(begin + LBP_DURATION / 4), (8_000, 2_000));
+    assert_eq!(pool.weights(begin + LBP_DURATION / 2), (7_000, 3_000));
+    assert_eq!(pool.weights(pool.end_time), (5_000, 5_000));
+    assert_eq!(pool.weights(pool.end_time + 1), (5_000, 5_000));
+
+    // With the reserves left alone, the same SOL buys more as the token's
+    // weight decays.
+    let tokens_for_a_sol = |now| {
+        let (token_weight, sol_weight) = pool.weights(now);
+        calculate_weighted_out(pool.reserve_two, sol_weight, pool.reserve_one, token_weight, SOL).unwrap()
+    };
+    assert!(tokens_for_a_sol(begin) < tokens_for_a_sol(begin + LBP_DURATION / 2));
+    assert!(tokens_for_a_sol(begin + LBP_DURATION / 2) < tokens_for_a_sol(pool.end_time - 1));
+}
+
+#[tokio::test]
+async fn lbp_trades_against_the_weighted_pool() {
+    let mut launch = launch_in(lbp_mode(), MAX_CONTRIBUTION).await;
+    let user = launch.contributor.pubkey();
+    let pool_address = pda::liquidity_pool(&launch.mint).0;
+    let ctx = &mut launch.ctx;
+    let ix = instructions::start_lbp(&launch.creator.pubkey(), &launch.token, &launch.mint);
+    send(ctx, &[ix], &[&launch.creator]).await.unwrap();
+    let pool: LiquidityPool = account(ctx, &pool_address).await;
+
+    // Halfway through, well inside one weight step.
+    let now = pool.start_time + LBP_DURATION / 2 + 108;
+    warp_to(ctx, now).await;
+    let (token_weight, sol_weight) = pool.weights(now);
+    assert_eq!((token_weight, sol_weight), (7_000, 3_000));
+
+    let paid = 5 * SOL;
+    let expected = calculate_weighted_out(pool.reserve_two, sol_weight, pool.reserve_one, token_weight, paid).unwrap();
+    let ix = instructions::buy(&user, &launch.token, &launch.mint, &launch.mode, paid, expected);
+    send(ctx, &[ix], &[&launch.contributor]).await.unwrap();
+    let user_tokens = get_associated_token_address(&user, &launch.mint);
+    let bought = token_balance(ctx, &user_tokens).await;
+    assert_eq!(bought, expected);
+
+    let pool: LiquidityPool = account(ctx, &pool_address).await;
+    assert_eq!((pool.reserve_one, pool.reserve_two), (SUPPLY - bought, MAX_CONTRIBUTION + paid));
+    let details: TokenDetails = account(ctx, &launch.token).await;
+    assert_eq!(details.total_contributed, pool.reserve_two);
+
+    // Selling it all back, in two goes, returns no more than was paid.
+    let mut received = 0;
+    for amount in [bought / 2, bought - bought / 2] {
+        let pool: LiquidityPool = account(ctx, &pool_address).await;
+        let expected = calculate_weighted_out(pool.reserve_one, token_weight, pool.reserve_two, sol_weight, amount).unwrap();
+        let before = lamports(ctx, &user).await;
+        let ix = instructions::sell(&user, &launch.token, &launch.mint, &launch.mode, amount, expected);
+        send(ctx, &[ix], &[&launch.contributor]).await.unwrap();
+        assert_eq!(lamports(ctx, &user).await, before + expected);
+        received += expected;
+    }
+    assert!(received <= paid, "sold back for {received} after paying {paid}");
+    assert_eq!(token_balance(ctx, &user_tokens).await, 0);
+
+    let pool: LiquidityPool = account(ctx, &pool_address).await;
+    assert_eq!((pool.reserve_one, pool.reserve_two), (SUPPLY, MAX_CONTRIBUTION + paid - received));
+    let details: TokenDetails = account(ctx, &launch.token).await;
+    assert_eq!(details.total_contributed, pool.reserve_two);
+}
+
+#[tokio::test]
+async fn lbp_migrates_its_closing_reserves_once_the_schedule_ends() {
+    let mut launch = launch_in(lbp_mode(), MAX_CONTRIBUTION).await;
+    let user = launch.contributor.pubkey();
+    let global = pda::global().0;
+    let pool_address = pda::liquidity_pool(&launch.mint).0;
+    let ctx = &mut launch.ctx;
+    let ix = instructions::start_lbp(&launch.creator.pubkey(), &launch.token, &launch.mint);
+    send(ctx, &[ix], &[&launch.creator]).await.unwrap();
+    let ix = instructions::buy(&user, &launch.token, &launch.mint, &launch.mode, 5 * SOL, 0);
+    send(ctx, &[ix], &[&launch.contributor]).await.unwrap();
+
+    // The pool runs its whole schedule, whatever it has raised.
+    let market = open_market(ctx, &launch.mint);
+    let steps = finalize(&launch, market, vec![]);
+    let ctx = &mut launch.ctx;
+    let error = send(ctx, &steps[..1], &[]).await.unwrap_err();
+    let code = u32::from(CustomError::PoolStillActive);
+    assert!(error.contains(&format!("custom program error: {code:#x}")), "{error}");
+
+    // Trading stops when it ends.
+    let pool: LiquidityPool = account(ctx, &pool_address).await;
+    warp_to(ctx, pool.end_time).await;
+    let ix = instructions::buy(&user, &launch.token, &launch.mint, &launch.mode, SOL, 0);
+    let error = send(ctx, &[ix], &[&launch.contributor]).await.unwrap_err();
+    let code = u32::from(CustomError::PoolNotActive);
+    assert!(error.contains(&format!("custom program error: {code:#x}")), "{error}");
+
+    // The pool has to come along to have its reserves emptied.
+    send(ctx, &steps[..1], &[]).await.unwrap();
+    let mut without_pool = steps[1].clone();
+    let meta = without_pool.accounts.iter_mut().find(|meta| meta.pubkey == pool_address).unwrap();
+    *meta = AccountMeta::new_readonly(pump::ID, false);
+    let error = send(ctx, &[without_pool], &[]).await.unwrap_err();
+    let code = u32::from(CustomError::InvalidPool);
+    assert!(error.contains(&format!("custom program error: {code:#x}")), "{error}");
+
+    send_each(ctx, &steps[1..]).await.unwrap();
+    let details: TokenDetails = account(ctx, &launch.token).await;
+    assert_eq!(details.finalize_step, FinalizeStep::Complete);
+    assert!(!details.is_virtual);
+
+    // The closing reserves, less the fee and crank reward, become the
+    // Raydium pool's opening liquidity.
+    let raised = MAX_CONTRIBUTION + 5 * SOL;
+    let fee = raised / 20;
+    assert_eq!(pool.reserve_two, raised);
+    assert_eq!(lamports(ctx, &launch.fee_recipient).await, fee);
+    assert_eq!(token_balance(ctx, &pda::raydium_pool(&market, raydium::PC_VAULT_SEED)).await, raised - fee - CRANK_REWARD);
+    assert_eq!(token_balance(ctx, &pda::raydium_pool(&market, raydium::COIN_VAULT_SEED)).await, pool.reserve_one);
+    assert_eq!(token_balance(ctx, &pda::pool_token_account(&launch.mint)).await, 0);
+    assert_eq!(lamports(ctx, &global).await, LAUNCH_FEE);
+
+    let pool: LiquidityPool = account(ctx, &pool_address).await;
+    assert_eq!((pool.reserve_one, pool.reserve_two), (0, 0));
+}
+
+#[tokio::test]
+async fn extending_the_deadline_lets_earlier_contributors_opt_out() {
+    let mut launch = launch(10 * SOL).await;
+    let global = pda::global().0;
+    let creator = launch.creator.pubkey();
+    let user = launch.contributor.pubkey();
+    let ctx = &mut launch.ctx;
+
+    let ix = instructions::contribute(&user, &launch.token, 1, 5 * SOL);
+    send(ctx, &[ix], &[&launch.contributor]).await.unwrap();
+    let contribution = pda::user_contribution(&user, &launch.token, 1).0;
+
+    // Nothing to opt out of yet, and only the creator can extend, within
+    // the protocol maximum.
+    let opt_out = instructions::opt_out(&user, &launch.token, &contribution);
+    assert!(send(ctx, std::slice::from_ref(&opt_out), &[&launch.contributor]).await.is_err());
+    let ix = instructions::extend_deadline(&user, &launch.token, MAX_EXTENSION);
+    assert!(send(ctx, &[ix], &[&launch.contributor]).await.is_err());
+    let ix = instructions::extend_deadline(&creator, &launch.token, MAX_EXTENSION + 1);
+    assert!(send(ctx, &[ix], &[&launch.creator]).await.is_err());
+
+    let ix = instructions::extend_deadline(&creator, &launch.token, MAX_EXTENSION);
+    send(ctx, &[ix], &[&launch.creator]).await.unwrap();
+    let details: TokenDetails = account(ctx, &launch.token).await;
+    assert_eq!(details.refund_deadline, details.creation_time + REFUND_DELAY + MAX_EXTENSION);
+    assert!(details.extended_at > 0);
+
+    // Once is the limit.
+    let ix = instructions::extend_deadline(&creator, &launch.token, 1);
+    assert!(send(ctx, &[ix], &[&launch.creator]).await.is_err());
+
+    // The creator chose the new deadline and cannot opt out of it.
+    let opening = pda::creator_contribution(&creator, TICKER).0;
+    let ix = instructions::opt_out(&creator, &launch.token, &opening);
+    assert!(send(ctx, &[ix], &[&launch.creator]).await.is_err());
+
+    let before = lamports(ctx, &user).await;
+    send(ctx, std::slice::from_ref(&opt_out), &[&launch.contributor]).await.unwrap();
+    assert_eq!(lamports(ctx, &user).await, before + 5 * SOL);
+    assert_eq!(lamports(ctx, &global).await, LAUNCH_FEE + 10 * SOL);
+
+    let position: UserContribution = account(ctx, &contribution).await;
+    assert!(position.opted_out);
+    assert_eq!(position.amount, 0);
+    let details: TokenDetails = account(ctx, &launch.token).await;
+    assert_eq!((details.total_contributed, details.opted_out), (10 * SOL, 5 * SOL));
+
+    // The old deadline no longer opens refunds.
+    warp_past_refund_delay(ctx).await;
+    let ix = instructions::refund(&creator, &launch.token, &creator, TICKER, &opening);
+    assert!(send(ctx, &[ix], &[&launch.creator]).await.is_err());
+    warp(ctx, MAX_EXTENSION).await;
+    let ix = instructions::refund(&creator, &launch.token, &creator, TICKER, &opening);
+    send(ctx, &[ix], &[&launch.creator]).await.unwrap();
+}
+
+#[tokio::test]
+async fn withdrawing_mid_sale_pays_the_penalty_to_the_treasury() {
+    let mut launch = launch(10 * SOL).await;
+    let global = pda::global().0;
+    let user = launch.contributor.pubkey();
+    let ctx = &mut launch.ctx;
+
+    let ix = instructions::contribute(&user, &launch.token, 1, 5 * SOL);
+    send(ctx, &[ix], &[&launch.contributor]).await.unwrap();
+    let contribution = pda::user_contribution(&user, &launch.token, 1).0;
+    let position: UserContribution = account(ctx, &contribution).await;
+    let sold = account::<TokenDetails>(ctx, &launch.token).await.tokens_sold;
+    let rent = lamports(ctx, &contribution).await;
+
+    // Part of the position comes back, less 10%.
+    let before = lamports(ctx, &user).await;
+    let ix = instructions::withdraw_contribution(&user, &launch.token, &contribution, &launch.fee_recipient, 2 * SOL);
+    send(ctx, &[ix], &[&launch.contributor]).await.unwrap();
+    assert_eq!(lamports(ctx, &user).await, before + 2 * SOL - 2 * SOL / 10);
+    assert_eq!(lamports(ctx, &launch.fee_recipient).await, 2 * SOL / 10);
+    assert_eq!(lamports(ctx, &global).await, LAUNCH_FEE + 13 * SOL);
+
+    let remaining: UserContribution = account(ctx, &contribution).await;
+    assert_eq!(remaining.amount, 3 * SOL);
+    let details: TokenDetails = account(ctx, &launch.token).await;
+    assert_eq!(details.total_contributed, 13 * SOL);
+    // The withdrawn share of the position goes back to the curve.
+    assert_eq!(sold - details.tokens_sold, position.total_tokens - remaining.total_tokens);
+    assert!(remaining.total_tokens < position.total_tokens);
+
+    // More than the position holds is rejected.
+    let ix = instructions::withdraw_contribution(&user, &launch.token, &contribution, &launch.fee_recipient, 3 * SOL + 1);
+    assert!(send(ctx, &[ix], &[&launch.contributor]).await.is_err());
+
+    // Withdrawing the rest closes the position.
+    let before = lamports(ctx, &user).await;
+    let ix = instructions::withdraw_contribution(&user, &launch.token, &contribution, &launch.fee_recipient, 3 * SOL);
+    send(ctx, &[ix], &[&launch.contributor]).await.unwrap();
+    assert_eq!(lamports(ctx, &user).await, before + 3 * SOL - 3 * SOL / 10 + rent);
+    assert!(ctx.banks_client.get_account(contribution).await.unwrap().is_none());
+    let details: TokenDetails = account(ctx, &launch.token).await;
+    assert_eq!((details.total_contributed, details.withdrawal_penalties), (10 * SOL, 0));
+}
+
+#[tokio::test]
+async fn withdrawal_penalties_can_go_to_the_remaining_contributors() {
+    let mut launch = launch(10 * SOL).await;
+    let global = pda::global().0;
+    let creator = launch.creator.pubkey();
+    let user = launch.contributor.pubkey();
+    let ctx = &mut launch.ctx;
+
+    // Launches keep the terms they opened with, so switch this one directly.
+    let mut details: TokenDetails = account(ctx, &launch.token).await;
+    details.terms.withdraw_penalty_recipient = PenaltyRecipient::Contributors;
+    ctx.set_account(&launch.token, &program_account(&details).into());
+
+    let ix = instructions::contribute(&user, &launch.token, 1, 5 * SOL);
+    send(ctx, &[ix], &[&launch.contributor]).await.unwrap();
+    let contribution = pda::user_contribution(&user, &launch.token, 1).0;
+    let ix = instructions::withdraw_contribution(&user, &launch.token, &contribution, &launch.fee_recipient, 4 * SOL);
+    send(ctx, &[ix], &[&launch.contributor]).await.unwrap();
+
+    // The penalty stays in custody for whoever is still in.
+    let penalty = 4 * SOL / 10;
+    assert_eq!(lamports(ctx, &global).await, LAUNCH_FEE + 11 * SOL + penalty);
+    let details: TokenDetails = account(ctx, &launch.token).await;
+    assert_eq!(details.withdrawal_penalties, penalty);
+
+    // Curve buys hold no position, so they take no part of it.
+    let ix = instructions::buy(&user, &launch.token, &launch.mint, &LaunchMode::Standard, SOL, 0);
+    send(ctx, &[ix], &[&launch.contributor]).await.unwrap();
+
+    // Refunds share it pro rata, and the last one out empties it.
+    warp_past_refund_delay(ctx).await;
+    let before = lamports(ctx, &creator).await;
+    let opening = pda::creator_contribution(&creator, TICKER).0;
+    let rent = lamports(ctx, &opening).await;
+    let ix = instructions::refund(&creator, &launch.token, &creator, TICKER, &opening);
+    send(ctx, &[ix], &[&launch.creator]).await.unwrap();
+    let creator_share = penalty * 10 / 11;
+    assert_eq!(lamports(ctx, &creator).await, before + 10 * SOL + creator_share + rent);
+
+    let ix = instructions::refund(&user, &launch.token, &creator, TICKER, &contribution);
+    send(ctx, &[ix], &[&launch.contributor]).await.unwrap();
+    assert_eq!(lamports(ctx, &global).await, LAUNCH_FEE + SOL);
+    let details: TokenDetails = account(ctx, &launch.token).await;
+    assert_eq!(details.withdrawal_penalties, 0);
+}
+
+#[tokio::test]
+async fn withdrawals_close_with_the_sale() {
+    let mut launch = launch(10 * SOL).await;
+    let user = launch.contributor.pubkey();
+    let ctx = &mut launch.ctx;
+
+    let ix = instructions::contribute(&user, &launch.token, 1, 5 * SOL);
+    send(ctx, &[ix], &[&launch.contributor]).await.unwrap();
+    let contribution = pda::user_contribution(&user, &launch.token, 1).0;
+
+    warp_past_refund_delay(ctx).await;
+    let ix = instructions::withdraw_contribution(&user, &launch.token, &contribution, &launch.fee_recipient, SOL);
+    let error = send(ctx, &[ix], &[&launch.contributor]).await.unwrap_err();
+    let code = u32::from(CustomError::WithdrawalWindowClosed);
+    assert!(error.contains(&format!("custom program error: {code:#x}")), "{error}");
+}
+
+#[tokio::test]
+async fn pausing_stops_contributions_but_not_refunds() {
+    let mut launch = launch(10 * SOL).await;
+    let creator = launch.creator.pubkey();
+    let user = launch.contributor.pubkey();
+    let admin = launch.admin.pubkey();
+    let ctx = &mut launch.ctx;
+
+    // Only the admin holds the switch.
+    let ix = instructions::set_pause(&creator, true, 0);
+    assert!(send(ctx, &[ix], &[&launch.creator]).await.is_err());
+
+    let ix = instructions::set_pause(&admin, true, 0);
+    send(ctx, &[ix], &[&launch.admin]).await.unwrap();
+    let extend = instructions::extend_deadline(&creator, &launch.token, MAX_EXTENSION);
+    assert!(send(ctx, std::slice::from_ref(&extend), &[&launch.creator]).await.is_err());
+
+    // A single bit stops only its instruction.
+    let ix = instructions::set_pause(&admin, false, pause::CONTRIBUTE);
+    send(ctx, &[ix], &[&launch.admin]).await.unwrap();
+    let ix = instructions::contribute(&user, &launch.token, 1, SOL);
+    assert!(send(ctx, &[ix], &[&launch.contributor]).await.is_err());
+    send(ctx, &[extend], &[&launch.creator]).await.unwrap();
+
+    // Everyone can still get out.
+    warp(ctx, REFUND_DELAY + MAX_EXTENSION + 1).await;
+    let opening = pda::creator_contribution(&creator, TICKER).0;
+    let ix = instructions::refund(&creator, &launch.token, &creator, TICKER, &opening);
+    send(ctx, &[ix], &[&launch.creator]).await.unwrap();
+
+    let ix = instructions::set_pause(&admin, false, 0);
+    send(ctx, &[ix], &[&launch.admin]).await.unwrap();
+    let config: Config = account(ctx, &pda::config().0).await;
+    assert!(!config.is_paused(pause::CONTRIBUTE));
+}
+
+#[tokio::test]
+async fn admin_transfers_take_the_new_admins_signature() {
+    let mut launch = launch(10 * SOL).await;
+    let admin = launch.admin.pubkey();
+    let next = Keypair::new();
+    let ctx = &mut launch.ctx;
+
+    let ix = instructions::propose_admin(&next.pubkey(), &next.pubkey());
+    assert!(send(ctx, &[ix], &[&next]).await.is_err());
+    let ix = instructions::propose_admin(&admin, &next.pubkey());
+    send(ctx, &[ix], &[&launch.admin]).await.unwrap();
+
+    // Until the transfer is accepted the old admin is still in charge.
+    let config: Config = account(ctx, &pda::config().0).await;
+    assert_eq!((config.admin, config.pending_admin), (admin, next.pubkey()));
+    let ix = instructions::accept_admin(&launch.creator.pubkey());
+    assert!(send(ctx, &[ix], &[&launch.creator]).await.is_err());
+
+    let ix = instructions::accept_admin(&next.pubkey());
+    send(ctx, &[ix], &[&next]).await.unwrap();
+    let config: Config = account(ctx, &pda::config().0).await;
+    assert_eq!((config.admin, config.pending_admin), (next.pubkey(), Pubkey::default()));
+
+    let ix = instructions::set_pause(&admin, true, 0);
+    assert!(send(ctx, &[ix], &[&launch.admin]).await.is_err());
+    let ix = instructions::set_pause(&next.pubkey(), true, 0);
+    send(ctx, &[ix], &[&next]).await.unwrap();
+}
+
+#[tokio::test]
+async fn operational_roles_are_limited_to_their_own_settings() {
+    let mut launch = launch(10 * SOL).await;
+    let admin = launch.admin.pubkey();
+    let (fee_admin, guardian) = (Keypair::new(), Keypair::new());
+    let ctx = &mut launch.ctx;
+
+    let ix = instructions::set_roles(&admin, &fee_admin.pubkey(), &guardian.pubkey());
+    send(ctx, &[ix], &[&launch.admin]).await.unwrap();
+
+    // The fee admin moves the fee recipient, behind the timelock; nobody
+    // else can.
+    let recipient = Pubkey::new_unique();
+    let ix = instructions::set_fee_recipient(&admin, &recipient);
+    assert!(send(ctx, &[ix], &[&launch.admin]).await.is_err());
+    let ix = instructions::set_fee_recipient(&fee_admin.pubkey(), &recipient);
+    send(ctx, &[ix], &[&fee_admin]).await.unwrap();
+    let config: Config = account(ctx, &pda::config().0).await;
+    assert_eq!(config.params.fee_recipient, launch.fee_recipient);
+    assert!(send(ctx, &[instructions::execute_config_change()], &[]).await.is_err());
+    warp(ctx, config.params.config_delay).await;
+    send(ctx, &[instructions::execute_config_change()], &[]).await.unwrap();
+    let config: Config = account(ctx, &pda::config().0).await;
+    assert_eq!(config.params.fee_recipient, recipient);
+
+    // The guardian can pause but not resume, nor hand out roles.
+    let ix = instructions::set_pause(&guardian.pubkey(), false, pause::CONTRIBUTE | pause::FINALIZE);
+    send(ctx, &[ix], &[&guardian]).await.unwrap();
+    let ix = instructions::set_pause(&guardian.pubkey(), false, pause::CONTRIBUTE);
+    assert!(send(ctx, &[ix], &[&guardian]).await.is_err());
+    let ix = instructions::set_roles(&guardian.pubkey(), &guardian.pubkey(), &guardian.pubkey());
+    assert!(send(ctx, &[ix], &[&guardian]).await.is_err());
+
+    let ix = instructions::set_pause(&admin, false, 0);
+    send(ctx, &[ix], &[&launch.admin]).await.unwrap();
+
+    // Moving the recipient again while the admin has a change queued rides
+    // along with it rather than pushing it back.
+    let params = ProtocolParams { max_contribution: SOL, ..config.params };
+    let ix = instructions::queue_config_change(&admin, params);
+    send(ctx, &[ix], &[&launch.admin]).await.unwrap();
+    let queued: Config = account(ctx, &pda::config().0).await;
+    warp(ctx, config.params.config_delay / 2).await;
+    let ix = instructions::set_fee_recipient(&fee_admin.pubkey(), &launch.fee_recipient);
+    send(ctx, &[ix], &[&fee_admin]).await.unwrap();
+    let config: Config = account(ctx, &pda::config().0).await;
+    let change = config.pending_change.unwrap();
+    assert_eq!(change.eta, queued.pending_change.unwrap().eta);
+    assert_eq!(
+        (change.params.max_contribution, change.params.fee_recipient),
+        (SOL, launch.fee_recipient),
+    );
+}
+
+#[tokio::test]
+async fn config_changes_wait_out_the_timelock_and_spare_open_launches() {
+    let mut launch = launch(10 * SOL).await;
+    let admin = launch.admin.pubkey();
+    let user = launch.contributor.pubkey();
+    let ctx = &mut launch.ctx;
+
+    let current: Config = account(ctx, &pda::config().0).await;
+    let params = ProtocolParams { max_contribution: SOL, launch_fee: 2 * SOL, ..current.params };
+    let ix = instructions::queue_config_change(&user, params);
+    assert!(send(ctx, &[ix], &[&launch.contributor]).await.is_err());
+    // The timelock cannot be queued away.
+    let unlocked = ProtocolParams { config_delay: ProtocolParams::MIN_CONFIG_DELAY - 1, ..params };
+    let ix = instructions::queue_config_change(&admin, unlocked);
+    assert!(send(ctx, &[ix], &[&launch.admin]).await.is_err());
+    // Nor can a crank reward that the fee leaves no room for.
+    let fee = params.target * params.finalize_fee_bps as u64 / 10_000;
+    let overpaid = ProtocolParams { crank_reward: params.target - fee + 1, ..params };
+    let ix = instructions::queue_config_change(&admin, overpaid);
+    assert!(send(ctx, &[ix], &[&launch.admin]).await.is_err());
+    let ix = instructions::queue_config_change(&admin, params);
+    send(ctx, &[ix], &[&launch.admin]).await.unwrap();
+
+    // Nothing changes until the delay is over.
+    assert!(send(ctx, &[instructions::execute_config_change()], &[]).await.is_err());
+    warp(ctx, current.params.config_delay).await;
+    send(ctx, &[instructions::execute_config_change()], &[]).await.unwrap();
+    let config: Config = account(ctx, &pda::config().0).await;
+    assert_eq!((config.params, config.pending_change), (params, None));
+
+    // The open launch still takes contributions up to its own cap.
+    let ix = instructions::contribute(&user, &launch.token, 1, 5 * SOL);
+    send(ctx, &[ix], &[&launch.contributor]).await.unwrap();
+    let details: TokenDetails = account(ctx, &launch.token).await;
+    assert_eq!((details.terms.launch_fee, details.terms.max_contribution), (LAUNCH_FEE, MAX_CONTRIBUTION));
+
+    // A queued change can be dropped, but only by the admin.
+    let ix = instructions::queue_config_change(&admin, current.params);
+    send(ctx, &[ix], &[&launch.admin]).await.unwrap();
+    let ix = instructions::cancel_config_change(&user);
+    assert!(send(ctx, &[ix], &[&launch.contributor]).await.is_err());
+    let ix = instructions::cancel_config_change(&admin);
+    send(ctx, &[ix], &[&launch.admin]).await.unwrap();
+    let config: Config = account(ctx, &pda::config().0).await;
+    assert_eq!((config.params, config.pending_change), (params, None));
+}
+
+#[tokio::test]
+async fn tickers_are_unique_across_creators_and_can_be_reserved() {
+    let mut launch = launch(10 * SOL).await;
+    let admin = launch.admin.pubkey();
+    let rival = Keypair::new();
+    let ctx = &mut launch.ctx;
+    ctx.set_account(&rival.pubkey(), &wallet(100 * SOL).into());
+    let create = |ticker: &str| instructions::initialize(&rival.pubkey(), LaunchParams {
+        name: "Rival".to_string(),
+        ticker: ticker.to_string(),
+        total_supply: SUPPLY,
+        initial_target: SOL,
+        decimals: 6,
+        mode: LaunchMode::Standard,
+        curve: Curve::Linear { base_price: 1_000, slope: 0 },
+        bond: 0,
+    });
+
+    let record: TickerRecord = account(ctx, &pda::ticker(TICKER).0).await;
+    assert_eq!((record.ticker.as_str(), record.token, record.reserved), ("LNCH", launch.token, false));
+    assert!(send(ctx, &[create("lnch")], &[&rival]).await.is_err());
+    assert!(send(ctx, &[create("LN-CH")], &[&rival]).await.is_err());
+
+    // Reserved tickers are off limits in any case until released.
+    let ix = instructions::reserve_ticker(&rival.pubkey(), "usdc");
+    assert!(send(ctx, &[ix], &[&rival]).await.is_err());
+    let ix = instructions::reserve_ticker(&admin, "usdc");
+    send(ctx, &[ix], &[&launch.admin]).await.unwrap();
+    assert!(send(ctx, &[create("Usdc")], &[&rival]).await.is_err());
+    let ix = instructions::release_ticker(&admin, "LNCH");
+    assert!(send(ctx, &[ix], &[&launch.admin]).await.is_err());
+    let ix = instructions::release_ticker(&admin, "USDC");
+    send(ctx, &[ix], &[&launch.admin]).await.unwrap();
+    send(ctx, &[create("Usdc")], &[&rival]).await.unwrap();
+
+    // A claim only lasts as long as the launch: once it is cancelled, the
+    // ticker is free again.
+    let creator = launch.creator.pubkey();
+    let ix = instructions::cancel_launch(&creator, &launch.token, &creator, &launch.mint, TICKER, false);
+    send(ctx, &[ix], &[&launch.creator]).await.unwrap();
+    assert!(ctx.banks_client.get_account(pda::ticker(TICKER).0).await.unwrap().is_none());
+    send(ctx, &[create("lnch")], &[&rival]).await.unwrap();
+}
+
+#[tokio::test]
+async fn initialize_rejects_malformed_launches() {
+    let mut launch = launch(10 * SOL).await;
+    let rival = Keypair::new();
+    let ctx = &mut launch.ctx;
+    ctx.set_account(&rival.pubkey(), &wallet(100 * SOL).into());
+    let params = |name: &str, ticker: &str, total_supply: u64, decimals: u8| LaunchParams {
+        name: name.to_string(),
+        ticker: ticker.to_string(),
+        total_supply,
+        initial_target: SOL,
+        decimals,
+        mode: LaunchMode::Standard,
+        curve: Curve::Linear { base_price: 1_000, slope: 0 },
+        bond: 0,
+    };
+
+    let malformed = [
+        params("", "RIVL", SUPPLY, 6),
+        params(" Rival", "RIVL", SUPPLY, 6),
+        params("Ri\u{7}val", "RIVL", SUPPLY, 6),
+        params(&"R".repeat(TokenDetails::MAX_NAME_LENGTH + 1), "RIVL", SUPPLY, 6),
+        params("Rival", "", SUPPLY, 6),
+        params("Rival", "RIVALRIVALR", SUPPLY, 6),
+        params("Rival", "RIVL", 0, 6),
+        params("Rival", "RIVL", 999_999, 6),
+        params("Rival", "RIVL", SUPPLY, 10),
+        params("Rival", "RIVL", u64::MAX, 0),
+    ];
+    for params in malformed {
+        let ix = instructions::initialize(&rival.pubkey(), params.clone());
+        assert!(send(ctx, &[ix], &[&rival]).await.is_err(), "{params:?}");
+    }
+
+    // Multi-byte names are fine as long as they fit.
+    let ix = instructions::initialize(&rival.pubkey(), params("Rivalé 🚀", "RIVL", SUPPLY, 6));
+    send(ctx, &[ix], &[&rival]).await.unwrap();
+}
+
+#[tokio::test]
+async fn unproven_creators_are_held_to_the_valuation_cap() {
+    let mut launch = launch(10 * SOL).await;
+    let rival = Keypair::new();
+    let ctx = &mut launch.ctx;
+    ctx.set_account(&rival.pubkey(), &wallet(100 * SOL).into());
+    let create = |ticker: &str, total_supply: u64| instructions::initialize(&rival.pubkey(), LaunchParams {
+        name: "Rival".to_string(),
+        ticker: ticker.to_string(),
+        total_supply,
+        initial_target: SOL,
+        decimals: 6,
+        mode: LaunchMode::Standard,
+        curve: Curve::Linear { base_price: 1_000, slope: 0 },
+        bond: 0,
+    });
+
+    // The full supply opens at 1_000 SOL.
+    let mut config: Config = account(ctx, &pda::config().0).await;
+    config.params.max_unproven_valuation = 500 * SOL;
+    ctx.set_account(&pda::config().0, &program_account(&config).into());
+    assert!(send(ctx, &[create("BIG", SUPPLY)], &[&rival]).await.is_err());
+    send(ctx, &[create("SMALL", SUPPLY / 4)], &[&rival]).await.unwrap();
+
+    // A graduated launch lifts the cap.
+    let address = pda::creator_profile(&rival.pubkey()).0;
+    let mut profile: CreatorProfile = account(ctx, &address).await;
+    assert_eq!((profile.creator, profile.launches), (rival.pubkey(), 1));
+    profile.graduations = 1;
+    ctx.set_account(&address, &program_account(&profile).into());
+    send(ctx, &[create("BIG", SUPPLY)], &[&rival]).await.unwrap();
+    let profile: CreatorProfile = account(ctx, &address).await;
+    assert_eq!(profile.launches, 2);
+}
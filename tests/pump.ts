@@ -2,11 +2,22 @@ import * as anchor from '@coral-xyz/anchor';
 import { Program } from '@coral-xyz/anchor';
 import { Pump } from '../target/types/pump';
 import { SystemProgram } from '@solana/web3.js';
+import { createHash, randomBytes } from 'crypto';
 import { assert } from 'chai';
 import fs from 'fs';
 import path from 'path';
 import os from 'os';
 
+// Raydium's and OpenBook's addresses, as in `programs/pump/src/utils/raydium.rs`.
+// Anchor.toml loads the mock AMM at Raydium's address.
+const AMM_PROGRAM_ID = new anchor.web3.PublicKey("675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8");
+const MARKET_PROGRAM_ID = new anchor.web3.PublicKey("srmqPvymJeFKQ4zGQed1GFppgkRHL9kaELCbyksJtPX");
+const CREATE_POOL_FEE_ID = new anchor.web3.PublicKey("7YttLkHDoNj9wyDur5pM1ejNaAvT9X4eqaYcHQqtj2G5");
+const NATIVE_MINT = new anchor.web3.PublicKey("So11111111111111111111111111111111111111112");
+const BPF_LOADER_UPGRADEABLE_ID = new anchor.web3.PublicKey("BPFLoaderUpgradeab1e11111111111111111111111");
+// Positions paid per `finalizeSettle`, few enough to fit a transaction.
+const SETTLE_BATCH = 8;
+
 describe("pump", () => {
     anchor.setProvider(anchor.AnchorProvider.env());
     const provider = anchor.getProvider();
@@ -14,9 +25,56 @@ describe("pump", () => {
 
     let token: anchor.web3.PublicKey;
     let user: anchor.web3.Keypair;
-    let programAccount: anchor.web3.Keypair;
+    let config: anchor.web3.PublicKey;
+    let programAccount: anchor.web3.PublicKey;
+    let eventAuthority: anchor.web3.PublicKey;
     let ticker: string;
 
+    function deriveCreatorProfilePDA(creator: anchor.web3.PublicKey) {
+        return anchor.web3.PublicKey.findProgramAddressSync(
+            [Buffer.from("creator-profile"), creator.toBuffer()],
+            program.programId
+        )[0];
+    }
+
+    function deriveTickerRecordPDA(ticker: string) {
+        return anchor.web3.PublicKey.findProgramAddressSync(
+            [Buffer.from("ticker"), Buffer.from(ticker.toUpperCase())],
+            program.programId
+        )[0];
+    }
+
+    function deriveRaydiumPDA(seed: string) {
+        return anchor.web3.PublicKey.findProgramAddressSync([Buffer.from(seed)], AMM_PROGRAM_ID)[0];
+    }
+
+    function deriveRaydiumPoolPDA(market: anchor.web3.PublicKey, seed: string) {
+        return anchor.web3.PublicKey.findProgramAddressSync(
+            [AMM_PROGRAM_ID.toBuffer(), market.toBuffer(), Buffer.from(seed)],
+            AMM_PROGRAM_ID
+        )[0];
+    }
+
+    function createAssociatedTokenAccount(
+        payer: anchor.web3.PublicKey,
+        address: anchor.web3.PublicKey,
+        owner: anchor.web3.PublicKey,
+        mint: anchor.web3.PublicKey
+    ) {
+        return new anchor.web3.TransactionInstruction({
+            programId: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+            keys: [
+                { pubkey: payer, isSigner: true, isWritable: true },
+                { pubkey: address, isSigner: false, isWritable: true },
+                { pubkey: owner, isSigner: false, isWritable: false },
+                { pubkey: mint, isSigner: false, isWritable: false },
+                { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+                { pubkey: anchor.utils.token.TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+            ],
+            data: Buffer.from([1]), // CreateIdempotent
+        });
+    }
+
     async function deriveUserContributionPDA(count: number, isInitializing: boolean = false) {
         const countBuffer = Buffer.alloc(4); // u32 is 4 bytes
         countBuffer.writeUInt32LE(count);
@@ -53,13 +111,55 @@ describe("pump", () => {
 
         console.log("user:", user.publicKey.toBase58());
 
-        programAccount = anchor.web3.Keypair.generate();
+        [programAccount] = await anchor.web3.PublicKey.findProgramAddress(
+            [Buffer.from("global")],
+            program.programId
+        );
 
-        await provider.connection.confirmTransaction(
-            await provider.connection.requestAirdrop(programAccount.publicKey, anchor.web3.LAMPORTS_PER_SOL),
-            "confirmed"
+        [eventAuthority] = await anchor.web3.PublicKey.findProgramAddress(
+            [Buffer.from("__event_authority")],
+            program.programId
         );
 
+        // The wallet deployed the program, so it is the upgrade authority
+        // `initializeConfig` asks for. A small target keeps the finalize test
+        // cheap to fill.
+        [config] = anchor.web3.PublicKey.findProgramAddressSync([Buffer.from("config")], program.programId);
+        if (!(await provider.connection.getAccountInfo(config))) {
+            const [programData] = anchor.web3.PublicKey.findProgramAddressSync(
+                [program.programId.toBuffer()],
+                BPF_LOADER_UPGRADEABLE_ID
+            );
+            await program.methods
+                .initializeConfig({
+                    launchFee: new anchor.BN(anchor.web3.LAMPORTS_PER_SOL / 10),
+                    finalizeFeeBps: 500,
+                    feeRecipient: anchor.web3.Keypair.generate().publicKey,
+                    crankReward: new anchor.BN(5_000_000),
+                    target: new anchor.BN(8 * anchor.web3.LAMPORTS_PER_SOL),
+                    maxContribution: new anchor.BN(5 * anchor.web3.LAMPORTS_PER_SOL),
+                    maxUnprovenValuation: new anchor.BN(0),
+                    refundDelay: new anchor.BN(7 * 24 * 60 * 60),
+                    maxDeadlineExtension: new anchor.BN(0),
+                    cancelRebateBps: 0,
+                    withdrawPenaltyBps: 0,
+                    withdrawPenaltyRecipient: { treasury: {} },
+                    bondSlashBps: 5_000,
+                    finalizeGracePeriod: new anchor.BN(3 * 24 * 60 * 60),
+                    configDelay: new anchor.BN(24 * 60 * 60),
+                    randomnessProgram: anchor.web3.PublicKey.default,
+                })
+                .accounts({
+                    config,
+                    admin: user.publicKey,
+                    program: program.programId,
+                    programData,
+                    systemProgram: SystemProgram.programId,
+                })
+                .signers([user])
+                .rpc();
+        }
+
        // Generate unique ticker for each test run
        ticker = `MTK${Math.floor(Math.random() * 1000000)}`;
         console.log("Using ticker:", ticker);
@@ -79,7 +179,7 @@ describe("pump", () => {
 
     it("Initializes the token", async () => {
         const initialTarget = 5 * anchor.web3.LAMPORTS_PER_SOL; 
-        const totalSupply = 1000 ;
+        const totalSupply = 1000 * 1_000_000_000;
 
         const [userContribution] = await deriveUserContributionPDA(0, true);
 
@@ -90,13 +190,18 @@ describe("pump", () => {
 
 
         await program.methods
-            .initialize("MyToken", ticker, new anchor.BN(totalSupply), new anchor.BN(initialTarget), 10)
+            .initialize("MyToken", ticker, new anchor.BN(totalSupply), new anchor.BN(initialTarget), 9, { standard: {} }, {
+                linear: { basePrice: new anchor.BN("20000000"), slope: new anchor.BN(0) },
+            }, new anchor.BN(0))
             .accounts({
                 token: token,
                 user: user.publicKey,
-                programAccount: programAccount.publicKey,
+                programAccount: programAccount,
                 userContribution: userContribution,
+                tickerRecord: deriveTickerRecordPDA(ticker),
                 systemProgram: SystemProgram.programId,
+                eventAuthority,
+                program: program.programId,
             })
             .signers([user])
             .rpc();
@@ -128,9 +233,11 @@ describe("pump", () => {
             .accounts({
                 token: token,
                 user: user.publicKey,
-                programAccount: programAccount.publicKey,
+                programAccount: programAccount,
                 userContribution: userContribution,
                 systemProgram: SystemProgram.programId,
+                eventAuthority,
+                program: program.programId,
             })
             .signers([user])
             .rpc();
@@ -152,10 +259,15 @@ describe("pump", () => {
                 .refund()
                 .accounts({
                     token: token,
+                    creatorProfile: deriveCreatorProfilePDA(user.publicKey),
+                    creator: user.publicKey,
+                    tickerRecord: deriveTickerRecordPDA(ticker),
                     userContribution: userContribution,
                     user: user.publicKey,
-                    programAccount: programAccount.publicKey,
+                    programAccount: programAccount,
                     systemProgram: SystemProgram.programId,
+                    eventAuthority,
+                    program: program.programId,
                 })
                 .signers([user])
                 .rpc();
@@ -168,54 +280,239 @@ describe("pump", () => {
         }
     });
 
-    it("Finalizes the token", async () => {
-        const [poolTokenAccount] = await anchor.web3.PublicKey.findProgramAddress(
-            [Buffer.from("pool-token"), token.toBuffer()],
+    it("Commits and reveals a sealed bid", async () => {
+        const bidTicker = `BID${Math.floor(Math.random() * 1000000)}`;
+        const [bidToken] = await anchor.web3.PublicKey.findProgramAddress(
+            [Buffer.from("token"), user.publicKey.toBuffer(), Buffer.from(bidTicker)],
             program.programId
         );
-
-        const [userTokenAccount] = await anchor.web3.PublicKey.findProgramAddress(
-            [Buffer.from("user-token"), user.publicKey.toBuffer(), token.toBuffer()],
+        const [initialContribution] = await anchor.web3.PublicKey.findProgramAddress(
+            [Buffer.from("user-contribution"), user.publicKey.toBuffer(), Buffer.from(bidTicker), Buffer.from([0])],
             program.programId
         );
-        
-        const tokenAccount = await program.account.tokenDetails.fetch(token);
-        const [userContribution] = await deriveUserContributionPDA(tokenAccount.contributionCount - 1, false);
-        
+        const [bidBook] = await anchor.web3.PublicKey.findProgramAddress(
+            [Buffer.from("bid-book"), bidToken.toBuffer()],
+            program.programId
+        );
+        const [bid] = await anchor.web3.PublicKey.findProgramAddress(
+            [Buffer.from("bid"), bidToken.toBuffer(), user.publicKey.toBuffer()],
+            program.programId
+        );
+
+        await program.methods
+            .initialize("BidToken", bidTicker, new anchor.BN(1000 * 1_000_000), new anchor.BN(0), 6, {
+                sealedBid: {
+                    commitDuration: new anchor.BN(3),
+                    revealDuration: new anchor.BN(30),
+                    penaltyBps: 1000,
+                    minPrice: new anchor.BN(1_000_000),
+                    priceStep: new anchor.BN(1_000_000),
+                },
+            }, { linear: { basePrice: new anchor.BN("4000000000000"), slope: new anchor.BN(0) } }, new anchor.BN(0))
+            .accounts({
+                token: bidToken,
+                user: user.publicKey,
+                userContribution: initialContribution,
+                tickerRecord: deriveTickerRecordPDA(bidTicker),
+                systemProgram: SystemProgram.programId,
+                eventAuthority,
+                program: program.programId,
+            })
+            .signers([user])
+            .rpc();
+
+        const amount = new anchor.BN(anchor.web3.LAMPORTS_PER_SOL / 2);
+        const priceLimit = new anchor.BN(3_000_000);
+        const salt = randomBytes(32);
+        const commitment = createHash("sha256")
+            .update(user.publicKey.toBuffer())
+            .update(amount.toArrayLike(Buffer, "le", 8))
+            .update(priceLimit.toArrayLike(Buffer, "le", 8))
+            .update(salt)
+            .digest();
+
+        await program.methods
+            .commitBid([...commitment], new anchor.BN(anchor.web3.LAMPORTS_PER_SOL))
+            .accounts({ token: bidToken, bidBook, bid, user: user.publicKey, eventAuthority, program: program.programId })
+            .signers([user])
+            .rpc();
+
+        await new Promise((resolve) => setTimeout(resolve, 4000));
+
         await program.methods
-            .finalize()
+            .revealBid(amount, priceLimit, [...salt])
+            .accounts({ token: bidToken, bidBook, bid, user: user.publicKey, eventAuthority, program: program.programId })
+            .signers([user])
+            .rpc();
+
+        const bidAccount = await program.account.bidCommitment.fetch(bid);
+        assert.isTrue(bidAccount.revealed);
+        assert.equal(bidAccount.amount.toNumber(), amount.toNumber());
+        assert.equal(bidAccount.tier, 2);
+
+        const book = await program.account.bidBook.fetch(bidBook);
+        assert.equal(book.demand[2].toNumber(), amount.toNumber());
+    });
+
+    it("Finalizes the token", async () => {
+        const { TOKEN_PROGRAM_ID, ASSOCIATED_PROGRAM_ID, associatedAddress } = anchor.utils.token;
+
+        // Fill whatever is left of the target, one capped position at a time.
+        let details = await program.account.tokenDetails.fetch(token);
+        while (details.totalContributed.lt(details.target)) {
+            const left = details.target.sub(details.totalContributed);
+            const amount = anchor.BN.min(left, details.terms.maxContribution);
+            const [userContribution] = await deriveUserContributionPDA(details.contributionCount, false);
+            await program.methods
+                .contribute(amount)
+                .accounts({
+                    token: token,
+                    user: user.publicKey,
+                    programAccount: programAccount,
+                    userContribution: userContribution,
+                    systemProgram: SystemProgram.programId,
+                    eventAuthority,
+                    program: program.programId,
+                })
+                .signers([user])
+                .rpc();
+            details = await program.account.tokenDetails.fetch(token);
+        }
+
+        const configAccount = await program.account.config.fetch(config);
+        const mint = details.mint;
+        const poolTokenAccount = associatedAddress({ mint, owner: programAccount });
+
+        // Every open position pays out to the owner's token account.
+        const userTokens = associatedAddress({ mint, owner: user.publicKey });
+        await provider.sendAndConfirm(
+            new anchor.web3.Transaction().add(createAssociatedTokenAccount(user.publicKey, userTokens, user.publicKey, mint)),
+            [user]
+        );
+        const positions = (await program.account.userContribution.all())
+            .filter(({ account }) => account.token.equals(token) && account.amount.gtn(0) && !account.settled)
+            .map(({ publicKey }) => publicKey);
+
+        const settle = () =>
+            program.methods.finalizeSettle().accounts({
+                token: token,
+                config: config,
+                creatorProfile: deriveCreatorProfilePDA(user.publicKey),
+                creator: user.publicKey,
+                tickerRecord: null,
+                programAccount: programAccount,
+                komWallet: configAccount.params.feeRecipient,
+                cranker: user.publicKey,
+                mint: mint,
+                poolTokenAccount: poolTokenAccount,
+                liquidityPool: null,
+                bidBook: null,
+                lottery: null,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                systemProgram: SystemProgram.programId,
+                eventAuthority,
+                program: program.programId,
+            });
+        for (let start = 0; start < positions.length; start += SETTLE_BATCH) {
+            const batch = positions.slice(start, start + SETTLE_BATCH).flatMap((position) => [
+                { pubkey: position, isSigner: false, isWritable: true },
+                { pubkey: userTokens, isSigner: false, isWritable: true },
+            ]);
+            await settle().remainingAccounts(batch).signers([user]).rpc();
+        }
+        details = await program.account.tokenDetails.fetch(token);
+        assert.deepEqual(details.finalizeStep, { settled: {} });
+
+        // The pool is opened on a market of its own. OpenBook only has to own it.
+        const market = anchor.web3.Keypair.generate();
+        const space = 0;
+        await provider.sendAndConfirm(
+            new anchor.web3.Transaction().add(
+                SystemProgram.createAccount({
+                    fromPubkey: user.publicKey,
+                    newAccountPubkey: market.publicKey,
+                    lamports: await provider.connection.getMinimumBalanceForRentExemption(space),
+                    space,
+                    programId: MARKET_PROGRAM_ID,
+                })
+            ),
+            [user, market]
+        );
+        const lpMint = deriveRaydiumPoolPDA(market.publicKey, "lp_mint_associated_seed");
+
+        await program.methods
+            .finalizeCreatePool()
+            .accounts({
+                token: token,
+                config: config,
+                cranker: user.publicKey,
+                ammProgram: AMM_PROGRAM_ID,
+                amm: deriveRaydiumPoolPDA(market.publicKey, "amm_associated_seed"),
+                ammAuthority: deriveRaydiumPDA("amm authority"),
+                ammOpenOrders: deriveRaydiumPoolPDA(market.publicKey, "open_order_associated_seed"),
+                lpMint: lpMint,
+                coinMint: mint,
+                pcMint: NATIVE_MINT,
+                coinVault: deriveRaydiumPoolPDA(market.publicKey, "coin_vault_associated_seed"),
+                pcVault: deriveRaydiumPoolPDA(market.publicKey, "pc_vault_associated_seed"),
+                targetOrders: deriveRaydiumPoolPDA(market.publicKey, "target_associated_seed"),
+                ammConfig: deriveRaydiumPDA("amm_config_account_seed"),
+                feeDestination: CREATE_POOL_FEE_ID,
+                marketProgram: MARKET_PROGRAM_ID,
+                market: market.publicKey,
+                globalAccount: programAccount,
+                userTokenCoin: poolTokenAccount,
+                userTokenPc: associatedAddress({ mint: NATIVE_MINT, owner: programAccount }),
+                userTokenLp: associatedAddress({ mint: lpMint, owner: programAccount }),
+                liquidityPool: null,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
+                systemProgram: SystemProgram.programId,
+                sysvarRent: anchor.web3.SYSVAR_RENT_PUBKEY,
+                eventAuthority,
+                program: program.programId,
+            })
+            .signers([user])
+            .rpc();
+
+        await program.methods
+            .finalizeLockLp()
+            .accounts({
+                token: token,
+                config: config,
+                cranker: user.publicKey,
+                lpMint: lpMint,
+                globalAccount: programAccount,
+                userTokenLp: associatedAddress({ mint: lpMint, owner: programAccount }),
+                tokenProgram: TOKEN_PROGRAM_ID,
+                systemProgram: SystemProgram.programId,
+                eventAuthority,
+                program: program.programId,
+            })
+            .signers([user])
+            .rpc();
+
+        await program.methods
+            .finalizeComplete()
             .accounts({
                 token: token,
-                programAccount: program.programId,
-                komWallet: user.publicKey,
-                poolTokenAccount: poolTokenAccount,  // Changed
-                userTokenAccount: userTokenAccount,  // Changed
+                config: config,
+                creatorProfile: deriveCreatorProfilePDA(user.publicKey),
+                creator: user.publicKey,
+                cranker: user.publicKey,
+                programAccount: programAccount,
                 systemProgram: SystemProgram.programId,
-                ammProgram: program.programId, 
-                amm: program.programId, 
-                ammAuthority: program.programId,
-                ammOpenOrders: program.programId, 
-                lpMint: program.programId, 
-                coinMint: program.programId, 
-                pcMint: program.programId,
-                coinVault: program.programId,
-                pcVault: program.programId, 
-                targetOrders: program.programId, 
-                ammConfig: program.programId, 
-                feeDestination: program.programId, 
-                marketProgram: program.programId, 
-                market: program.programId, 
-                globalAccount: program.programId,
-                userTokenCoin: program.programId, 
-                userTokenPc: program.programId, 
-                userTokenLp: program.programId,
-                tokenProgram: program.programId, 
-                associatedTokenProgram: program.programId, 
-                sysvarRent: program.programId, 
-                userContributions: userContribution,
-                liquidityPool: program.programId,
+                eventAuthority,
+                program: program.programId,
             })
             .signers([user])
             .rpc();
+
+        details = await program.account.tokenDetails.fetch(token);
+        assert.deepEqual(details.finalizeStep, { complete: {} });
+        assert.isFalse(details.isVirtual);
+        assert.isTrue(details.market.equals(market.publicKey));
+        const lpTokens = await provider.connection.getTokenAccountBalance(associatedAddress({ mint: lpMint, owner: programAccount }));
+        assert.equal(lpTokens.value.amount, "0");
     });
 });
\ No newline at end of file